@@ -8,7 +8,7 @@ use griffin_core::h224::H224;
 use griffin_core::pallas_codec::minicbor;
 use griffin_core::pallas_codec::utils::AnyCbor;
 use griffin_core::pallas_primitives::{Bytes, MaybeIndefArray};
-use griffin_core::types::{Address, AssetName, Datum, Output};
+use griffin_core::types::{Address, AssetName, Datum, DatumOption, Output};
 use griffin_core::uplc::Hash;
 use griffin_core::utxo_set::TransparentUtxoSet;
 use hex_literal::hex;
@@ -75,10 +75,20 @@ fn fetch_utxo_datum() -> Result<Datum, ConfigParsingErrors> {
     let policy_id: H224 = H224::from(Hash::from_str(RAW_AUTHORITIES_POLICY_ID).unwrap());
     let authorities_addr: Address = Address::from(AUTHORITIES_ADDRESS.to_vec());
 
-    let outputs = TransparentUtxoSet::peek_utxos_with_asset(&asset_name, &policy_id);
+    // Two utxos are enough to tell whether the authorities utxo is unique.
+    let outputs: Vec<Output> =
+        TransparentUtxoSet::peek_utxos_with_asset(&asset_name, &policy_id, None, 2)
+            .into_iter()
+            .map(|(_, o)| o)
+            .collect();
     let output = expect_unique(&outputs).unwrap();
     if output.address == authorities_addr {
-        Ok(output.datum_option.clone().expect("Missing Inline Datum"))
+        Ok(output
+            .datum_option
+            .as_ref()
+            .and_then(DatumOption::inline)
+            .cloned()
+            .expect("Missing Inline Datum"))
     } else {
         Err(ConfigParsingErrors::BadAddress)
     }
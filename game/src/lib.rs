@@ -34,6 +34,22 @@ pub enum Command {
     ShowShips(ShowShipsArgs),
 }
 
+impl Command {
+    /// Path to the game parameters, for the commands dealing with game UTxOs.
+    fn params_path(&self) -> Option<&str> {
+        match self {
+            Command::CreateShip(args) => Some(&args.params_path),
+            Command::GatherFuel(args) => Some(&args.params_path),
+            Command::MoveShip(args) => Some(&args.params_path),
+            Command::MineAsteria(args) => Some(&args.params_path),
+            Command::DeployScripts(_) => None,
+            Command::ShowAsteria(args) | Command::ShowPellets(args) | Command::ShowShips(args) => {
+                Some(&args.params_path)
+            }
+        }
+    }
+}
+
 impl GameCommand {
     pub async fn run(&self) -> sc_cli::Result<()> {
         let Context {
@@ -44,6 +60,11 @@ impl GameCommand {
             slot_config,
             ..
         } = Context::<GameCommand>::load_context().await.unwrap();
+        if let Some(GameCommand::Game(cmd)) = &cli.command {
+            if let Some(params_path) = cmd.params_path() {
+                game::watch_game_addresses(&db, params_path).await.unwrap();
+            }
+        }
         match cli.command {
             Some(GameCommand::Game(cmd)) => match cmd {
                 Command::CreateShip(args) => {
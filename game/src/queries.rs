@@ -3,7 +3,7 @@ use anyhow::anyhow;
 use colored::Colorize;
 use gpc_wallet::sync;
 use griffin_core::types::{
-    compute_plutus_v2_script_hash, Address, Datum, PlutusScript, PolicyId, Value,
+    compute_plutus_script_hash, Address, Datum, PlutusScript, PlutusVersion, PolicyId, Value,
 };
 use parity_scale_codec::Decode;
 use sled::Db;
@@ -29,9 +29,11 @@ pub async fn show_asteria(db: &Db, args: ShowAsteriaArgs) -> anyhow::Result<()>
         let (owner_pubkey, value, datum_option) =
             <(Address, Value, Option<Datum>)>::decode(&mut &owner_amount_datum_ivec[..])?;
 
-        let asteria_script: PlutusScript =
-            PlutusScript(hex::decode(asteria_script_hex).expect("Failed to decode asteria script"));
-        let asteria_hash: PolicyId = compute_plutus_v2_script_hash(asteria_script.clone());
+        let asteria_script: PlutusScript = PlutusScript::new(
+            PlutusVersion::V2,
+            hex::decode(asteria_script_hex).expect("Failed to decode asteria script"),
+        );
+        let asteria_hash: PolicyId = compute_plutus_script_hash(asteria_script.clone());
         let asteria_address: Address = Address(
             hex::decode("70".to_owned() + &hex::encode(asteria_hash))
                 .map_err(|e| anyhow!("Failed to decode asteria address: {}", e))?,
@@ -91,9 +93,11 @@ pub async fn show_pellets(db: &Db, args: ShowPelletsArgs) -> anyhow::Result<()>
         let (owner_pubkey, value, datum_option) =
             <(Address, Value, Option<Datum>)>::decode(&mut &owner_amount_datum_ivec[..])?;
 
-        let pellet_script: PlutusScript =
-            PlutusScript(hex::decode(pellet_script_hex).expect("Failed to decode pellet script"));
-        let pellet_hash: PolicyId = compute_plutus_v2_script_hash(pellet_script.clone());
+        let pellet_script: PlutusScript = PlutusScript::new(
+            PlutusVersion::V2,
+            hex::decode(pellet_script_hex).expect("Failed to decode pellet script"),
+        );
+        let pellet_hash: PolicyId = compute_plutus_script_hash(pellet_script.clone());
         let pellet_address: Address = Address(
             hex::decode("70".to_owned() + &hex::encode(pellet_hash))
                 .map_err(|e| anyhow!("Failed to decode pellet address: {}", e))?,
@@ -156,10 +160,11 @@ pub async fn show_ships(db: &Db, args: ShowShipsArgs) -> anyhow::Result<()> {
         let (owner_pubkey, value, datum_option) =
             <(Address, Value, Option<Datum>)>::decode(&mut &owner_amount_datum_ivec[..])?;
 
-        let spacetime_script: PlutusScript = PlutusScript(
+        let spacetime_script: PlutusScript = PlutusScript::new(
+            PlutusVersion::V2,
             hex::decode(spacetime_script_hex).expect("Failed to decode spacetime script"),
         );
-        let spacetime_hash: PolicyId = compute_plutus_v2_script_hash(spacetime_script.clone());
+        let spacetime_hash: PolicyId = compute_plutus_script_hash(spacetime_script.clone());
         let spacetime_address: Address = Address(
             hex::decode("70".to_owned() + &hex::encode(spacetime_hash))
                 .map_err(|e| anyhow!("Failed to decode spacetime address: {}", e))?,
@@ -17,8 +17,8 @@ mod tests {
             },
         },
         types::{
-            Address, AssetName, Datum, Input, Output, PlutusData, PlutusScript, Redeemer,
-            RedeemerTag, Transaction, Value,
+            Address, AssetName, Datum, DatumOption, ExUnits, Input, Output, PlutusData,
+            PlutusScript, PlutusVersion, Redeemer, RedeemerTag, Transaction, Value,
         },
         uplc::tx::{eval_phase_two, ResolvedInput, SlotConfig},
     };
@@ -44,8 +44,10 @@ mod tests {
     fn test_gather_fuel() {
         use griffin_core::pallas_primitives::conway::BoundedBytes;
 
-        let ship_script = PlutusScript(hex::decode(SHIP_SCRIPT_HEX).unwrap());
-        let pellet_script = PlutusScript(hex::decode(PELLET_SCRIPT_HEX).unwrap());
+        let ship_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(SHIP_SCRIPT_HEX).unwrap());
+        let pellet_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(PELLET_SCRIPT_HEX).unwrap());
 
         let fuel_policy = H224::from(
             Hash::from_str("6a25ad5476105ac4a3784769cb93f92fd67a11932ef9a65a61abd1d6").unwrap(),
@@ -132,18 +134,25 @@ mod tests {
                 address: pellet_address.clone(),
                 value: Value::from((314, fuel_policy, fuel_name.clone(), 50))
                     + Value::from((admin_policy, admin_name.clone(), 1)),
-                datum_option: Some(Datum(PlutusData::from(pellet_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(pellet_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: ship_address.clone(),
                 value: Value::from((2000, ship_policy, ship_name.clone(), 1))
                     + Value::from((fuel_policy, fuel_name.clone(), 25)),
-                datum_option: Some(Datum(PlutusData::from(ship_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(ship_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: wallet_address.clone(),
                 value: Value::from((2000, ship_policy, pilot_name.clone(), 1)),
                 datum_option: None,
+                script_ref: None,
             },
         ];
 
@@ -152,13 +161,19 @@ mod tests {
                 address: pellet_address.clone(),
                 value: Value::from((314, fuel_policy, fuel_name.clone(), 30))
                     + Value::from((1, admin_policy, admin_name, 1)),
-                datum_option: Some(Datum(PlutusData::from(pellet_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(pellet_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: ship_address.clone(),
                 value: Value::from((2000, ship_policy, ship_name, 1))
                     + Value::from((fuel_policy, fuel_name.clone(), 45)),
-                datum_option: Some(Datum(PlutusData::from(ship_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(ship_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
         ];
 
@@ -168,7 +183,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -200,6 +215,7 @@ mod tests {
                     .to_vec(),
                 ),
             })),
+            ex_units: ExUnits::default(),
         };
         let ship_redeemer = Redeemer {
             tag: RedeemerTag::Spend,
@@ -221,11 +237,12 @@ mod tests {
                     .to_vec(),
                 ),
             })),
+            ex_units: ExUnits::default(),
         };
         transaction.transaction_witness_set.redeemer = Some(vec![fuel_redeemer, ship_redeemer]);
         transaction.transaction_witness_set.plutus_script = Some(vec![pellet_script, ship_script]);
 
-        let pallas_tx: BabbageTx = <_>::from(transaction.clone());
+        let pallas_tx = BabbageTx::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -256,18 +273,21 @@ mod tests {
             PlutusData as PallasPlutusData, TransactionInput, TransactionOutput,
         };
         use griffin_core::types::{
-            compute_plutus_v2_script_hash, Address, AssetName, Multiasset, Output, PlutusData,
-            PlutusScript, Redeemer, RedeemerTag, Value,
+            compute_plutus_script_hash, Address, AssetName, ExUnits, Multiasset, Output,
+            PlutusData, PlutusScript, PlutusVersion, Redeemer, RedeemerTag, Value,
         };
         use griffin_core::uplc::tx::{eval_phase_two, ResolvedInput};
 
         // PARSE SCRIPTS
-        let spacetime_script = PlutusScript(hex::decode(SHIP_SCRIPT_HEX).unwrap());
-        let spacetime_hash = compute_plutus_v2_script_hash(spacetime_script.clone());
-        let asteria_script = PlutusScript(hex::decode(ASTERIA_SCRIPT_HEX).unwrap());
-        let asteria_hash = compute_plutus_v2_script_hash(asteria_script.clone());
-        let pellet_script = PlutusScript(hex::decode(PELLET_SCRIPT_HEX).unwrap());
-        let pellet_policy = compute_plutus_v2_script_hash(pellet_script.clone());
+        let spacetime_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(SHIP_SCRIPT_HEX).unwrap());
+        let spacetime_hash = compute_plutus_script_hash(spacetime_script.clone());
+        let asteria_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(ASTERIA_SCRIPT_HEX).unwrap());
+        let asteria_hash = compute_plutus_script_hash(asteria_script.clone());
+        let pellet_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(PELLET_SCRIPT_HEX).unwrap());
+        let pellet_policy = compute_plutus_script_hash(pellet_script.clone());
         let admin_policy = H224::from(
             Hash::from_str("516238dd0a79bac4bebe041c44bad8bf880d74720733d2fc0d255d28").unwrap(),
         );
@@ -299,6 +319,7 @@ mod tests {
                     .to_vec(),
                 ),
             })),
+            ex_units: ExUnits::default(),
         };
 
         // BUILD REDEEMERS
@@ -310,6 +331,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
         let ship_burn_redeemer = Redeemer {
             tag: RedeemerTag::Mint,
@@ -319,6 +341,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
         let pellet_redeemer = Redeemer {
             tag: RedeemerTag::Mint,
@@ -328,6 +351,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
 
         // BUILD DATUMS
@@ -383,7 +407,10 @@ mod tests {
                 ),
                 value: Value::Coin(500000000)
                     + Value::from((1, admin_policy, admin_name.clone(), 1)),
-                datum_option: Some(Datum(PlutusData::from(asteria_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(asteria_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -392,7 +419,10 @@ mod tests {
                 value: Value::Coin(314150000)
                     + Value::from((1, spacetime_hash, ship_name.clone(), 1))
                     + Value::from((1, pellet_policy, fuel_name.clone(), 30)),
-                datum_option: Some(Datum(PlutusData::from(ship_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(ship_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -402,6 +432,7 @@ mod tests {
                 value: Value::Coin(314150000)
                     + Value::from((1, spacetime_hash, pilot_name.clone(), 1)),
                 datum_option: None,
+                script_ref: None,
             },
         ];
 
@@ -411,7 +442,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -426,7 +457,10 @@ mod tests {
                 ),
                 value: Value::Coin(300000000)
                     + Value::from((1, admin_policy, admin_name.clone(), 1)),
-                datum_option: Some(Datum(PlutusData::from(asteria_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(asteria_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -435,6 +469,7 @@ mod tests {
                 ),
                 value: Value::Coin(514150000),
                 datum_option: None,
+                script_ref: None,
             },
         ];
 
@@ -453,7 +488,7 @@ mod tests {
         transaction.transaction_witness_set.plutus_script =
             Some(vec![asteria_script, spacetime_script, pellet_script]);
 
-        let pallas_tx: BabbageTx = <_>::from(transaction.clone());
+        let pallas_tx = BabbageTx::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -481,16 +516,18 @@ mod tests {
             PlutusData as PallasPlutusData, TransactionInput, TransactionOutput,
         };
         use griffin_core::types::{
-            compute_plutus_v2_script_hash, Address, AssetName, Multiasset, Output, PlutusData,
-            PlutusScript, Redeemer, RedeemerTag, Value,
+            compute_plutus_script_hash, Address, AssetName, ExUnits, Multiasset, Output,
+            PlutusData, PlutusScript, PlutusVersion, Redeemer, RedeemerTag, Value,
         };
         use griffin_core::uplc::tx::{eval_phase_two, ResolvedInput};
 
         // PARSE SCRIPTS
-        let spacetime_script = PlutusScript(hex::decode(SHIP_SCRIPT_HEX).unwrap());
-        let spacetime_hash = compute_plutus_v2_script_hash(spacetime_script.clone());
-        let pellet_script = PlutusScript(hex::decode(PELLET_SCRIPT_HEX).unwrap());
-        let pellet_policy = compute_plutus_v2_script_hash(pellet_script.clone());
+        let spacetime_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(SHIP_SCRIPT_HEX).unwrap());
+        let spacetime_hash = compute_plutus_script_hash(spacetime_script.clone());
+        let pellet_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(PELLET_SCRIPT_HEX).unwrap());
+        let pellet_policy = compute_plutus_script_hash(pellet_script.clone());
 
         let ship_name = AssetName::from("SHIP0".to_string());
         let pilot_name = AssetName::from("PILOT0".to_string());
@@ -525,6 +562,7 @@ mod tests {
                     .to_vec(),
                 ),
             })),
+            ex_units: ExUnits::default(),
         };
 
         let pellet_redeemer = Redeemer {
@@ -535,6 +573,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
 
         // BUILD DATUMS
@@ -589,7 +628,10 @@ mod tests {
                 value: Value::Coin(314150000)
                     + Value::from((1, spacetime_hash, ship_name.clone(), 1))
                     + Value::from((1, pellet_policy, fuel_name.clone(), 50)),
-                datum_option: Some(Datum(PlutusData::from(in_ship_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(in_ship_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -599,6 +641,7 @@ mod tests {
                 value: Value::Coin(314150000)
                     + Value::from((1, spacetime_hash, pilot_name.clone(), 1)),
                 datum_option: None,
+                script_ref: None,
             },
         ];
 
@@ -608,7 +651,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -621,7 +664,10 @@ mod tests {
             value: Value::Coin(314150000)
                 + Value::from((1, spacetime_hash, ship_name.clone(), 1))
                 + Value::from((1, pellet_policy, fuel_name.clone(), 49)),
-            datum_option: Some(Datum(PlutusData::from(out_ship_datum.clone()).0)),
+            datum_option: Some(DatumOption::Inline(Datum(
+                PlutusData::from(out_ship_datum.clone()).0,
+            ))),
+            script_ref: None,
         }];
 
         for output in outputs {
@@ -635,7 +681,7 @@ mod tests {
         transaction.transaction_witness_set.plutus_script =
             Some(vec![spacetime_script, pellet_script]);
 
-        let pallas_tx: BabbageTx = <_>::from(transaction.clone());
+        let pallas_tx = BabbageTx::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -666,18 +712,21 @@ mod tests {
             PlutusData as PallasPlutusData, TransactionInput, TransactionOutput,
         };
         use griffin_core::types::{
-            compute_plutus_v2_script_hash, Address, AssetName, Multiasset, Output, PlutusData,
-            PlutusScript, Redeemer, RedeemerTag, Value,
+            compute_plutus_script_hash, Address, AssetName, ExUnits, Multiasset, Output,
+            PlutusData, PlutusScript, PlutusVersion, Redeemer, RedeemerTag, Value,
         };
         use griffin_core::uplc::tx::{eval_phase_two, ResolvedInput};
 
         // PARSE SCRIPTS
-        let spacetime_script = PlutusScript(hex::decode(SHIP_SCRIPT_HEX).unwrap());
-        let spacetime_hash = compute_plutus_v2_script_hash(spacetime_script.clone());
-        let asteria_script = PlutusScript(hex::decode(ASTERIA_SCRIPT_HEX).unwrap());
-        let asteria_hash = compute_plutus_v2_script_hash(asteria_script.clone());
-        let pellet_script = PlutusScript(hex::decode(PELLET_SCRIPT_HEX).unwrap());
-        let pellet_policy = compute_plutus_v2_script_hash(pellet_script.clone());
+        let spacetime_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(SHIP_SCRIPT_HEX).unwrap());
+        let spacetime_hash = compute_plutus_script_hash(spacetime_script.clone());
+        let asteria_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(ASTERIA_SCRIPT_HEX).unwrap());
+        let asteria_hash = compute_plutus_script_hash(asteria_script.clone());
+        let pellet_script =
+            PlutusScript::new(PlutusVersion::V2, hex::decode(PELLET_SCRIPT_HEX).unwrap());
+        let pellet_policy = compute_plutus_script_hash(pellet_script.clone());
         let admin_policy = H224::from(
             Hash::from_str("516238dd0a79bac4bebe041c44bad8bf880d74720733d2fc0d255d28").unwrap(),
         );
@@ -704,6 +753,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
         let ship_mint_redeemer = Redeemer {
             tag: RedeemerTag::Mint,
@@ -713,6 +763,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
         let pellet_mint_redeemer = Redeemer {
             tag: RedeemerTag::Mint,
@@ -722,6 +773,7 @@ mod tests {
                 any_constructor: None,
                 fields: Indef([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
 
         // BUILD DATUMS
@@ -776,7 +828,10 @@ mod tests {
         let resolved_inputs = vec![Output {
             address: Address(hex::decode("70".to_owned() + &hex::encode(asteria_hash)).unwrap()),
             value: Value::Coin(500000000) + Value::from((1, admin_policy, admin_name.clone(), 1)),
-            datum_option: Some(Datum(PlutusData::from(asteria_input_datum.clone()).0)),
+            datum_option: Some(DatumOption::Inline(Datum(
+                PlutusData::from(asteria_input_datum.clone()).0,
+            ))),
+            script_ref: None,
         }];
 
         let pallas_inputs = inputs
@@ -785,7 +840,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -800,7 +855,10 @@ mod tests {
                 ),
                 value: Value::Coin(503000000)
                     + Value::from((1, admin_policy, admin_name.clone(), 1)),
-                datum_option: Some(Datum(PlutusData::from(asteria_output_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(asteria_output_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -809,7 +867,10 @@ mod tests {
                 value: Value::Coin(314150000)
                     + Value::from((1, spacetime_hash, ship_name.clone(), 1))
                     + Value::from((1, pellet_policy, fuel_name.clone(), 30)),
-                datum_option: Some(Datum(PlutusData::from(ship_datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(
+                    PlutusData::from(ship_datum.clone()).0,
+                ))),
+                script_ref: None,
             },
         ];
 
@@ -828,7 +889,7 @@ mod tests {
         transaction.transaction_witness_set.plutus_script =
             Some(vec![asteria_script, spacetime_script, pellet_script]);
 
-        let pallas_tx: BabbageTx = <_>::from(transaction.clone());
+        let pallas_tx = BabbageTx::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
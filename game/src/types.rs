@@ -47,7 +47,8 @@ impl From<AsteriaDatum> for Datum {
 
 impl From<Datum> for AsteriaDatum {
     fn from(datum: Datum) -> Self {
-        <_>::from(PallasPlutusData::from(PlutusData(datum.0)))
+        PallasPlutusData::try_from(PlutusData(datum.0))
+            .map_or(AsteriaDatum::MalformedAsteriaDatum, <_>::from)
     }
 }
 
@@ -119,7 +120,8 @@ impl From<PelletDatum> for Datum {
 
 impl From<Datum> for PelletDatum {
     fn from(datum: Datum) -> Self {
-        <_>::from(PallasPlutusData::from(PlutusData(datum.0)))
+        PallasPlutusData::try_from(PlutusData(datum.0))
+            .map_or(PelletDatum::MalformedPelletDatum, <_>::from)
     }
 }
 
@@ -198,7 +200,8 @@ impl From<ShipDatum> for Datum {
 
 impl From<Datum> for ShipDatum {
     fn from(datum: Datum) -> Self {
-        <_>::from(PallasPlutusData::from(PlutusData(datum.0)))
+        PallasPlutusData::try_from(PlutusData(datum.0))
+            .map_or(ShipDatum::MalformedShipDatum, <_>::from)
     }
 }
 
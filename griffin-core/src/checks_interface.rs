@@ -3,29 +3,31 @@
 //!
 //! Some of these functions were brought directly from Pallas since they belong
 //! to its test suit.
-use crate::pallas_applying::{utils::ValidationError, UTxOs};
+use crate::pallas_applying::{
+    utils::{BabbageProtParams, ValidationError},
+    UTxOs,
+};
 use crate::pallas_codec::minicbor::{decode::Error, encode};
 use crate::pallas_codec::utils::{Bytes, CborWrap};
+use crate::pallas_crypto::hash::Hash as PallasHash;
 use crate::pallas_primitives::{
-    alonzo::Value,
+    alonzo::{ExUnitPrices, ExUnits, Nonce, NonceVariant, RationalNumber, Value},
     babbage::{
-        MintedDatumOption, MintedPostAlonzoTransactionOutput, MintedScriptRef,
-        MintedTransactionBody, MintedTransactionOutput, MintedTx as BabbageMintedTx,
-        PseudoTransactionOutput, Tx as BabbageTx,
+        CostModels, MintedDatumOption, MintedPostAlonzoTransactionOutput, MintedScriptRef,
+        MintedTransactionBody, MintedTransactionOutput, MintedTx as BabbageMintedTx, PlutusScript,
+        PseudoDatumOption, PseudoTransactionOutput, TransactionInput, Tx as BabbageTx,
     },
     conway::MintedTx as ConwayMintedTx,
 };
 use crate::pallas_traverse::{MultiEraInput, MultiEraOutput};
+use crate::protocol_parameters::ProtocolParameters;
 use crate::types::{
-    value_leq, DispatchResult,
+    value_leq, Coin, DatumOption, DispatchResult, Output, PlutusVersion,
     UTxOError::{self, *},
 };
 use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
 use core::iter::zip;
 
-/// Every output must contain this many `Coin`s.
-pub const MIN_COIN_PER_OUTPUT: crate::types::Coin = 10;
-
 impl From<ValidationError> for UTxOError {
     /// Translation of Cardano's Babbage era errors to Griffin's.
     fn from(err: ValidationError) -> UTxOError {
@@ -36,6 +38,52 @@ impl From<ValidationError> for UTxOError {
     }
 }
 
+/// Babbage protocol parameters used to run Pallas checks on Griffin
+/// transactions, taken from the Griffin ones. Fields with no Griffin
+/// counterpart hold neutral values.
+pub fn mk_babbage_prot_params(pps: &ProtocolParameters) -> BabbageProtParams {
+    let zero = || RationalNumber {
+        numerator: 0,
+        denominator: 1,
+    };
+
+    BabbageProtParams {
+        minfee_a: pps.min_fee_a,
+        minfee_b: pps.min_fee_b,
+        max_block_body_size: u32::MAX,
+        max_transaction_size: pps.max_tx_size,
+        max_block_header_size: u32::MAX,
+        key_deposit: 0,
+        pool_deposit: 0,
+        desired_number_of_stake_pools: 0,
+        protocol_version: (8, 0),
+        min_pool_cost: 0,
+        ada_per_utxo_byte: pps.coins_per_utxo_word,
+        cost_models_for_script_languages: CostModels {
+            plutus_v1: pps.cost_models.plutus_v1.clone(),
+            plutus_v2: pps.cost_models.plutus_v2.clone(),
+        },
+        execution_costs: ExUnitPrices {
+            mem_price: zero(),
+            step_price: zero(),
+        },
+        max_tx_ex_units: ExUnits::from(pps.max_tx_ex_units.clone()),
+        max_block_ex_units: ExUnits::from(pps.max_block_ex_units.clone()),
+        max_value_size: pps.max_value_size,
+        collateral_percentage: pps.collateral_percentage,
+        max_collateral_inputs: pps.max_collateral_inputs,
+        expansion_rate: zero(),
+        treasury_growth_rate: zero(),
+        maximum_epoch: 0,
+        pool_pledge_influence: zero(),
+        decentralization_constant: zero(),
+        extra_entropy: Nonce {
+            variant: NonceVariant::NeutralNonce,
+            hash: None,
+        },
+    }
+}
+
 pub fn babbage_tx_to_cbor(tx: &BabbageTx) -> Vec<u8> {
     let mut tx_buf: Vec<u8> = Vec::new();
     let _ = encode(tx, &mut tx_buf);
@@ -67,7 +115,41 @@ pub fn mk_utxo_for_babbage_tx<'a>(
     tx_outs_info: &OutputInfoList<'a>,
 ) -> UTxOs<'a> {
     let mut utxos: UTxOs = UTxOs::new();
-    for (tx_in, (addr, val, datum_opt, script_ref)) in zip(tx_body.inputs.clone(), tx_outs_info) {
+    insert_utxos(&mut utxos, tx_body.inputs.clone(), tx_outs_info);
+
+    utxos
+}
+
+/// Adds the outputs resolved by the reference inputs of a transaction to its
+/// local UTxO set. `ref_input_info` must follow the order of `reference_inputs`.
+pub fn add_ref_input_babbage<'a>(
+    tx_body: &MintedTransactionBody,
+    utxos: &mut UTxOs<'a>,
+    ref_input_info: &OutputInfoList<'a>,
+) {
+    if let Some(ref_inputs) = &tx_body.reference_inputs {
+        insert_utxos(utxos, ref_inputs.clone(), ref_input_info);
+    }
+}
+
+/// Adds the outputs resolved by the collateral inputs of a transaction to its
+/// local UTxO set. `collateral_info` must follow the order of `collateral`.
+pub fn add_collateral_babbage<'a>(
+    tx_body: &MintedTransactionBody,
+    utxos: &mut UTxOs<'a>,
+    collateral_info: &OutputInfoList<'a>,
+) {
+    if let Some(collaterals) = &tx_body.collateral {
+        insert_utxos(utxos, collaterals.clone(), collateral_info);
+    }
+}
+
+fn insert_utxos<'a>(
+    utxos: &mut UTxOs<'a>,
+    tx_ins: Vec<TransactionInput>,
+    tx_outs_info: &OutputInfoList<'a>,
+) {
+    for (tx_in, (addr, val, datum_opt, script_ref)) in zip(tx_ins, tx_outs_info) {
         let multi_era_in: MultiEraInput =
             MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(tx_in)));
         let address_bytes: Bytes = match hex::decode(addr) {
@@ -84,16 +166,40 @@ pub fn mk_utxo_for_babbage_tx<'a>(
         let multi_era_out: MultiEraOutput = MultiEraOutput::Babbage(Box::new(Cow::Owned(tx_out)));
         utxos.insert(multi_era_in, multi_era_out);
     }
+}
 
-    utxos
+/// Reference script of a Griffin output, in the form expected by the local
+/// UTxO set.
+pub fn mk_minted_script_ref<'a>(output: &Output) -> Option<CborWrap<MintedScriptRef<'a>>> {
+    output.script_ref.as_ref().map(|script| {
+        let bytes = Bytes::from(script.bytes.clone());
+        CborWrap(match script.version {
+            PlutusVersion::V1 => MintedScriptRef::PlutusV1Script(PlutusScript::<1>(bytes)),
+            PlutusVersion::V2 => MintedScriptRef::PlutusV2Script(PlutusScript::<2>(bytes)),
+            PlutusVersion::V3 => MintedScriptRef::PlutusV3Script(PlutusScript::<3>(bytes)),
+        })
+    })
+}
+
+/// Datum hash of an output, as needed by phase one checks. Inline datums are
+/// left out since these checks only look for the witness-set datums of
+/// hashed ones.
+pub fn mk_minted_datum_hash<'a>(output: &Output) -> Option<MintedDatumOption<'a>> {
+    match output.datum_option {
+        Some(DatumOption::Hash(hash)) => Some(PseudoDatumOption::Hash(PallasHash::<32>::from(
+            hash.as_bytes(),
+        ))),
+        _ => None,
+    }
 }
 
-pub fn check_min_coin(tx_body: &MintedTransactionBody) -> DispatchResult {
+/// Every output must contain at least `min_coin` `Coin`s.
+pub fn check_min_coin(tx_body: &MintedTransactionBody, min_coin: Coin) -> DispatchResult {
     use crate::pallas_applying::utils::BabbageError::MinLovelaceUnreached;
 
     let min_reached: bool = tx_body.outputs.iter().all(|out| {
         value_leq(
-            &crate::types::Value::Coin(MIN_COIN_PER_OUTPUT),
+            &crate::types::Value::Coin(min_coin),
             &<_>::from(match out {
                 PseudoTransactionOutput::PostAlonzo(pos) => pos.value.clone(),
                 _ => return false, // Legacy outputs should not be here!
@@ -0,0 +1,151 @@
+//! Committee of block producers and finality voters.
+//!
+//! The committee of each epoch is selected during the previous one, from the
+//! candidates registered on Cardano and the D-parameter, by the Ariadne
+//! algorithm. A block makes the selection by its committee inherent (see
+//! [`NextCommittee::to_inherent`]), built from the selection inputs its author
+//! provides as inherent data and checked by importing nodes against their own.
+//! The selection is announced in the header of the block (see
+//! [`COMMITTEE_ENGINE_ID`]) and takes over at the first block of its epoch,
+//! which schedules the corresponding GRANDPA authority set change. When no
+//! committee was selected for an epoch, the current one stays in office.
+//!
+//! Chains without a committee selection configuration keep their genesis
+//! committee forever.
+
+use crate::types::{AuxiliaryData, Transaction};
+use authority_selection_inherents::AuthoritySelectionInputs;
+use parity_scale_codec::{Decode, DecodeAll, DecodeWithMemTracking, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sidechain_domain::{MainchainAddress, PolicyId, UtxoId};
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_consensus_grandpa::{AuthorityId as GrandpaId, SetId};
+use sp_runtime::ConsensusEngineId;
+
+use alloc::vec::Vec;
+
+/// Engine ID of the digest log carrying the [`NextCommittee`] selected by a
+/// block.
+pub const COMMITTEE_ENGINE_ID: ConsensusEngineId = *b"grcm";
+
+/// Keys of a committee member.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct CommitteeMember {
+    pub aura: AuraId,
+    pub grandpa: GrandpaId,
+}
+
+/// Committee in office.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct Committee {
+    /// Epoch since which the committee is in office.
+    pub epoch: u64,
+    /// GRANDPA authority set ID of the committee.
+    pub set_id: SetId,
+    pub members: Vec<CommitteeMember>,
+}
+
+/// Committee selected for the next epoch.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct NextCommittee {
+    pub epoch: u64,
+    pub members: Vec<CommitteeMember>,
+}
+
+impl NextCommittee {
+    /// The inherent extrinsic selecting this committee: a transaction with
+    /// neither inputs nor outputs carrying the committee as auxiliary data.
+    /// Users cannot submit such transactions, since the pool rejects those
+    /// without inputs.
+    pub fn to_inherent(&self) -> Transaction {
+        Transaction {
+            auxiliary_data: Some(AuxiliaryData(self.encode())),
+            ..Transaction::default()
+        }
+    }
+
+    /// The committee selected by `transaction`, if it is a committee inherent.
+    pub fn from_inherent(transaction: &Transaction) -> Option<Self> {
+        let data = &transaction.auxiliary_data.as_ref()?.0;
+        let next = Self::decode_all(&mut &data[..]).ok()?;
+
+        (next.to_inherent() == *transaction).then_some(next)
+    }
+}
+
+/// Cardano data identifying the candidates of a chain.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct CommitteeSelectionConfig {
+    /// UTxO identifying the chain on Cardano, signed by candidate registrations.
+    pub genesis_utxo: UtxoId,
+    /// Address holding the registrations of candidates.
+    pub committee_candidate_address: MainchainAddress,
+    /// Policy of the token marking the D-parameter UTxO.
+    pub d_parameter_policy_id: PolicyId,
+    /// Policy of the token marking the permissioned candidates UTxO.
+    pub permissioned_candidates_policy_id: PolicyId,
+}
+
+mod api_declarations {
+    use super::*;
+    sp_api::decl_runtime_apis! {
+        pub trait CommitteeApi {
+            fn current_committee() -> Option<Committee>;
+            fn next_committee() -> Option<NextCommittee>;
+            fn committee_selection() -> Option<CommitteeSelectionConfig>;
+            /// Epoch whose committee is due to be selected, if any.
+            fn next_unset_epoch() -> Option<u64>;
+            /// Committee selected for `epoch` from the given inputs.
+            fn calculate_committee(
+                inputs: AuthoritySelectionInputs,
+                epoch: u64,
+            ) -> Option<Vec<CommitteeMember>>;
+        }
+    }
+}
+pub use api_declarations::*;
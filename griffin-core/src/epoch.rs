@@ -0,0 +1,58 @@
+//! Sidechain slots and epochs.
+//!
+//! The slot of a block is the ledger slot in progress when its Aura slot
+//! starts, so that validity intervals and the time ranges of script contexts
+//! follow the wall clock even if slots are missed. Epochs are spans of
+//! `slots_per_epoch` slots, numbered from slot zero as in `sidechain-slots`.
+//! The epoch of the latest block is persisted in state, and the first block of
+//! every epoch runs the epoch change hook of the
+//! [`Executive`](crate::Executive), which applies protocol parameter updates
+//! and rotates the committee.
+
+use crate::protocol_parameters::ProtocolParameters;
+use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// Position of the latest block in time.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct EpochInfo {
+    pub epoch: u64,
+    pub slot: u64,
+}
+
+/// The ledger slot in progress when Aura slot `aura_slot` starts. Aura slots
+/// are numbered from the UNIX epoch, while ledger slots are numbered from
+/// `zero_slot`, which starts at `zero_time`.
+pub fn ledger_slot(aura_slot: u64, pps: &ProtocolParameters) -> u64 {
+    let slot_length = pps.slot_length as u64;
+    let time = aura_slot.saturating_mul(slot_length);
+
+    pps.zero_slot + time.saturating_sub(pps.zero_time) / slot_length
+}
+
+/// The epoch `slot` belongs to, or `None` if `slots_per_epoch` is zero.
+pub fn epoch_of(slot: u64, slots_per_epoch: u64) -> Option<u64> {
+    slot.checked_div(slots_per_epoch)
+}
+
+mod api_declarations {
+    use super::*;
+    sp_api::decl_runtime_apis! {
+        pub trait EpochApi {
+            fn current_epoch() -> EpochInfo;
+        }
+    }
+}
+pub use api_declarations::*;
@@ -0,0 +1,111 @@
+//! Dry runs of Griffin transactions.
+//!
+//! A transaction can be evaluated against the current state without being
+//! submitted, to learn the execution units spent by its scripts, the fee it
+//! must pay and the error it would be rejected with, if any.
+
+use crate::types::{Coin, ExUnits, Redeemer, RedeemerTag, UTxOError};
+use alloc::{format, string::String, vec::Vec};
+use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// Execution units spent by the script run by a redeemer.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct EvaluatedRedeemer {
+    /// Purpose of the redeemer (`"Spend"`, `"Mint"` or `"Withdraw"`, the
+    /// latter for `RedeemerTag::Reward`).
+    pub tag: String,
+    /// Index of the redeemed input, policy or reward account, as in the
+    /// redeemer.
+    pub index: u32,
+    pub ex_units: ExUnits,
+}
+
+impl EvaluatedRedeemer {
+    /// Whether this is the evaluation of `redeemer`.
+    pub fn evaluates(&self, redeemer: &Redeemer) -> bool {
+        let tag = match redeemer.tag {
+            RedeemerTag::Spend => "Spend",
+            RedeemerTag::Mint => "Mint",
+            RedeemerTag::Reward => "Withdraw",
+        };
+        self.tag == tag && self.index == redeemer.index
+    }
+}
+
+/// Outcome of evaluating a transaction.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct TxEvaluation {
+    /// Execution units spent by each redeemer, identified by its tag and index.
+    pub redeemers: Vec<EvaluatedRedeemer>,
+    /// Minimum fee of the transaction, given its size.
+    pub min_fee: Coin,
+    /// Error the transaction would be rejected with, if any.
+    pub error: Option<EvaluationError>,
+}
+
+/// Encodable description of a `UTxOError`.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct EvaluationError {
+    /// Stable code of the error (see `UTxOError::code`).
+    pub code: u8,
+    /// Debug representation of the error.
+    pub message: String,
+    /// Trace logs of the failing script, for phase two errors.
+    pub traces: Vec<String>,
+}
+
+impl From<UTxOError> for EvaluationError {
+    fn from(err: UTxOError) -> Self {
+        EvaluationError {
+            code: err.code(),
+            message: format!("{:?}", err),
+            traces: err.traces(),
+        }
+    }
+}
+
+mod api_declarations {
+    use super::*;
+    use crate::types::Transaction;
+    sp_api::decl_runtime_apis! {
+        pub trait TransactionEvaluationApi {
+            fn evaluate_transaction(transaction: Transaction) -> TxEvaluation;
+        }
+    }
+}
+pub use api_declarations::*;
@@ -5,15 +5,24 @@
 //!
 //! It does all the reusable verification of UTXO transactions.
 
+use crate::pallas_addresses::Address as PallasAddress;
 use crate::pallas_applying::{
     babbage::{
+        check_auxiliary_data,
+        check_collaterals,
+        check_fee,
         check_ins_not_empty,
         // check_all_ins_in_utxos,
+        check_min_fee,
+        check_min_lovelace,
+        check_output_val_size,
         check_preservation_of_value,
+        check_tx_ex_units,
+        check_tx_size,
         check_tx_validity_interval,
         check_witness_set,
     },
-    utils::BabbageError::*,
+    utils::{get_babbage_tx_size, BabbageError::*},
     UTxOs,
 };
 use crate::pallas_codec::utils::CborWrap;
@@ -22,31 +31,57 @@ use crate::pallas_primitives::{
         MintedDatumOption, MintedScriptRef, MintedTransactionBody, MintedTx,
         Tx as PallasTransaction, Value as PallasValue,
     },
-    conway::{MintedTx as ConwayMintedTx, TransactionOutput},
+    conway::{
+        CostModels as ConwayCostModels, ExUnits as ConwayExUnits, MintedTx as ConwayMintedTx,
+        Redeemer as ConwayRedeemer, TransactionOutput,
+    },
+};
+use crate::uplc::{
+    machine::{self, cost_model::ExBudget},
+    tx::{
+        error::Error as PhaseTwoError, eval::eval_redeemer, iter_redeemers, redeemer_tag_to_string,
+        DataLookupTable, ResolvedInput, SlotConfig,
+    },
 };
-use crate::uplc::tx::{eval_phase_two, ResolvedInput, SlotConfig};
 use crate::{
     checks_interface::{
-        babbage_minted_tx_from_cbor, babbage_tx_to_cbor, check_min_coin,
-        conway_minted_tx_from_cbor, mk_utxo_for_babbage_tx,
+        add_collateral_babbage, add_ref_input_babbage, babbage_minted_tx_from_cbor,
+        babbage_tx_to_cbor, check_min_coin, conway_minted_tx_from_cbor, mk_babbage_prot_params,
+        mk_minted_datum_hash, mk_minted_script_ref, mk_utxo_for_babbage_tx,
     },
+    committee::{Committee, CommitteeSelectionConfig, NextCommittee, COMMITTEE_ENGINE_ID},
     ensure,
-    header::ExtendedHeader,
-    types::{Block, BlockNumber, DispatchResult, Header, Input, Output, Transaction, UTxOError},
+    epoch::{epoch_of, ledger_slot, EpochInfo},
+    evaluation::{EvaluatedRedeemer, TxEvaluation},
+    header::{ExtendedHeader, PCData},
+    main_chain::mc_hash_from_digest,
+    pallas_interface::PallasConversionError,
+    protocol_parameters::{
+        fetch_pending_update, AppliedUpdate, GovernanceToken, PendingUpdate, ProtocolParameters,
+    },
+    reward_accounts::RewardAccounts,
+    types::{
+        Block, BlockError, BlockNumber, Coin, CostModels, DispatchResult, ExUnits, Header, Input,
+        Output, Transaction, UTxOError,
+    },
     utxo_set::TransparentUtxoSet,
-    DATA_KEY, EXTRINSIC_KEY, HEADER_KEY, HEIGHT_KEY, LOG_TARGET,
+    BLOCK_EX_UNITS, COMMITTEE_SELECTION, CURRENT_COMMITTEE, DATA_KEY, EPOCH_KEY, EXTRINSIC_KEY,
+    GOVERNANCE_TOKEN, HEADER_KEY, HEIGHT_KEY, LAST_PARAMETERS_UPDATE, LOGS_KEY, LOG_TARGET,
+    NEXT_COMMITTEE, PROTOCOL_PARAMETERS, REJECTED_BLOCK_KEY, SLOT_KEY,
 };
-use crate::{SLOT_LENGTH, ZERO_SLOT, ZERO_TIME};
 use alloc::{collections::btree_set::BTreeSet, string::String, vec::Vec};
 use log::debug;
 use parity_scale_codec::{Decode, Encode};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_consensus_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_core::H256;
 use sp_runtime::{
     traits::{BlakeTwo256, Block as BlockT, ExtrinsicLike, Hash as HashT, Header as HeaderT},
     transaction_validity::{
-        TransactionLongevity, TransactionSource, TransactionValidity, TransactionValidityError,
-        ValidTransaction,
+        InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionSource,
+        TransactionValidity, TransactionValidityError, ValidTransaction,
     },
-    ApplyExtrinsicResult, ExtrinsicInclusionMode, StateVersion,
+    ApplyExtrinsicResult, DigestItem, ExtrinsicInclusionMode, StateVersion,
 };
 
 type OutputInfoList<'a> = Vec<(
@@ -56,6 +91,11 @@ type OutputInfoList<'a> = Vec<(
     Option<CborWrap<MintedScriptRef<'a>>>,
 )>;
 
+/// Fees are scaled by this factor before being divided by the transaction size
+/// to obtain its pool priority, so that small differences in fee-per-byte are
+/// not lost to integer division.
+const FEE_PRIORITY_SCALE: u64 = 1_000;
+
 /// The executive is in charge of validating transactions for admittance in the
 /// pool and in blocks. It is in charge of *executing* transactions, i.e.,
 /// applying them to the ledger.
@@ -79,50 +119,274 @@ where
         let tx_body: &MintedTransactionBody = &mtx.transaction_body.clone();
         // Next unneeded since already checked at `apply_griffin_transaction`
         // check_all_ins_in_utxos(tx_body, utxos)?;
-        let current_slot = Self::zero_slot() + (Self::block_height() as u64);
+        let pps = Self::protocol_parameters().ok_or(UTxOError::Fail)?;
+        let current_slot = Self::current_slot();
         check_tx_validity_interval(tx_body, &current_slot)?;
+        let size: u32 = get_babbage_tx_size(mtx).ok_or(UTxOError::Babbage(UnknownTxSize))?;
+        let prot_pps = mk_babbage_prot_params(&pps);
+        check_tx_size(&size, &prot_pps)?;
+        // Collateral is mandatory for script transactions only if the chain
+        // demands a collateral percentage, but it is always checked if present.
+        if prot_pps.collateral_percentage > 0 {
+            check_fee(tx_body, &size, mtx, utxos, &prot_pps)?;
+        } else {
+            check_min_fee(tx_body, &size, &prot_pps)?;
+            if tx_body.collateral.is_some() {
+                check_collaterals(tx_body, utxos, &prot_pps)?;
+            }
+        }
         check_preservation_of_value(tx_body, utxos)?;
+        check_tx_ex_units(mtx, &prot_pps)?;
         check_witness_set(mtx, utxos)?;
-        check_min_coin(tx_body)?;
+        check_auxiliary_data(tx_body, mtx)?;
+        check_min_coin(tx_body, pps.min_coin_per_output)?;
+        check_min_lovelace(tx_body, &prot_pps)?;
+        check_output_val_size(tx_body, &prot_pps)?;
 
         Ok(())
     }
 
-    fn phase_two_checks(tx_cbor_bytes: &[u8], input_utxos: Vec<Output>) -> DispatchResult {
-        let conway_mtx: ConwayMintedTx = conway_minted_tx_from_cbor(tx_cbor_bytes);
-        let pallas_input_utxos = input_utxos
-            .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
-            .collect::<Vec<_>>();
-        let pallas_resolved_inputs: Vec<ResolvedInput> = conway_mtx
-            .transaction_body
-            .inputs
-            .iter()
-            .zip(pallas_input_utxos.iter())
-            .map(|(input, output)| ResolvedInput {
-                input: input.clone(),
-                output: output.clone(),
+    /// Checks that every account withdrawn from is a well-formed reward
+    /// account, which scripts need in order to be run.
+    fn check_reward_accounts(transaction: &Transaction) -> DispatchResult {
+        for (account, _) in transaction.transaction_body.withdrawals.iter().flatten() {
+            ensure!(
+                matches!(
+                    PallasAddress::from_bytes(&account.0),
+                    Ok(PallasAddress::Stake(_))
+                ),
+                UTxOError::Babbage(AddressDecoding)
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that withdrawals are made from well-formed reward accounts, each
+    /// of them at most once and draining its whole balance.
+    fn check_withdrawals(transaction: &Transaction) -> DispatchResult {
+        Self::check_reward_accounts(transaction)?;
+        let mut accounts = BTreeSet::new();
+        for (account, amount) in transaction.transaction_body.withdrawals.iter().flatten() {
+            ensure!(
+                accounts.insert(&account.0),
+                UTxOError::Babbage(DuplicateWithdrawal)
+            );
+            ensure!(
+                RewardAccounts::balance(account) == *amount,
+                UTxOError::Babbage(IncompleteWithdrawal)
+            );
+        }
+        Ok(())
+    }
+
+    /// Pool priority of a transaction, given by the fee it pays per byte.
+    /// Transactions paying more per byte are included first, so that cheap
+    /// spam cannot starve fee-paying transactions.
+    fn fee_priority(fee: Coin, mtx: &MintedTx) -> TransactionPriority {
+        let size = get_babbage_tx_size(mtx).unwrap_or(u32::MAX).max(1) as u64;
+
+        fee.saturating_mul(FEE_PRIORITY_SCALE) / size
+    }
+
+    /// Runs the Plutus script of every redeemer of the transaction, each with
+    /// the budget that `budget` gives for the execution units declared by the
+    /// redeemer, returning the redeemers with the execution units actually
+    /// spent. `resolved_inputs` must contain the outputs resolved by both the
+    /// inputs and the reference inputs.
+    fn eval_redeemers(
+        conway_mtx: &ConwayMintedTx,
+        resolved_inputs: Vec<(Input, Output)>,
+        budget: impl Fn(ConwayExUnits) -> ExBudget,
+    ) -> Result<Vec<ConwayRedeemer>, UTxOError> {
+        let pallas_resolved_inputs: Vec<ResolvedInput> = resolved_inputs
+            .into_iter()
+            .map(|(input, output)| {
+                Ok(ResolvedInput {
+                    input: <_>::from(input),
+                    output: TransactionOutput::try_from(output)?,
+                })
             })
-            .collect();
+            .collect::<Result<_, PallasConversionError>>()?;
 
+        let pps = Self::protocol_parameters().ok_or(UTxOError::Fail)?;
         let slot_config = SlotConfig {
-            zero_time: Self::zero_time(),
-            zero_slot: Self::zero_slot(),
-            slot_length: Self::slot_length(),
+            zero_time: pps.zero_time,
+            zero_slot: pps.zero_slot,
+            slot_length: pps.slot_length,
         };
-        let phase_two_result = eval_phase_two(
-            &conway_mtx,
-            &pallas_resolved_inputs,
-            None,
-            None,
-            &slot_config,
-            false,
-            |_| (),
-        );
+        // Without cost models, every language is evaluated with the default costs.
+        let cost_mdls: Option<ConwayCostModels> =
+            (pps.cost_models != CostModels::default()).then(|| <_>::from(pps.cost_models));
+        let lookup_table = DataLookupTable::from_transaction(conway_mtx, &pallas_resolved_inputs);
+
+        // Redeemers are evaluated in the order they appear in the witness set.
+        conway_mtx
+            .transaction_witness_set
+            .redeemer
+            .iter()
+            .flat_map(|redeemers| iter_redeemers(redeemers))
+            .map(|(key, data, ex_units)| {
+                let redeemer = ConwayRedeemer {
+                    tag: key.tag,
+                    index: key.index,
+                    data: data.clone(),
+                    ex_units,
+                };
+                eval_redeemer(
+                    conway_mtx,
+                    &pallas_resolved_inputs,
+                    &slot_config,
+                    &redeemer,
+                    &lookup_table,
+                    cost_mdls.as_ref(),
+                    &budget(ex_units),
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map_err(UTxOError::PhaseTwo)
+    }
+
+    /// Runs the Plutus scripts of the transaction, giving each of them the
+    /// execution units limit of the transaction, whatever its redeemer
+    /// declares. Returns the execution units spent by each redeemer.
+    fn run_scripts(
+        tx_cbor_bytes: &[u8],
+        resolved_inputs: Vec<(Input, Output)>,
+    ) -> Result<Vec<EvaluatedRedeemer>, UTxOError> {
+        let pps = Self::protocol_parameters().ok_or(UTxOError::Fail)?;
+        let conway_mtx: ConwayMintedTx = conway_minted_tx_from_cbor(tx_cbor_bytes);
+        let max_budget = |_: ConwayExUnits| ExBudget {
+            mem: pps.max_tx_ex_units.mem as i64,
+            cpu: pps.max_tx_ex_units.steps as i64,
+        };
+
+        match Self::eval_redeemers(&conway_mtx, resolved_inputs, max_budget) {
+            Ok(redeemers) => Ok(redeemers
+                .into_iter()
+                .map(|r| EvaluatedRedeemer {
+                    tag: redeemer_tag_to_string(&r.tag),
+                    index: r.index,
+                    ex_units: ExUnits {
+                        mem: r.ex_units.mem,
+                        steps: r.ex_units.steps,
+                    },
+                })
+                .collect()),
+            // Declared units never exceed the limit, so neither do they cover
+            // a script running out of it.
+            Err(UTxOError::PhaseTwo(PhaseTwoError::RedeemerError { tag, index, err }))
+                if matches!(
+                    *err,
+                    PhaseTwoError::Machine(machine::Error::OutOfExError(_), _, _)
+                ) =>
+            {
+                Err(UTxOError::ExUnitsExceeded { tag, index })
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Checks that each of the `evaluated` redeemers of the transaction stays
+    /// within the execution units it declares.
+    fn phase_two_checks(
+        transaction: &Transaction,
+        evaluated: &[EvaluatedRedeemer],
+    ) -> DispatchResult {
+        for spent in evaluated {
+            let declared = transaction
+                .transaction_witness_set
+                .redeemer
+                .iter()
+                .flatten()
+                .find(|redeemer| spent.evaluates(redeemer))
+                .map(|redeemer| redeemer.ex_units.clone())
+                .unwrap_or(ExUnits { mem: 0, steps: 0 });
+            ensure!(
+                spent.ex_units.fits(&declared),
+                UTxOError::ExUnitsExceeded {
+                    tag: spent.tag.clone(),
+                    index: spent.index,
+                }
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether `err` comes from a script that was run and failed, possibly
+    /// for exceeding its execution units, as opposed to a transaction whose
+    /// scripts cannot even be run.
+    fn is_script_failure(err: &UTxOError) -> bool {
+        match err {
+            UTxOError::ExUnitsExceeded { .. } => true,
+            UTxOError::PhaseTwo(PhaseTwoError::RedeemerError { err, .. }) => {
+                matches!(**err, PhaseTwoError::Machine(..))
+            }
+            _ => false,
+        }
+    }
+
+    /// Runs the Plutus scripts of the transaction and tells whether its
+    /// regular effects take place, which must agree with its validity flag.
+    /// A transaction flagged as valid whose scripts fail is rejected with the
+    /// script error, so that the pool never includes it and its submitter
+    /// learns why. One flagged as invalid must have failing scripts, and its
+    /// collateral is forfeited instead. The execution units spent by the
+    /// redeemers are pushed to `evaluated` if every script runs.
+    fn scripts_succeed(
+        transaction: &Transaction,
+        tx_cbor_bytes: &[u8],
+        resolved_inputs: Vec<(Input, Output)>,
+        evaluated: &mut Vec<EvaluatedRedeemer>,
+    ) -> Result<bool, UTxOError> {
+        let outcome = Self::run_scripts(tx_cbor_bytes, resolved_inputs).and_then(|redeemers| {
+            evaluated.extend(redeemers);
+            Self::phase_two_checks(transaction, evaluated)
+        });
+        match outcome {
+            Ok(()) => {
+                ensure!(
+                    transaction.is_valid,
+                    UTxOError::Babbage(ValidityFlagMismatch)
+                );
+                Ok(true)
+            }
+            Err(err) if Self::is_script_failure(&err) && !transaction.is_valid => {
+                ensure!(
+                    transaction.transaction_body.collateral.is_some(),
+                    UTxOError::Babbage(CollateralMissing)
+                );
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Execution units declared by the redeemers of a transaction.
+    fn declared_ex_units(transaction: &Transaction) -> ExUnits {
+        transaction
+            .transaction_witness_set
+            .redeemer
+            .iter()
+            .flatten()
+            .fold(ExUnits { mem: 0, steps: 0 }, |acc, r| {
+                acc + r.ex_units.clone()
+            })
+    }
+
+    /// Adds the execution units declared by a transaction to those of the
+    /// current block, failing if the block limit is exceeded.
+    fn add_block_ex_units(transaction: &Transaction) -> DispatchResult {
+        let pps = Self::protocol_parameters().ok_or(UTxOError::Fail)?;
+        let block_ex_units = sp_io::storage::get(BLOCK_EX_UNITS)
+            .and_then(|d| ExUnits::decode(&mut &*d).ok())
+            .unwrap_or(ExUnits { mem: 0, steps: 0 })
+            + Self::declared_ex_units(transaction);
         ensure!(
-            phase_two_result.is_ok(),
-            UTxOError::PhaseTwo(phase_two_result.unwrap_err())
+            block_ex_units.fits(&pps.max_block_ex_units),
+            UTxOError::Babbage(BlockExUnitsExceeded)
         );
+        sp_io::storage::set(BLOCK_EX_UNITS, &block_ex_units.encode());
 
         Ok(())
     }
@@ -131,12 +395,23 @@ where
     /// Does not commit anything to storage.
     /// This returns Ok even if some inputs are still missing because the tagged transaction pool can handle that.
     /// We later check that there are no missing inputs in `apply_griffin_transaction`.
-    ///
-    /// The output includes the list of relevant UTxOs to be used for other
-    /// checks (in order to avoid a further db search).
     fn validate_griffin_transaction(
         transaction: &Transaction,
     ) -> Result<ValidTransaction, UTxOError> {
+        Self::check_griffin_transaction(transaction, &mut Vec::new())
+            .map(|(valid_transaction, _)| valid_transaction)
+    }
+
+    /// Validation of a griffin transaction, as in `validate_griffin_transaction`.
+    /// Also tells whether the scripts of the transaction succeed, in which case
+    /// its regular outputs are produced; otherwise, its collateral is forfeited.
+    /// Transactions with missing inputs are assumed to behave as flagged.
+    /// The execution units spent by the redeemers are pushed to `evaluated` if
+    /// the scripts are run to completion.
+    fn check_griffin_transaction(
+        transaction: &Transaction,
+        evaluated: &mut Vec<EvaluatedRedeemer>,
+    ) -> Result<(ValidTransaction, bool), UTxOError> {
         debug!(
             target: LOG_TARGET,
             "validating griffin transaction",
@@ -156,34 +431,46 @@ where
             );
         }
 
-        let mut tx_outs_info: OutputInfoList = Vec::new();
-        let mut input_utxos: Vec<Output> = Vec::new();
+        // Reward accounts are not part of the local UTxO set, so withdrawals
+        // are checked against storage here.
+        Self::check_withdrawals(transaction)?;
 
         // Add present inputs to a list to be used to produce the local UTxO set.
         // Keep track of any missing inputs for use in the tagged transaction pool
         let mut missing_inputs = Vec::new();
-        for input in transaction.transaction_body.inputs.iter() {
-            if let Some(u) = TransparentUtxoSet::peek_utxo(input) {
-                tx_outs_info.push((
-                    hex::encode(u.address.0.as_slice()),
-                    PallasValue::from(u.clone().value),
-                    None, // irrelevant for phase 1 checks (always inline datum)
-                    None,
-                ));
-                // Repeated info in tx_outs_info, but we need this type for phase 2 checks
-                input_utxos.push(u);
-            } else {
-                missing_inputs.push(input.clone().encode());
-            }
-        }
+        let (tx_outs_info, mut resolved_inputs) =
+            Self::peek_inputs(&transaction.transaction_body.inputs, &mut missing_inputs);
 
-        // Make sure no outputs already exist in storage
+        // Reference inputs are resolved in the same way, but they are only
+        // read by the transaction and never consumed.
+        let (ref_outs_info, resolved_ref_inputs) = Self::peek_inputs(
+            transaction
+                .transaction_body
+                .reference_inputs
+                .as_deref()
+                .unwrap_or_default(),
+            &mut missing_inputs,
+        );
+        resolved_inputs.extend(resolved_ref_inputs);
+
+        // Collateral inputs are not visible to scripts.
+        let (coll_outs_info, _) = Self::peek_inputs(
+            transaction
+                .transaction_body
+                .collateral
+                .as_deref()
+                .unwrap_or_default(),
+            &mut missing_inputs,
+        );
+
+        // Make sure no outputs already exist in storage, including the
+        // collateral return, which is placed after the regular outputs.
         let tx_hash = BlakeTwo256::hash_of(&transaction.encode());
-        for index in 0..transaction.transaction_body.outputs.len() {
-            let input = Input {
-                tx_hash,
-                index: index as u32,
-            };
+        for index in Self::produced_indices(transaction, true)
+            .into_iter()
+            .chain(Self::produced_indices(transaction, false))
+        {
+            let input = Input { tx_hash, index };
 
             debug!(
                 target: LOG_TARGET,
@@ -197,27 +484,30 @@ where
         }
 
         // Griffin Tx -> Pallas Tx -> CBOR -> Minted Pallas Tx
-        // This last one is used to produce the local UTxO set.
-        let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
+        // This last one is used to produce the local UTxO set. Transactions
+        // with malformed datums, redeemer data or auxiliary data cannot be
+        // converted, and are rejected here.
+        let pallas_tx = PallasTransaction::try_from(transaction.clone())?;
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: MintedTx = babbage_minted_tx_from_cbor(&cbor_bytes);
         let tx_body: &MintedTransactionBody = &mtx.transaction_body.clone();
         let outs_info_clone = tx_outs_info.clone();
-        let utxos: UTxOs = mk_utxo_for_babbage_tx(tx_body, outs_info_clone.as_slice());
+        let mut utxos: UTxOs = mk_utxo_for_babbage_tx(tx_body, outs_info_clone.as_slice());
+        add_ref_input_babbage(tx_body, &mut utxos, ref_outs_info.as_slice());
+        add_collateral_babbage(tx_body, &mut utxos, coll_outs_info.as_slice());
 
         Self::pool_checks(&mtx, &utxos)?;
 
+        let priority = Self::fee_priority(transaction.transaction_body.fee, &mtx);
+
         // Calculate the tx-pool tags provided by this transaction, which
         // are just the encoded Inputs
-        let provides = (0..transaction.transaction_body.outputs.len())
-            .map(|i| {
-                let input = Input {
-                    tx_hash,
-                    index: i as u32,
-                };
-                input.encode()
-            })
-            .collect::<Vec<_>>();
+        let provides = |scripts_succeed: bool| {
+            Self::produced_indices(transaction, scripts_succeed)
+                .into_iter()
+                .map(|index| Input { tx_hash, index }.encode())
+                .collect::<Vec<_>>()
+        };
 
         // If any of the inputs are missing, we cannot make any more progress
         if !missing_inputs.is_empty() {
@@ -225,29 +515,90 @@ where
                 target: LOG_TARGET,
                 "Transaction is valid but still has missing inputs. Returning early.",
             );
-            return Ok(ValidTransaction {
+            let valid_transaction = ValidTransaction {
                 requires: missing_inputs,
-                provides,
-                priority: 0,
+                provides: provides(transaction.is_valid),
+                priority,
                 longevity: TransactionLongevity::MAX,
                 propagate: true,
-            });
+            };
+            return Ok((valid_transaction, transaction.is_valid));
         }
 
         // These checks were done in `apply_griffin_transaction`, but we do them here for simplicity.
         // This might limit the ledger's ability to accept transactions that would be valid
         // in a block, as in chaining.
         Self::ledger_checks(&mtx, &utxos)?;
-        Self::phase_two_checks(&cbor_bytes, input_utxos)?;
+        let scripts_succeed =
+            Self::scripts_succeed(transaction, &cbor_bytes, resolved_inputs, evaluated)?;
 
         // Return the valid transaction
-        Ok(ValidTransaction {
+        let valid_transaction = ValidTransaction {
             requires: Vec::new(),
-            provides,
-            priority: 0,
+            provides: provides(scripts_succeed),
+            priority,
             longevity: TransactionLongevity::MAX,
             propagate: true,
-        })
+        };
+        Ok((valid_transaction, scripts_succeed))
+    }
+
+    /// Indices of the outputs produced by a transaction: the regular ones if
+    /// its scripts succeed, or else its collateral return, if any, which is
+    /// placed after them.
+    fn produced_indices(transaction: &Transaction, scripts_succeed: bool) -> Vec<u32> {
+        let num_outputs = transaction.transaction_body.outputs.len() as u32;
+        if scripts_succeed {
+            (0..num_outputs).collect()
+        } else {
+            transaction
+                .transaction_body
+                .collateral_return
+                .iter()
+                .map(|_| num_outputs)
+                .collect()
+        }
+    }
+
+    /// Evaluates a transaction against the current state without applying it,
+    /// running the checks of the pool once. The evaluation reports the
+    /// execution units spent by each redeemer, regardless of those declared,
+    /// provided that every script runs within the execution units limit of the
+    /// transaction, together with its minimum fee and the error it would be
+    /// rejected with, if any. Transactions with missing inputs are reported as
+    /// such, since their scripts cannot be run.
+    pub fn evaluate_transaction(transaction: &Transaction) -> TxEvaluation {
+        debug!(
+            target: LOG_TARGET,
+            "Evaluating griffin transaction: {:?}", transaction
+        );
+
+        // Transactions that cannot be converted are reported by the checks.
+        let size = PallasTransaction::try_from(transaction.clone())
+            .ok()
+            .and_then(|pallas_tx| {
+                let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
+                get_babbage_tx_size(&babbage_minted_tx_from_cbor(&cbor_bytes))
+            });
+        let min_fee = match (size, Self::protocol_parameters()) {
+            (Some(size), Some(pps)) => pps.min_fee_a as Coin * size as Coin + pps.min_fee_b as Coin,
+            _ => 0,
+        };
+
+        let mut redeemers = Vec::new();
+        let error = match Self::check_griffin_transaction(transaction, &mut redeemers) {
+            Ok((valid_transaction, _)) if !valid_transaction.requires.is_empty() => {
+                Some(UTxOError::Babbage(InputNotInUTxO))
+            }
+            Ok(_) => None,
+            Err(err) => Some(err),
+        };
+
+        TxEvaluation {
+            redeemers,
+            min_fee,
+            error: error.map(<_>::from),
+        }
     }
 
     /// Does full verification and application of griffin transactions.
@@ -261,7 +612,15 @@ where
 
         // Re-do the pre-checks. These should have been done in the pool, but we can't
         // guarantee that foreign nodes do these checks faithfully, so we need to check on-chain.
-        let valid_transaction = Self::validate_griffin_transaction(transaction)?;
+        // Failing scripts of a transaction flagged as valid make the block invalid.
+        let (valid_transaction, scripts_succeed) =
+            Self::check_griffin_transaction(transaction, &mut Vec::new()).map_err(|err| {
+                if Self::is_script_failure(&err) {
+                    UTxOError::Babbage(ValidityFlagMismatch)
+                } else {
+                    err
+                }
+            })?;
 
         // If there are still missing inputs, we cannot execute this,
         // although it would be valid in the pool
@@ -270,12 +629,52 @@ where
             UTxOError::Babbage(InputNotInUTxO)
         );
 
+        // Scripts of all transactions in a block share an execution budget
+        Self::add_block_ex_units(transaction)?;
+
         // At this point, all validation is complete, so we can commit the storage changes.
-        Self::update_storage(transaction);
+        if scripts_succeed {
+            Self::update_storage(transaction);
+        } else {
+            debug!(
+                target: LOG_TARGET,
+                "Scripts failed, consuming collateral instead",
+            );
+            Self::forfeit_collateral(transaction);
+        }
 
         Ok(())
     }
 
+    /// Fetches `inputs` from the UTxO set. Returns the information needed to
+    /// produce the local UTxO set for phase one checks, along with the resolved
+    /// inputs for phase two. The encodings of missing inputs are pushed to
+    /// `missing_inputs`.
+    fn peek_inputs<'a>(
+        inputs: &[Input],
+        missing_inputs: &mut Vec<Vec<u8>>,
+    ) -> (OutputInfoList<'a>, Vec<(Input, Output)>) {
+        let mut outs_info: OutputInfoList = Vec::new();
+        let mut resolved_inputs: Vec<(Input, Output)> = Vec::new();
+
+        for input in inputs.iter() {
+            if let Some(u) = TransparentUtxoSet::peek_utxo(input) {
+                outs_info.push((
+                    hex::encode(u.address.0.as_slice()),
+                    PallasValue::from(u.clone().value),
+                    mk_minted_datum_hash(&u),
+                    mk_minted_script_ref(&u),
+                ));
+                // Repeated info in outs_info, but we need this type for phase 2 checks
+                resolved_inputs.push((input.clone(), u));
+            } else {
+                missing_inputs.push(input.clone().encode());
+            }
+        }
+
+        (outs_info, resolved_inputs)
+    }
+
     /// Helper function to update the utxo set according to the given transaction.
     /// This function does absolutely no validation. It assumes that the transaction
     /// has already passed validation. Changes proposed by the transaction are written
@@ -286,6 +685,11 @@ where
             TransparentUtxoSet::consume_utxo(input);
         }
 
+        // Drain the reward accounts withdrawn from
+        for (account, amount) in transaction.transaction_body.withdrawals.iter().flatten() {
+            RewardAccounts::withdraw(account, *amount);
+        }
+
         debug!(
             target: LOG_TARGET,
             "Transaction before updating storage {:?}", transaction
@@ -300,33 +704,332 @@ where
         }
     }
 
-    /// A helper function that allows griffin runtimes to read the current block height
+    /// Helper function to update the utxo set according to a transaction whose
+    /// scripts failed: the collateral inputs are consumed and the collateral
+    /// return, if any, is stored after the indices of the regular outputs.
+    fn forfeit_collateral(transaction: &Transaction) {
+        for input in transaction.transaction_body.collateral.iter().flatten() {
+            TransparentUtxoSet::consume_utxo(input);
+        }
+
+        if let Some(output) = &transaction.transaction_body.collateral_return {
+            let input = Input {
+                tx_hash: BlakeTwo256::hash_of(&transaction.encode()),
+                index: transaction.transaction_body.outputs.len() as u32,
+            };
+            TransparentUtxoSet::store_utxo(input, output);
+        }
+    }
+
+    /// A helper function that allows griffin runtimes to read the current block height.
+    /// The height is stored at the beginning of block one, so it is zero at genesis.
     pub fn block_height() -> BlockNumber {
         sp_io::storage::get(HEIGHT_KEY)
             .and_then(|d| BlockNumber::decode(&mut &*d).ok())
-            .expect("A height is stored at the beginning of block one and never cleared.")
+            .unwrap_or_default()
+    }
+
+    /// A helper function that allows griffin runtimes to read the protocol parameters.
+    /// They are set at genesis, so they are only missing from a corrupted state.
+    pub fn protocol_parameters() -> Option<ProtocolParameters> {
+        sp_io::storage::get(PROTOCOL_PARAMETERS)
+            .and_then(|d| ProtocolParameters::decode(&mut &*d).ok())
+    }
+
+    /// The last update applied to the protocol parameters, if any.
+    pub fn last_parameters_update() -> Option<AppliedUpdate> {
+        sp_io::storage::get(LAST_PARAMETERS_UPDATE)
+            .and_then(|d| AppliedUpdate::decode(&mut &*d).ok())
+    }
+
+    /// The update carried by the governance UTxO that has not been applied
+    /// yet, if any.
+    pub fn pending_parameters_update() -> Option<PendingUpdate> {
+        let token = Self::governance_token()?;
+        let (source, pending) = fetch_pending_update(&token).ok()??;
+        let applied = Self::last_parameters_update().is_some_and(|last| last.source == source);
+
+        (!applied).then_some(pending)
     }
 
-    /// A helper function that allows griffin runtimes to read the start posix time of the first
-    /// block, in milliseconds
-    pub fn zero_time() -> u64 {
-        sp_io::storage::get(ZERO_TIME)
+    fn governance_token() -> Option<GovernanceToken> {
+        sp_io::storage::get(GOVERNANCE_TOKEN).and_then(|d| GovernanceToken::decode(&mut &*d).ok())
+    }
+
+    /// Applies the pending protocol parameters update if `epoch` is at or
+    /// after the one named by the update.
+    fn update_protocol_parameters(epoch: u64, pps: &ProtocolParameters) {
+        let Some(token) = Self::governance_token() else {
+            return;
+        };
+        let (source, pending) = match fetch_pending_update(&token) {
+            Ok(Some(update)) => update,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "Ignoring protocol parameters update due to: {:?}", e
+                );
+                return;
+            }
+        };
+        if pending.epoch > epoch
+            || Self::last_parameters_update().is_some_and(|last| last.source == source)
+        {
+            return;
+        }
+
+        let updated = match pending.update.apply_to(pps) {
+            Ok(updated) => updated,
+            Err(e) => {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "Ignoring protocol parameters update due to: {:?}", e
+                );
+                return;
+            }
+        };
+
+        debug!(
+            target: LOG_TARGET,
+            "Applying protocol parameters update at epoch {}: {:?}", epoch, pending
+        );
+        let applied = AppliedUpdate {
+            epoch,
+            source,
+            previous: pps.clone(),
+        };
+        sp_io::storage::set(PROTOCOL_PARAMETERS, &updated.encode());
+        sp_io::storage::set(LAST_PARAMETERS_UPDATE, &applied.encode());
+    }
+
+    /// The slot of the current block.
+    pub fn current_slot() -> u64 {
+        sp_io::storage::get(SLOT_KEY)
             .and_then(|d| u64::decode(&mut &*d).ok())
-            .expect("Failed to read ZERO_TIME from storage.")
+            .or_else(|| {
+                Self::protocol_parameters().map(|pps| pps.zero_slot + Self::block_height() as u64)
+            })
+            .unwrap_or_default()
     }
 
-    /// A helper function that allows griffin runtimes to read the slot number of the first block
-    pub fn zero_slot() -> u64 {
-        sp_io::storage::get(ZERO_SLOT)
+    /// Records the slot of the block with the given header, taken from its Aura
+    /// pre-runtime digest. Blocks without one, which are only built outside of
+    /// Aura consensus, are assumed to take one slot each from `zero_slot`.
+    fn record_slot(header: &Header, pps: &ProtocolParameters) {
+        let slot = match header
+            .digest()
+            .convert_first(|log| log.pre_runtime_try_to::<Slot>(&AURA_ENGINE_ID))
+        {
+            Some(aura_slot) => ledger_slot(*aura_slot, pps),
+            None => pps.zero_slot + *header.number() as u64,
+        };
+        sp_io::storage::set(SLOT_KEY, &slot.encode());
+    }
+
+    /// Records the main chain reference of the block with the given header,
+    /// taken from its pre-runtime digest.
+    fn record_mc_hash(header: &Header) {
+        match mc_hash_from_digest(header.digest()) {
+            Some(mc_hash) => sp_io::storage::set(DATA_KEY, &PCData { mc_hash }.encode()),
+            None => sp_io::storage::clear(DATA_KEY),
+        }
+    }
+
+    /// The epoch of the latest block.
+    pub fn current_epoch() -> u64 {
+        sp_io::storage::get(EPOCH_KEY)
             .and_then(|d| u64::decode(&mut &*d).ok())
-            .expect("Failed to read ZERO_SLOT from storage.")
+            .or_else(|| {
+                epoch_of(
+                    Self::current_slot(),
+                    Self::protocol_parameters()?.slots_per_epoch,
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// The epoch and slot of the latest block.
+    pub fn epoch_info() -> EpochInfo {
+        EpochInfo {
+            epoch: Self::current_epoch(),
+            slot: Self::current_slot(),
+        }
     }
 
-    /// A helper function that allows griffin runtimes to read the millisecs per slot
+    /// Records the epoch of the current block, running the epoch change hook
+    /// if the block is the first one of its epoch.
+    fn update_epoch(pps: &ProtocolParameters) {
+        // Genesis and parameter updates never leave `slots_per_epoch` at zero.
+        let Some(epoch) = epoch_of(Self::current_slot(), pps.slots_per_epoch) else {
+            return;
+        };
+        if epoch <= Self::current_epoch() {
+            return;
+        }
+
+        debug!(target: LOG_TARGET, "Entering epoch {}", epoch);
+        sp_io::storage::set(EPOCH_KEY, &epoch.encode());
+        Self::on_epoch_change(epoch, pps);
+    }
+
+    /// Hook run by the first block of every epoch. Subsystems acting at epoch
+    /// boundaries are called from here, in order.
+    fn on_epoch_change(epoch: u64, pps: &ProtocolParameters) {
+        Self::update_protocol_parameters(epoch, pps);
+        Self::rotate_committee(epoch);
+    }
+
+    /// The Cardano data committees are selected from, if any.
+    pub fn committee_selection() -> Option<CommitteeSelectionConfig> {
+        sp_io::storage::get(COMMITTEE_SELECTION)
+            .and_then(|d| CommitteeSelectionConfig::decode(&mut &*d).ok())
+    }
+
+    /// The committee in office, unless it is still the genesis committee.
+    pub fn current_committee() -> Option<Committee> {
+        sp_io::storage::get(CURRENT_COMMITTEE).and_then(|d| Committee::decode(&mut &*d).ok())
+    }
+
+    /// The committee selected for the next epoch, if any.
+    pub fn next_committee() -> Option<NextCommittee> {
+        sp_io::storage::get(NEXT_COMMITTEE).and_then(|d| NextCommittee::decode(&mut &*d).ok())
+    }
+
+    /// The epoch following the current one, if committees are selected and the
+    /// committee of that epoch has not been selected yet.
+    pub fn next_unset_epoch() -> Option<u64> {
+        Self::committee_selection()?;
+        let epoch = Self::current_epoch() + 1;
+
+        match Self::next_committee() {
+            Some(next) if next.epoch == epoch => None,
+            _ => Some(epoch),
+        }
+    }
+
+    /// Stores the committee selected by the committee inherent of the current
+    /// block, which must be that of the epoch due, and announces it in the
+    /// header.
+    fn schedule_committee(next: NextCommittee) -> Result<(), BlockError> {
+        let due = Self::next_unset_epoch();
+        ensure!(
+            due == Some(next.epoch),
+            BlockError::UnexpectedCommitteeSelection {
+                due,
+                announced: next.epoch,
+            }
+        );
+        debug!(target: LOG_TARGET, "Scheduling committee: {:?}", next);
+
+        sp_io::storage::set(NEXT_COMMITTEE, &next.encode());
+        Self::deposit_log(DigestItem::Consensus(COMMITTEE_ENGINE_ID, next.encode()));
+
+        Ok(())
+    }
+
+    /// Applies an extrinsic of a block: committee inherents schedule the
+    /// committee they select, and any other extrinsic is applied as a griffin
+    /// transaction.
+    fn apply_block_extrinsic(extrinsic: &Transaction, index: u32) -> Result<(), BlockError> {
+        match NextCommittee::from_inherent(extrinsic) {
+            Some(next) => Self::schedule_committee(next),
+            None => Self::apply_griffin_transaction(extrinsic).map_err(|error| {
+                BlockError::InvalidExtrinsic {
+                    index,
+                    tx_hash: BlakeTwo256::hash_of(&extrinsic.encode()),
+                    error,
+                }
+            }),
+        }
+    }
+
+    /// Puts the committee selected for `epoch` in office, scheduling the
+    /// corresponding GRANDPA authority set change. The current committee stays
+    /// in office if none was selected.
+    ///
+    /// The change is a standard one with no delay. GRANDPA only enacts a
+    /// standard change once the outgoing set finalizes the block announcing
+    /// it, so every node hands over to the new set at that same block, and a
+    /// change announced on a fork that is never finalized is never enacted.
+    /// The delay would only postpone the handover further. Aura reads the
+    /// authorities of a block from the state of its parent, so the new
+    /// committee authors blocks from the second block of its epoch on, and
+    /// the first one is still authored by the outgoing committee.
+    fn rotate_committee(epoch: u64) {
+        let Some(next) = Self::next_committee().filter(|next| next.epoch <= epoch) else {
+            if Self::committee_selection().is_some() {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "No committee was selected for epoch {}, keeping the current one", epoch
+                );
+            }
+            return;
+        };
+        sp_io::storage::clear(NEXT_COMMITTEE);
+
+        let set_id = Self::current_committee().map_or(0, |current| current.set_id) + 1;
+        let change = ScheduledChange {
+            next_authorities: next
+                .members
+                .iter()
+                .map(|member| (member.grandpa.clone(), 1))
+                .collect(),
+            delay: 0,
+        };
+        Self::deposit_log(DigestItem::Consensus(
+            GRANDPA_ENGINE_ID,
+            ConsensusLog::<BlockNumber>::ScheduledChange(change).encode(),
+        ));
+
+        debug!(
+            target: LOG_TARGET,
+            "Committee of epoch {} takes office with set ID {}", epoch, set_id
+        );
+        let committee = Committee {
+            epoch,
+            set_id,
+            members: next.members,
+        };
+        sp_io::storage::set(CURRENT_COMMITTEE, &committee.encode());
+    }
+
+    /// Appends `log` to the digest of the block being built.
+    fn deposit_log(log: DigestItem) {
+        let mut logs = Self::take_logs();
+        logs.push(log);
+        sp_io::storage::set(LOGS_KEY, &logs.encode());
+    }
+
+    /// Removes the digest logs deposited so far from storage.
+    fn take_logs() -> Vec<DigestItem> {
+        let logs = sp_io::storage::get(LOGS_KEY)
+            .and_then(|d| <Vec<DigestItem>>::decode(&mut &*d).ok())
+            .unwrap_or_default();
+        sp_io::storage::clear(LOGS_KEY);
+        logs
+    }
+
+    /// A helper function that allows griffin runtimes to read the millisecs per slot,
+    /// which are zero if the state holds no protocol parameters.
     pub fn slot_length() -> u32 {
-        sp_io::storage::get(SLOT_LENGTH)
-            .and_then(|d| u32::decode(&mut &*d).ok())
-            .expect("Failed to read SLOT_LENGTH from storage.")
+        Self::protocol_parameters().map_or(0, |pps| pps.slot_length)
+    }
+
+    /// Records the slot, main chain reference and epoch of the block with the
+    /// given header, running the epoch change hook if it starts an epoch.
+    fn enter_block(header: &Header) -> Result<(), BlockError> {
+        let pps = Self::protocol_parameters().ok_or(BlockError::MissingProtocolParameters)?;
+        Self::record_slot(header, &pps);
+        Self::record_mc_hash(header);
+        Self::update_epoch(&pps);
+
+        Ok(())
+    }
+
+    /// The root of the current state.
+    fn state_root() -> Option<H256> {
+        <Header as HeaderT>::Hash::decode(&mut &sp_io::storage::root(StateVersion::V1)[..]).ok()
     }
 
     // These next three methods are for the block authoring workflow.
@@ -346,8 +1049,10 @@ where
         // performing pool validations and other off-chain runtime calls.
         sp_io::storage::set(HEIGHT_KEY, &header.number().encode());
 
-        if let Some(data) = ExtendedHeader::get_pcdata_storage() {
-            sp_io::storage::set(DATA_KEY, &(data).encode());
+        // A block opened on a state it cannot be executed on is left without
+        // slot and epoch; importing it will reject it.
+        if let Err(e) = Self::enter_block(header) {
+            log::error!(target: LOG_TARGET, "⛔ Cannot open block: {}", e);
         }
 
         // griffin blocks always allow user transactions.
@@ -369,6 +1074,17 @@ where
         sp_io::storage::set(EXTRINSIC_KEY, &extrinsics.encode());
 
         // Now actually apply the extrinsic
+        if let Some(next) = NextCommittee::from_inherent(&extrinsic) {
+            Self::schedule_committee(next).map_err(|e| {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "⛔ Committee inherent could not be applied due to: {}",
+                    e,
+                );
+                TransactionValidityError::Invalid(InvalidTransaction::BadMandatory)
+            })?;
+            return Ok(Ok(()));
+        }
         Self::apply_griffin_transaction(&extrinsic).map_err(|e| {
             log::warn!(
                 target: LOG_TARGET,
@@ -381,10 +1097,17 @@ where
         Ok(Ok(()))
     }
 
+    /// Finalizes the block being authored and returns its header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no block was opened with [`Self::open_block`], since there
+    /// is no header to finalize.
     pub fn close_block() -> Header {
+        // The header is stored when opening the block and never mutated.
         let mut header = sp_io::storage::get(HEADER_KEY)
             .and_then(|d| Header::decode(&mut &*d).ok())
-            .expect("We initialized with header, it never got mutated, qed");
+            .expect("close_block called on a block that was not opened");
 
         // the header itself contains the state root, so it cannot be inside the state (circular
         // dependency..). Make sure in execute block path we have the same rule.
@@ -396,13 +1119,14 @@ where
         let extrinsics_root =
             <Header as HeaderT>::Hashing::ordered_trie_root(extrinsics, StateVersion::V0);
         sp_io::storage::clear(EXTRINSIC_KEY);
+        sp_io::storage::clear(BLOCK_EX_UNITS);
         header.set_extrinsics_root(extrinsics_root);
+        for log in Self::take_logs() {
+            header.digest_mut().push(log);
+        }
 
-        let raw_state_root = &sp_io::storage::root(StateVersion::V1)[..];
-        let state_root = <Header as HeaderT>::Hash::decode(&mut &raw_state_root[..]).unwrap();
-        header.set_state_root(state_root);
-        if let Some(data) = ExtendedHeader::get_pcdata_storage() {
-            header.set_pcdata(data);
+        if let Some(state_root) = Self::state_root() {
+            header.set_state_root(state_root);
         }
 
         debug!(target: LOG_TARGET, "finalizing block {:?}", header);
@@ -411,7 +1135,29 @@ where
 
     // This one is for the Core api. It is used to import blocks authored by foreign nodes.
 
+    /// Executes a block authored by another node. The Core API cannot report
+    /// errors, so the changes of an invalid block are rolled back and the hash
+    /// of its header is stored instead: the resulting state root cannot be the
+    /// one the header commits to, and the client rejects the block when
+    /// comparing them.
     pub fn execute_block(block: Block) {
+        let header_hash = block.header().hash();
+        sp_io::storage::start_transaction();
+        match Self::try_execute_block(block) {
+            Ok(()) => sp_io::storage::commit_transaction(),
+            Err(e) => {
+                log::error!(target: LOG_TARGET, "⛔ Invalid block: {}", e);
+                sp_io::storage::rollback_transaction();
+                sp_io::storage::set(REJECTED_BLOCK_KEY, &header_hash.encode());
+            }
+        }
+    }
+
+    /// Executes a block, returning the reason to reject it if it is invalid.
+    /// Storage changes made before the error are not reverted, so callers
+    /// must run it within a storage transaction, as
+    /// [`Self::execute_block`] does.
+    fn try_execute_block(block: Block) -> Result<(), BlockError> {
         debug!(
             target: LOG_TARGET,
             "Entering execute_block. block: {:?}", block
@@ -426,27 +1172,30 @@ where
         // performing pool validations and other off-chain runtime calls.
         sp_io::storage::set(HEIGHT_KEY, &block.header().number().encode());
 
+        Self::enter_block(block.header())?;
+
         // Apply each extrinsic
-        for extrinsic in block.extrinsics() {
-            match Self::apply_griffin_transaction(extrinsic) {
-                Ok(()) => debug!(
-                    target: LOG_TARGET,
-                    "Successfully executed extrinsic: {:?}", extrinsic
-                ),
-                Err(e) => panic!("{:?}", e),
-            }
+        for (index, extrinsic) in block.extrinsics().iter().enumerate() {
+            Self::apply_block_extrinsic(extrinsic, index as u32)?;
+            debug!(
+                target: LOG_TARGET,
+                "Successfully executed extrinsic: {:?}", extrinsic
+            );
         }
 
-        // Clear the transient header out of storage
+        // Clear the transient header, execution units and logs out of storage
         sp_io::storage::clear(HEADER_KEY);
+        sp_io::storage::clear(BLOCK_EX_UNITS);
+        let logs = Self::take_logs();
 
         // Check state root
-        let raw_state_root = &sp_io::storage::root(StateVersion::V1)[..];
-        let state_root = <Header as HeaderT>::Hash::decode(&mut &raw_state_root[..]).unwrap();
-        assert_eq!(
-            *block.header().state_root(),
-            state_root,
-            "state root mismatch"
+        let state_root = Self::state_root();
+        ensure!(
+            state_root == Some(*block.header().state_root()),
+            BlockError::StateRootMismatch {
+                expected: *block.header().state_root(),
+                computed: state_root.unwrap_or_default(),
+            }
         );
 
         // Check extrinsics root.
@@ -457,11 +1206,36 @@ where
             .collect::<Vec<_>>();
         let extrinsics_root =
             <Header as HeaderT>::Hashing::ordered_trie_root(extrinsics, StateVersion::V0);
-        assert_eq!(
-            *block.header().extrinsics_root(),
-            extrinsics_root,
-            "extrinsics root mismatch"
+        ensure!(
+            *block.header().extrinsics_root() == extrinsics_root,
+            BlockError::ExtrinsicsRootMismatch {
+                expected: *block.header().extrinsics_root(),
+                computed: extrinsics_root,
+            }
         );
+
+        // Check the committee and authority set logs.
+        let header_logs = block
+            .header()
+            .digest()
+            .logs()
+            .iter()
+            .filter(|log| {
+                matches!(
+                    log.as_consensus(),
+                    Some((id, _)) if id == GRANDPA_ENGINE_ID || id == COMMITTEE_ENGINE_ID
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        ensure!(header_logs == logs, BlockError::DigestLogsMismatch);
+
+        Ok(())
+    }
+
+    /// The main chain reference hash of the latest block, if any.
+    pub fn main_chain_hash() -> Option<H256> {
+        ExtendedHeader::get_pcdata_storage().map(|data| data.mc_hash)
     }
 
     // This one is the pool api. It is used to make preliminary checks in the transaction pool
@@ -493,3 +1267,6 @@ where
         r
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1974 @@
+use super::Executive;
+use crate::{
+    checks_interface::{
+        babbage_minted_tx_from_cbor, babbage_tx_to_cbor, conway_minted_tx_from_cbor,
+    },
+    committee::{
+        Committee, CommitteeMember, CommitteeSelectionConfig, NextCommittee, COMMITTEE_ENGINE_ID,
+    },
+    epoch::{epoch_of, ledger_slot, EpochInfo},
+    evaluation::EvaluatedRedeemer,
+    genesis::config_builder::{
+        transp_to_output, GenesisConfig, GriffinGenesisConfigBuilder, TransparentOutput,
+    },
+    main_chain::MC_HASH_DIGEST_ID,
+    pallas_applying::{babbage::check_tx_validity_interval, utils::BabbageError},
+    pallas_codec::minicbor,
+    pallas_codec::utils::CborWrap,
+    pallas_crypto::hash::Hasher as PallasHasher,
+    pallas_interface::PallasConversionError,
+    pallas_primitives::babbage::{
+        MintedTransactionBody, NativeScript as PallasNativeScript, PostAlonzoTransactionOutput,
+        PseudoScript, Tx as PallasTransaction,
+    },
+    pallas_traverse::OriginalHash,
+    protocol_parameters::{
+        AppliedUpdate, GovernanceError, GovernanceToken, PendingUpdate, ProtocolParameters,
+        ProtocolParametersUpdate,
+    },
+    reward_accounts::RewardAccounts,
+    types::{
+        address_from_native_script, address_from_pk, asset_fingerprint_hash,
+        compute_auxiliary_data_hash, compute_datum_hash, compute_native_script_hash,
+        compute_plutus_script_hash, reward_account_from_script_hash, Address, AssetName,
+        AuxiliaryData, Block, BlockError, BlockNumber, Coin, Datum, ExUnits, Header, Input,
+        Multiasset, NativeScript, Output, PlutusData, PlutusScript, PlutusVersion, PolicyId,
+        Redeemer, RedeemerTag, RewardAccount, Transaction, UTxOError, VKeyWitness, Value,
+    },
+    uplc::ast::{DeBruijn, Name, Program, Term},
+    utxo_set::TransparentUtxoSet,
+    BLOCK_EX_UNITS, COMMITTEE_SELECTION, EPOCH_KEY, EXTRINSIC_KEY, GOVERNANCE_TOKEN,
+    PROTOCOL_PARAMETERS, REJECTED_BLOCK_KEY,
+};
+use parity_scale_codec::{Decode, Encode};
+use sidechain_domain::{McTxHash, UtxoId, UtxoIndex};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_consensus_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_core::{ed25519, ed25519::Pair, sr25519, Pair as _, H256};
+use sp_io::TestExternalities;
+use sp_runtime::{
+    generic::Digest,
+    traits::{BlakeTwo256, Hash as HashT, Header as HeaderT},
+    DigestItem, StateVersion,
+};
+
+// Start time deliberately not aligned with the slot length.
+const ZERO_TIME: u64 = 1_747_081_100_000;
+const SLOT_LENGTH: u64 = 3_000;
+const SLOTS_PER_EPOCH: u64 = 10;
+
+fn new_test_ext() -> TestExternalities {
+    new_test_ext_with_outputs(serde_json::json!([]))
+}
+
+fn new_test_ext_with_outputs(outputs: serde_json::Value) -> TestExternalities {
+    let config: GenesisConfig = serde_json::from_value(serde_json::json!({
+        "zero_time": ZERO_TIME,
+        "zero_slot": 0,
+        "slot_length": SLOT_LENGTH,
+        "slots_per_epoch": SLOTS_PER_EPOCH,
+        "outputs": outputs,
+    }))
+    .unwrap();
+
+    let mut ext = TestExternalities::default();
+    ext.execute_with(|| {
+        GriffinGenesisConfigBuilder::build(config).unwrap();
+        // The genesis block builder takes the genesis transactions out of storage.
+        sp_io::storage::clear(EXTRINSIC_KEY);
+    });
+    ext
+}
+
+/// The Aura slot during which ledger slot `slot` is in progress.
+fn aura_slot(slot: u64) -> u64 {
+    ZERO_TIME.div_ceil(SLOT_LENGTH) + slot
+}
+
+fn header(number: u32, aura_slot: Option<u64>) -> Header {
+    let logs = aura_slot
+        .map(|slot| DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(slot).encode()))
+        .into_iter()
+        .collect();
+
+    Header::new(
+        number,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Digest { logs },
+    )
+}
+
+#[test]
+fn ledger_slot_follows_aura_slot() {
+    new_test_ext().execute_with(|| {
+        let pps = Executive::protocol_parameters().unwrap();
+
+        for slot in [0, 1, 2, 600, 601] {
+            assert_eq!(ledger_slot(aura_slot(slot), &pps), slot);
+        }
+    });
+}
+
+#[test]
+fn slot_comes_from_aura_digest() {
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        assert_eq!(Executive::current_slot(), 1);
+
+        // Slots 2 to 4 are missed.
+        Executive::open_block(&header(2, Some(aura_slot(5))));
+        assert_eq!(Executive::current_slot(), 5);
+    });
+}
+
+#[test]
+fn blocks_without_aura_digest_take_one_slot_each() {
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header(3, None));
+        assert_eq!(Executive::current_slot(), 3);
+    });
+}
+
+/// Header of block `number` carrying `mc_hash` in its main chain reference
+/// digest.
+fn header_with_mc_hash(number: u32, mc_hash: Vec<u8>) -> Header {
+    let mut header = header(number, Some(aura_slot(number as u64)));
+    header
+        .digest
+        .push(DigestItem::PreRuntime(MC_HASH_DIGEST_ID, mc_hash));
+    header
+}
+
+#[test]
+fn main_chain_reference_comes_from_digest() {
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header_with_mc_hash(1, vec![7; 32]));
+        assert_eq!(Executive::main_chain_hash(), Some(H256::repeat_byte(7)));
+
+        Executive::open_block(&header(2, Some(aura_slot(2))));
+        assert_eq!(Executive::main_chain_hash(), None);
+    });
+}
+
+#[test]
+fn malformed_main_chain_reference_is_ignored() {
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header_with_mc_hash(1, vec![7; 31]));
+        assert_eq!(Executive::main_chain_hash(), None);
+    });
+}
+
+#[test]
+fn imported_block_records_its_main_chain_reference() {
+    let header = new_test_ext().execute_with(|| {
+        Executive::open_block(&header_with_mc_hash(1, vec![7; 32]));
+        Executive::close_block()
+    });
+    let block = Block {
+        header,
+        extrinsics: vec![],
+    };
+
+    new_test_ext().execute_with(|| {
+        assert!(Executive::try_execute_block(block.clone()).is_ok());
+        assert_eq!(Executive::main_chain_hash(), Some(H256::repeat_byte(7)));
+    });
+
+    // The reference is committed to by the state root.
+    let mut forged = block;
+    forged.header.digest = header_with_mc_hash(1, vec![8; 32]).digest;
+    new_test_ext().execute_with(|| {
+        assert!(matches!(
+            Executive::try_execute_block(forged),
+            Err(BlockError::StateRootMismatch { .. })
+        ));
+    });
+}
+
+#[test]
+fn missed_slots_across_epochs_change_epoch() {
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(3))));
+        assert_eq!(Executive::current_epoch(), 0);
+
+        // Epoch 1 has no blocks at all.
+        Executive::open_block(&header(2, Some(aura_slot(2 * SLOTS_PER_EPOCH + 7))));
+        assert_eq!(Executive::current_epoch(), 2);
+        assert_eq!(Executive::epoch_info().slot, 2 * SLOTS_PER_EPOCH + 7);
+    });
+}
+
+#[test]
+fn epoch_changes_at_the_first_slot_of_the_epoch() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Executive::epoch_info(), EpochInfo { epoch: 0, slot: 0 });
+
+        Executive::open_block(&header(1, Some(aura_slot(SLOTS_PER_EPOCH - 1))));
+        assert_eq!(
+            Executive::epoch_info(),
+            EpochInfo {
+                epoch: 0,
+                slot: SLOTS_PER_EPOCH - 1,
+            }
+        );
+
+        Executive::open_block(&header(2, Some(aura_slot(SLOTS_PER_EPOCH))));
+        assert_eq!(
+            Executive::epoch_info(),
+            EpochInfo {
+                epoch: 1,
+                slot: SLOTS_PER_EPOCH,
+            }
+        );
+        assert_eq!(
+            sp_io::storage::get(EPOCH_KEY).map(|d| u64::decode(&mut &*d).unwrap()),
+            Some(1)
+        );
+    });
+}
+
+#[test]
+fn imported_block_changes_epoch() {
+    let header = new_test_ext().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(SLOTS_PER_EPOCH + 2))));
+        Executive::close_block()
+    });
+    let block = Block {
+        header,
+        extrinsics: vec![],
+    };
+
+    new_test_ext().execute_with(|| {
+        assert!(Executive::try_execute_block(block).is_ok());
+        assert_eq!(Executive::current_epoch(), 1);
+    });
+}
+
+#[test]
+fn zero_slots_per_epoch_is_rejected() {
+    assert_eq!(epoch_of(5, 0), None);
+
+    let config: GenesisConfig = serde_json::from_value(serde_json::json!({
+        "zero_time": ZERO_TIME,
+        "zero_slot": 0,
+        "slot_length": SLOT_LENGTH,
+        "slots_per_epoch": 0,
+        "outputs": [],
+    }))
+    .unwrap();
+    TestExternalities::default().execute_with(|| {
+        assert_eq!(
+            GriffinGenesisConfigBuilder::build(config.clone()),
+            Err("slots_per_epoch must be > 0".into())
+        );
+    });
+
+    let update = ProtocolParametersUpdate {
+        min_fee_a: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(
+        update.apply_to(&config.protocol_parameters),
+        Err(GovernanceError::InvalidParameters(
+            "slots_per_epoch must be > 0"
+        ))
+    );
+}
+
+/// Stores a governance UTxO carrying `pending`, returning its reference.
+fn store_pending_update(pending: &PendingUpdate) -> Input {
+    let token = GovernanceToken {
+        policy: PolicyId::repeat_byte(9),
+        name: AssetName::from(String::from("governance")),
+    };
+    sp_io::storage::set(GOVERNANCE_TOKEN, &token.encode());
+
+    let input = Input {
+        tx_hash: H256::repeat_byte(9),
+        index: 0,
+    };
+    let output = Output::from((
+        Address(vec![0x61; 29]),
+        100,
+        Multiasset::from((token.policy, token.name, 1)),
+        Some(Datum(minicbor::to_vec(pending).unwrap())),
+    ));
+    TransparentUtxoSet::store_utxo(input.clone(), &output);
+
+    input
+}
+
+#[test]
+fn pending_update_is_applied_at_its_epoch_boundary() {
+    new_test_ext().execute_with(|| {
+        let pending = PendingUpdate {
+            epoch: 1,
+            update: ProtocolParametersUpdate {
+                min_fee_b: Some(7),
+                ..Default::default()
+            },
+        };
+        let source = store_pending_update(&pending);
+        let previous = Executive::protocol_parameters().unwrap();
+
+        Executive::open_block(&header(1, Some(aura_slot(SLOTS_PER_EPOCH - 1))));
+        assert_eq!(Executive::protocol_parameters(), Some(previous.clone()));
+        assert_eq!(Executive::pending_parameters_update(), Some(pending));
+        assert_eq!(Executive::last_parameters_update(), None);
+
+        Executive::open_block(&header(2, Some(aura_slot(SLOTS_PER_EPOCH))));
+        let updated = Executive::protocol_parameters().unwrap();
+        assert_eq!(updated.min_fee_b, 7);
+        assert_eq!(Executive::pending_parameters_update(), None);
+        assert_eq!(
+            Executive::last_parameters_update(),
+            Some(AppliedUpdate {
+                epoch: 1,
+                source,
+                previous,
+            })
+        );
+
+        // The update is not applied again at the following boundaries.
+        sp_io::storage::set(
+            PROTOCOL_PARAMETERS,
+            &ProtocolParameters {
+                min_fee_b: 0,
+                ..updated
+            }
+            .encode(),
+        );
+        Executive::open_block(&header(3, Some(aura_slot(2 * SLOTS_PER_EPOCH))));
+        assert_eq!(Executive::protocol_parameters().unwrap().min_fee_b, 0);
+    });
+}
+
+#[test]
+fn invalid_pending_update_is_ignored() {
+    new_test_ext().execute_with(|| {
+        store_pending_update(&PendingUpdate {
+            epoch: 1,
+            update: ProtocolParametersUpdate {
+                min_fee_b: Some(7),
+                ..Default::default()
+            },
+        });
+        let previous = Executive::protocol_parameters();
+
+        // A second governance UTxO makes the update ambiguous.
+        let mut ambiguous = TransparentUtxoSet::peek_utxo(&Input {
+            tx_hash: H256::repeat_byte(9),
+            index: 0,
+        })
+        .unwrap();
+        ambiguous.datum_option = None;
+        TransparentUtxoSet::store_utxo(
+            Input {
+                tx_hash: H256::repeat_byte(9),
+                index: 1,
+            },
+            &ambiguous,
+        );
+
+        Executive::open_block(&header(1, Some(aura_slot(SLOTS_PER_EPOCH))));
+        assert_eq!(Executive::protocol_parameters(), previous);
+        assert_eq!(Executive::last_parameters_update(), None);
+    });
+}
+
+#[test]
+fn validity_interval_is_checked_against_aura_slot() {
+    // Transaction body with no inputs nor outputs, a zero fee and TTL 4.
+    let cbor = hex::decode("a40080018002000304").unwrap();
+    let tx_body: MintedTransactionBody = minicbor::decode(&cbor).unwrap();
+
+    new_test_ext().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::open_block(&header(2, Some(aura_slot(2))));
+        assert!(check_tx_validity_interval(&tx_body, &Executive::current_slot()).is_ok());
+
+        // The third block is at slot 5, past the TTL, even though only three
+        // blocks have been built.
+        Executive::open_block(&header(3, Some(aura_slot(5))));
+        assert!(check_tx_validity_interval(&tx_body, &Executive::current_slot()).is_err());
+    });
+}
+
+/// An empty block 1 built on top of genesis, at slot 1.
+fn empty_block() -> Block {
+    let header = new_test_ext().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::close_block()
+    });
+
+    Block {
+        header,
+        extrinsics: vec![],
+    }
+}
+
+/// A block 1 with the given extrinsics, whose header is otherwise the one of
+/// [`empty_block`].
+fn block_with(extrinsics: Vec<Transaction>) -> Block {
+    Block {
+        extrinsics,
+        ..empty_block()
+    }
+}
+
+fn execute(block: Block) -> Result<(), BlockError> {
+    new_test_ext().execute_with(|| Executive::try_execute_block(block))
+}
+
+fn missing_input() -> Input {
+    Input {
+        tx_hash: H256::repeat_byte(0xab),
+        index: 0,
+    }
+}
+
+#[test]
+fn valid_block_is_accepted() {
+    assert!(execute(empty_block()).is_ok());
+}
+
+#[test]
+fn block_spending_missing_input_is_rejected() {
+    let tx = Transaction::from((vec![missing_input()], vec![]));
+    let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+
+    match execute(block_with(vec![tx])) {
+        Err(BlockError::InvalidExtrinsic {
+            index: 0,
+            tx_hash: hash,
+            error: UTxOError::Babbage(BabbageError::InputNotInUTxO),
+        }) => assert_eq!(hash, tx_hash),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn block_with_malformed_inline_datum_is_rejected() {
+    let output = Output::from((
+        address_from_pk(&Pair::from_seed(&[0; 32]).public()),
+        2_000_000,
+        Datum(vec![0xff]),
+    ));
+    let tx = Transaction::from((vec![missing_input()], vec![output]));
+
+    match execute(block_with(vec![tx])) {
+        Err(BlockError::InvalidExtrinsic {
+            index: 0, error, ..
+        }) => {
+            assert!(matches!(
+                error,
+                UTxOError::Conversion(PallasConversionError::MalformedPlutusData)
+            ));
+            assert_eq!(error.code(), 187);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn block_with_duplicate_input_is_rejected() {
+    let tx = Transaction::from((vec![missing_input(), missing_input()], vec![]));
+
+    assert!(matches!(
+        execute(block_with(vec![tx])),
+        Err(BlockError::InvalidExtrinsic {
+            index: 0,
+            error: UTxOError::Babbage(BabbageError::DuplicateInput),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn block_with_transaction_without_inputs_is_rejected() {
+    let tx = Transaction::from((vec![], vec![]));
+
+    match execute(block_with(vec![tx])) {
+        Err(BlockError::InvalidExtrinsic {
+            index: 0, error, ..
+        }) => {
+            assert!(matches!(
+                error,
+                UTxOError::Babbage(BabbageError::TxInsEmpty)
+            ));
+            assert_eq!(error.code(), 197);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn block_with_wrong_state_root_is_rejected() {
+    let mut block = empty_block();
+    block.header.state_root = H256::repeat_byte(1);
+
+    assert!(matches!(
+        execute(block),
+        Err(BlockError::StateRootMismatch { expected, .. }) if expected == H256::repeat_byte(1)
+    ));
+}
+
+#[test]
+fn block_without_protocol_parameters_is_rejected() {
+    let block = empty_block();
+
+    new_test_ext().execute_with(|| {
+        sp_io::storage::clear(PROTOCOL_PARAMETERS);
+        assert!(matches!(
+            Executive::try_execute_block(block),
+            Err(BlockError::MissingProtocolParameters)
+        ));
+    });
+}
+
+#[test]
+fn executed_block_is_committed() {
+    let block = empty_block();
+
+    new_test_ext().execute_with(|| {
+        Executive::execute_block(block.clone());
+        assert_eq!(Executive::block_height(), 1);
+        assert_eq!(sp_io::storage::get(REJECTED_BLOCK_KEY), None);
+        assert_eq!(
+            &sp_io::storage::root(StateVersion::V1)[..],
+            block.header.state_root.as_bytes()
+        );
+    });
+}
+
+#[test]
+fn invalid_block_is_rolled_back_without_panicking() {
+    let block = block_with(vec![Transaction::from((vec![missing_input()], vec![]))]);
+
+    new_test_ext().execute_with(|| {
+        Executive::execute_block(block.clone());
+
+        // Only the hash of the header is left, which no state root matches.
+        assert_eq!(Executive::block_height(), 0);
+        assert_eq!(Executive::current_slot(), 0);
+        assert_eq!(
+            sp_io::storage::get(REJECTED_BLOCK_KEY),
+            Some(block.header.hash().encode().into())
+        );
+        assert_ne!(
+            &sp_io::storage::root(StateVersion::V1)[..],
+            block.header.state_root.as_bytes()
+        );
+    });
+}
+
+#[test]
+fn block_with_wrong_extrinsics_root_is_rejected() {
+    let mut block = empty_block();
+    block.header.extrinsics_root = H256::repeat_byte(1);
+
+    assert!(matches!(
+        execute(block),
+        Err(BlockError::ExtrinsicsRootMismatch { expected, .. }) if expected == H256::repeat_byte(1)
+    ));
+}
+
+#[test]
+fn block_with_forged_authority_set_change_is_rejected() {
+    let mut block = empty_block();
+    block
+        .header
+        .digest
+        .push(DigestItem::Consensus(GRANDPA_ENGINE_ID, vec![]));
+
+    assert!(matches!(
+        execute(block),
+        Err(BlockError::DigestLogsMismatch)
+    ));
+}
+
+#[test]
+fn block_with_unexpected_committee_selection_is_rejected() {
+    let next = NextCommittee {
+        epoch: 1,
+        members: vec![],
+    };
+
+    // Committees are not selected on chains without a selection configuration.
+    assert!(matches!(
+        execute(block_with(vec![next.to_inherent()])),
+        Err(BlockError::UnexpectedCommitteeSelection {
+            due: None,
+            announced: 1,
+        })
+    ));
+}
+
+/// Test externalities of a chain selecting its committees from Cardano.
+fn new_test_ext_with_committee_selection() -> TestExternalities {
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        let config = CommitteeSelectionConfig {
+            genesis_utxo: UtxoId {
+                tx_hash: McTxHash([0; 32]),
+                index: UtxoIndex(0),
+            },
+            committee_candidate_address: Default::default(),
+            d_parameter_policy_id: Default::default(),
+            permissioned_candidates_policy_id: Default::default(),
+        };
+        sp_io::storage::set(COMMITTEE_SELECTION, &config.encode());
+    });
+    ext
+}
+
+fn committee_member(seed: u8) -> CommitteeMember {
+    CommitteeMember {
+        aura: sr25519::Public::from_raw([seed; 32]).into(),
+        grandpa: ed25519::Public::from_raw([seed; 32]).into(),
+    }
+}
+
+/// The committee inherent selecting `members` for `epoch`.
+fn committee_inherent(epoch: u64, members: Vec<CommitteeMember>) -> Transaction {
+    NextCommittee { epoch, members }.to_inherent()
+}
+
+/// Block 1 selecting a committee made of `members` for epoch 1, as authored
+/// by an honest node.
+fn block_selecting(members: Vec<CommitteeMember>) -> Block {
+    let inherent = committee_inherent(1, members);
+    let header = new_test_ext_with_committee_selection().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_extrinsic(inherent.clone())
+            .unwrap()
+            .unwrap();
+        Executive::close_block()
+    });
+
+    Block {
+        header,
+        extrinsics: vec![inherent],
+    }
+}
+
+#[test]
+fn committee_inherent_is_applied_and_announced() {
+    let (header, inherent) = new_test_ext_with_committee_selection().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+
+        // As built from the inherent data by the runtime.
+        let epoch = Executive::next_unset_epoch().unwrap();
+        let inherent = committee_inherent(epoch, vec![committee_member(1)]);
+        assert_eq!(
+            NextCommittee::from_inherent(&inherent),
+            Some(NextCommittee {
+                epoch: 1,
+                members: vec![committee_member(1)],
+            })
+        );
+
+        Executive::apply_extrinsic(inherent.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(Executive::next_unset_epoch(), None);
+        (Executive::close_block(), inherent)
+    });
+    let next = NextCommittee {
+        epoch: 1,
+        members: vec![committee_member(1)],
+    };
+    assert!(header
+        .digest
+        .logs
+        .contains(&DigestItem::Consensus(COMMITTEE_ENGINE_ID, next.encode())));
+
+    // Importing nodes decode the inherent from the block they receive.
+    let extrinsic = Transaction::decode(&mut &inherent.encode()[..]).unwrap();
+    assert_eq!(NextCommittee::from_inherent(&extrinsic), Some(next.clone()));
+    let block = Block {
+        header,
+        extrinsics: vec![extrinsic],
+    };
+
+    new_test_ext_with_committee_selection().execute_with(|| {
+        assert!(Executive::try_execute_block(block).is_ok());
+        assert_eq!(Executive::next_committee(), Some(next));
+    });
+}
+
+#[test]
+fn transaction_resembling_a_committee_inherent_is_not_one() {
+    let mut inherent = committee_inherent(1, vec![committee_member(1)]);
+    inherent.transaction_body.fee = 1;
+
+    assert_eq!(NextCommittee::from_inherent(&inherent), None);
+    assert_eq!(NextCommittee::from_inherent(&Transaction::default()), None);
+}
+
+#[test]
+fn block_selecting_its_committee_is_accepted() {
+    let block = block_selecting(vec![committee_member(1)]);
+
+    new_test_ext_with_committee_selection().execute_with(|| {
+        assert!(Executive::try_execute_block(block).is_ok());
+        assert_eq!(
+            Executive::next_committee(),
+            Some(NextCommittee {
+                epoch: 1,
+                members: vec![committee_member(1)],
+            })
+        );
+    });
+}
+
+#[test]
+fn block_announcing_another_committee_than_its_inherent_is_rejected() {
+    let mut block = block_selecting(vec![committee_member(1)]);
+    let forged = NextCommittee {
+        epoch: 1,
+        members: vec![committee_member(2)],
+    };
+    block
+        .header
+        .digest
+        .logs
+        .retain(|log| !matches!(log.as_consensus(), Some((id, _)) if id == COMMITTEE_ENGINE_ID));
+    block
+        .header
+        .digest
+        .push(DigestItem::Consensus(COMMITTEE_ENGINE_ID, forged.encode()));
+
+    new_test_ext_with_committee_selection().execute_with(|| {
+        assert!(matches!(
+            Executive::try_execute_block(block),
+            Err(BlockError::DigestLogsMismatch)
+        ));
+    });
+}
+
+#[test]
+fn block_with_second_committee_inherent_is_rejected() {
+    let mut block = block_selecting(vec![committee_member(1)]);
+    block
+        .extrinsics
+        .push(committee_inherent(1, vec![committee_member(2)]));
+
+    new_test_ext_with_committee_selection().execute_with(|| {
+        assert!(matches!(
+            Executive::try_execute_block(block),
+            Err(BlockError::UnexpectedCommitteeSelection {
+                due: None,
+                announced: 1,
+            })
+        ));
+    });
+}
+
+/// The GRANDPA log scheduling the change to the authority set of `members`.
+fn authority_set_change(members: &[CommitteeMember]) -> DigestItem {
+    let change = ScheduledChange {
+        next_authorities: members
+            .iter()
+            .map(|member| (member.grandpa.clone(), 1))
+            .collect(),
+        delay: 0,
+    };
+
+    DigestItem::Consensus(
+        GRANDPA_ENGINE_ID,
+        ConsensusLog::<BlockNumber>::ScheduledChange(change).encode(),
+    )
+}
+
+#[test]
+#[should_panic(expected = "close_block called on a block that was not opened")]
+fn closing_a_block_that_was_not_opened_panics() {
+    new_test_ext().execute_with(|| Executive::close_block());
+}
+
+#[test]
+fn selected_committees_take_office_with_increasing_set_ids() {
+    new_test_ext_with_committee_selection().execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_extrinsic(committee_inherent(1, vec![committee_member(1)]))
+            .unwrap()
+            .unwrap();
+        Executive::close_block();
+        assert_eq!(Executive::current_committee(), None);
+        assert_eq!(Executive::next_unset_epoch(), None);
+
+        Executive::open_block(&header(2, Some(aura_slot(SLOTS_PER_EPOCH))));
+        assert_eq!(Executive::next_unset_epoch(), Some(2));
+        Executive::apply_extrinsic(committee_inherent(2, vec![committee_member(2)]))
+            .unwrap()
+            .unwrap();
+        let closed = Executive::close_block();
+        assert_eq!(
+            Executive::current_committee(),
+            Some(Committee {
+                epoch: 1,
+                set_id: 1,
+                members: vec![committee_member(1)],
+            })
+        );
+        assert!(closed
+            .digest
+            .logs
+            .contains(&authority_set_change(&[committee_member(1)])));
+
+        Executive::open_block(&header(3, Some(aura_slot(2 * SLOTS_PER_EPOCH))));
+        let closed = Executive::close_block();
+        assert_eq!(
+            Executive::current_committee(),
+            Some(Committee {
+                epoch: 2,
+                set_id: 2,
+                members: vec![committee_member(2)],
+            })
+        );
+        assert!(closed
+            .digest
+            .logs
+            .contains(&authority_set_change(&[committee_member(2)])));
+
+        // No committee was selected for epoch 3, so the current one stays in
+        // office and the authority set is left unchanged.
+        Executive::open_block(&header(4, Some(aura_slot(3 * SLOTS_PER_EPOCH))));
+        let closed = Executive::close_block();
+        assert_eq!(Executive::current_committee().unwrap().set_id, 2);
+        assert!(!closed
+            .digest
+            .logs
+            .iter()
+            .any(|log| matches!(log.as_consensus(), Some((id, _)) if id == GRANDPA_ENGINE_ID)));
+    });
+}
+
+/// Test externalities whose genesis holds a single output of 100 coins at
+/// `address`, together with the input referencing that output.
+fn new_test_ext_with_output_at(address: &Address) -> (TestExternalities, Input) {
+    new_test_ext_with_output(serde_json::json!({
+        "address": hex::encode(&address.0),
+        "coin": 100,
+        "value": [],
+        "datum": null,
+    }))
+}
+
+/// Test externalities whose genesis holds the single (transparent) `output`,
+/// together with the input referencing it.
+fn new_test_ext_with_output(output: serde_json::Value) -> (TestExternalities, Input) {
+    let genesis_tx = Transaction::from((
+        vec![],
+        vec![transp_to_output(serde_json::from_value(output.clone()).unwrap()).unwrap()],
+    ));
+    let input = Input {
+        tx_hash: BlakeTwo256::hash_of(&genesis_tx.encode()),
+        index: 0,
+    };
+
+    (
+        new_test_ext_with_outputs(serde_json::json!([output])),
+        input,
+    )
+}
+
+fn key_hash(pair: &Pair) -> crate::h224::H224 {
+    PallasHasher::<224>::hash(&pair.public().0).into()
+}
+
+/// Transaction spending `input` into an output of the same value, with the
+/// given native script in its witness set and signed by `signers`.
+fn spend_with_script(
+    input: Input,
+    script: NativeScript,
+    validity_interval_start: Option<u64>,
+    signers: &[&Pair],
+) -> Transaction {
+    let recipient = address_from_pk(&Pair::from_seed(&[0; 32]).public());
+    let mut tx = Transaction::from((
+        vec![input],
+        vec![Output::from((recipient, Value::Coin(100)))],
+    ));
+    tx.transaction_body.validity_interval_start = validity_interval_start;
+    sign(&mut tx, signers);
+    tx.transaction_witness_set.native_script = Some(vec![script]);
+
+    tx
+}
+
+/// Adds to `tx` the signatures of its body by `signers`.
+fn sign(tx: &mut Transaction, signers: &[&Pair]) {
+    let pallas_tx = PallasTransaction::try_from(tx.clone()).unwrap();
+    let cbor_bytes = babbage_tx_to_cbor(&pallas_tx);
+    let tx_hash = babbage_minted_tx_from_cbor(&cbor_bytes)
+        .transaction_body
+        .original_hash();
+    let witnesses: Vec<VKeyWitness> = signers
+        .iter()
+        .map(|pair| {
+            VKeyWitness::from((
+                pair.public().0.to_vec(),
+                pair.sign(tx_hash.as_ref()).0.to_vec(),
+            ))
+        })
+        .collect();
+    if !witnesses.is_empty() {
+        tx.transaction_witness_set.vkeywitness = Some(witnesses);
+    }
+}
+
+/// Validates `tx` within a block at `slot`, in `ext`.
+fn validate_at(mut ext: TestExternalities, slot: u64, tx: &Transaction) -> Result<(), UTxOError> {
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(slot))));
+        Executive::validate_griffin_transaction(tx).map(|_| ())
+    })
+}
+
+/// Signed transaction spending the 100 coins of the genesis output owned by
+/// `owner`, paying `fee`, together with the externalities holding that output.
+fn spend_paying(owner: &Pair, fee: Coin) -> (TestExternalities, Transaction) {
+    let (ext, input) = new_test_ext_with_output_at(&address_from_pk(&owner.public()));
+    let recipient = address_from_pk(&Pair::from_seed(&[0; 32]).public());
+    let mut tx = Transaction::from((
+        vec![input],
+        vec![Output::from((recipient, Value::Coin(100 - fee)))],
+    ));
+    tx.transaction_body.fee = fee;
+    sign(&mut tx, &[owner]);
+
+    (ext, tx)
+}
+
+#[test]
+fn pool_priority_grows_with_the_fee_per_byte() {
+    let owner = Pair::from_seed(&[1; 32]);
+    let priority = |fee| {
+        let (mut ext, tx) = spend_paying(&owner, fee);
+        ext.execute_with(|| {
+            Executive::open_block(&header(1, Some(aura_slot(1))));
+            Executive::validate_griffin_transaction(&tx)
+                .unwrap()
+                .priority
+        })
+    };
+
+    // Both fees are encoded with the same size.
+    assert_eq!(priority(0), 0);
+    assert!(priority(10) > 0);
+    assert!(priority(20) > priority(10));
+}
+
+#[test]
+fn fee_below_the_linear_minimum_is_rejected() {
+    let owner = Pair::from_seed(&[1; 32]);
+    let with_min_fee = |fee| {
+        let (mut ext, tx) = spend_paying(&owner, fee);
+        set_protocol_parameters(&mut ext, |pps| pps.min_fee_b = 15);
+        validate_at(ext, 1, &tx)
+    };
+
+    assert!(with_min_fee(15).is_ok());
+    assert!(matches!(
+        with_min_fee(14),
+        Err(UTxOError::Babbage(BabbageError::FeeBelowMin))
+    ));
+}
+
+/// Validates a transaction spending 100 coins into a single output, once the
+/// protocol parameters have been overridden by `f`.
+fn validate_with_parameters(f: impl FnOnce(&mut ProtocolParameters)) -> Result<(), UTxOError> {
+    let (mut ext, tx) = spend_paying(&Pair::from_seed(&[1; 32]), 0);
+    set_protocol_parameters(&mut ext, f);
+    validate_at(ext, 1, &tx)
+}
+
+#[test]
+fn transaction_larger_than_the_max_tx_size_is_rejected() {
+    assert!(validate_with_parameters(|_| ()).is_ok());
+    assert!(matches!(
+        validate_with_parameters(|pps| pps.max_tx_size = 10),
+        Err(UTxOError::Babbage(BabbageError::MaxTxSizeExceeded))
+    ));
+}
+
+#[test]
+fn output_below_the_min_lovelace_is_rejected() {
+    // The single coin value takes one word, so the output needs 161 coins.
+    assert!(matches!(
+        validate_with_parameters(|pps| pps.coins_per_utxo_word = 1),
+        Err(UTxOError::Babbage(BabbageError::MinLovelaceUnreached))
+    ));
+    assert!(matches!(
+        validate_with_parameters(|pps| pps.min_coin_per_output = 101),
+        Err(UTxOError::Babbage(BabbageError::MinLovelaceUnreached))
+    ));
+}
+
+#[test]
+fn output_value_larger_than_the_max_value_size_is_rejected() {
+    assert!(validate_with_parameters(|pps| pps.max_value_size = 1).is_ok());
+    assert!(matches!(
+        validate_with_parameters(|pps| pps.max_value_size = 0),
+        Err(UTxOError::Babbage(BabbageError::MaxValSizeExceeded))
+    ));
+}
+
+fn two_of_three() -> (NativeScript, [Pair; 3]) {
+    let pairs = [1, 2, 3].map(|seed| Pair::from_seed(&[seed; 32]));
+    let script = NativeScript::ScriptNOfK(
+        2,
+        pairs
+            .iter()
+            .map(|pair| NativeScript::ScriptPubkey(key_hash(pair)))
+            .collect(),
+    );
+
+    (script, pairs)
+}
+
+#[test]
+fn multisig_spend_signed_by_enough_keys_is_valid() {
+    let (script, [alice, _, carol]) = two_of_three();
+    let (ext, input) = new_test_ext_with_output_at(&address_from_native_script(script.clone()));
+    let tx = spend_with_script(input, script, None, &[&alice, &carol]);
+
+    assert!(validate_at(ext, 1, &tx).is_ok());
+}
+
+#[test]
+fn multisig_spend_signed_by_too_few_keys_is_rejected() {
+    let (script, [_, bob, _]) = two_of_three();
+    let (ext, input) = new_test_ext_with_output_at(&address_from_native_script(script.clone()));
+    let tx = spend_with_script(input, script, None, &[&bob]);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::NativeScriptUnsatisfied)
+    ));
+    assert_eq!(error.code(), 248);
+}
+
+#[test]
+fn multisig_spend_with_forged_signature_is_rejected() {
+    let (script, [alice, bob, _]) = two_of_three();
+    let (ext, input) = new_test_ext_with_output_at(&address_from_native_script(script.clone()));
+    let mut tx = spend_with_script(input, script, None, &[&alice, &bob]);
+    tx.transaction_witness_set.vkeywitness.as_mut().unwrap()[1].signature = vec![0; 64];
+
+    assert!(validate_at(ext, 1, &tx).is_err());
+}
+
+#[test]
+fn timelocked_spend_is_valid_only_once_unlocked() {
+    let script = NativeScript::InvalidBefore(5);
+    let address = address_from_native_script(script.clone());
+
+    // No key witness is needed to spend from a pure timelock.
+    let (ext, input) = new_test_ext_with_output_at(&address);
+    let tx = spend_with_script(input, script.clone(), Some(5), &[]);
+    assert!(validate_at(ext, 6, &tx).is_ok());
+
+    let (ext, input) = new_test_ext_with_output_at(&address);
+    let tx = spend_with_script(input, script, Some(3), &[]);
+    assert!(matches!(
+        validate_at(ext, 3, &tx),
+        Err(UTxOError::Babbage(BabbageError::NativeScriptUnsatisfied))
+    ));
+}
+
+/// Test externalities whose genesis holds an output guarded by an always
+/// satisfied timelock and carrying the hash of `datum`, together with the
+/// transaction spending it.
+fn spend_datum_hash_output(datum: &Datum) -> (TestExternalities, Transaction) {
+    let script = NativeScript::InvalidBefore(0);
+    let (ext, input) = new_test_ext_with_output(serde_json::json!({
+        "address": hex::encode(&address_from_native_script(script.clone()).0),
+        "coin": 100,
+        "value": [],
+        "datum": null,
+        "datum_hash": hex::encode(compute_datum_hash(datum)),
+    }));
+
+    (ext, spend_with_script(input, script, Some(0), &[]))
+}
+
+/// CBOR of the Plutus integer 42.
+fn sample_datum() -> Datum {
+    Datum(vec![0x18, 0x2a])
+}
+
+#[test]
+fn datum_hash_input_is_spent_with_its_datum_in_witness_set() {
+    let datum = sample_datum();
+    let (ext, mut tx) = spend_datum_hash_output(&datum);
+    tx.transaction_witness_set.plutus_data = Some(vec![PlutusData(datum.0)]);
+
+    assert!(validate_at(ext, 1, &tx).is_ok());
+}
+
+#[test]
+fn datum_hash_input_without_its_datum_is_rejected() {
+    let (ext, tx) = spend_datum_hash_output(&sample_datum());
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::DatumMissing)
+    ));
+    assert_eq!(error.code(), 231);
+}
+
+#[test]
+fn unneeded_witness_datum_is_rejected() {
+    let datum = sample_datum();
+    let (ext, mut tx) = spend_datum_hash_output(&datum);
+    tx.transaction_witness_set.plutus_data =
+        Some(vec![PlutusData(datum.0), PlutusData(vec![0x18, 0x2b])]);
+
+    assert!(matches!(
+        validate_at(ext, 1, &tx),
+        Err(UTxOError::Babbage(BabbageError::UnneededDatum))
+    ));
+}
+
+/// Transaction spending an output guarded by an always satisfied timelock.
+fn spend_timelocked_output() -> (TestExternalities, Transaction) {
+    let script = NativeScript::InvalidBefore(0);
+    let (ext, input) = new_test_ext_with_output_at(&address_from_native_script(script.clone()));
+
+    (ext, spend_with_script(input, script, Some(0), &[]))
+}
+
+/// CBOR of the metadata `{ 674: "hi" }`.
+fn sample_metadata() -> AuxiliaryData {
+    AuxiliaryData(vec![0xa1, 0x19, 0x02, 0xa2, 0x62, 0x68, 0x69])
+}
+
+#[test]
+fn transaction_with_metadata_bound_to_its_body_is_valid() {
+    let (ext, mut tx) = spend_timelocked_output();
+    let metadata = sample_metadata();
+    tx.transaction_body.auxiliary_data_hash = Some(compute_auxiliary_data_hash(&metadata));
+    tx.auxiliary_data = Some(metadata);
+
+    assert!(validate_at(ext, 1, &tx).is_ok());
+}
+
+#[test]
+fn transaction_with_unbound_metadata_is_rejected() {
+    let (ext, mut tx) = spend_timelocked_output();
+    tx.transaction_body.auxiliary_data_hash = Some(H256::repeat_byte(1));
+    tx.auxiliary_data = Some(sample_metadata());
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::MetadataHash)
+    ));
+    assert_eq!(error.code(), 230);
+
+    let (ext, mut tx) = spend_timelocked_output();
+    tx.auxiliary_data = Some(sample_metadata());
+    assert!(matches!(
+        validate_at(ext, 1, &tx),
+        Err(UTxOError::Babbage(BabbageError::MetadataHash))
+    ));
+}
+
+#[test]
+fn transaction_with_undecodable_metadata_is_rejected() {
+    let (ext, mut tx) = spend_timelocked_output();
+    let metadata = AuxiliaryData(vec![0xff]);
+    tx.transaction_body.auxiliary_data_hash = Some(compute_auxiliary_data_hash(&metadata));
+    tx.auxiliary_data = Some(metadata);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Conversion(PallasConversionError::MalformedAuxiliaryData)
+    ));
+    assert_eq!(error.code(), 189);
+}
+
+#[test]
+fn transaction_encoding_round_trips_with_metadata() {
+    let (_, mut tx) = spend_timelocked_output();
+    let metadata = sample_metadata();
+    tx.transaction_body.auxiliary_data_hash = Some(compute_auxiliary_data_hash(&metadata));
+    tx.auxiliary_data = Some(metadata);
+
+    let decoded = <Transaction as parity_scale_codec::Decode>::decode(&mut &tx.encode()[..]);
+    assert_eq!(decoded.unwrap(), tx);
+}
+
+/// Reward account ruled by the always satisfied timelock of
+/// `spend_timelocked_output`.
+fn timelocked_reward_account() -> RewardAccount {
+    reward_account_from_script_hash(&compute_native_script_hash(NativeScript::InvalidBefore(0)))
+}
+
+/// Test externalities where the timelocked reward account holds `balance`,
+/// together with the transaction spending the timelocked output and adding the
+/// given withdrawals to its single output.
+fn withdraw_from_timelocked_account(
+    balance: Coin,
+    withdrawals: Vec<(RewardAccount, Coin)>,
+) -> (TestExternalities, Transaction) {
+    let (mut ext, mut tx) = spend_timelocked_output();
+    ext.execute_with(|| RewardAccounts::deposit(&timelocked_reward_account(), balance));
+    let withdrawn: Coin = withdrawals.iter().map(|(_, amount)| amount).sum();
+    tx.transaction_body.outputs[0].value = Value::Coin(100 + withdrawn);
+    tx.transaction_body.withdrawals = Some(withdrawals);
+
+    (ext, tx)
+}
+
+#[test]
+fn genesis_credits_reward_accounts() {
+    let account = timelocked_reward_account();
+    let config: GenesisConfig = serde_json::from_value(serde_json::json!({
+        "zero_time": ZERO_TIME,
+        "zero_slot": 0,
+        "slot_length": SLOT_LENGTH,
+        "slots_per_epoch": SLOTS_PER_EPOCH,
+        "outputs": [],
+        "reward_accounts": [[hex::encode(&account.0), 50]],
+    }))
+    .unwrap();
+
+    TestExternalities::default().execute_with(|| {
+        GriffinGenesisConfigBuilder::build(config).unwrap();
+        assert_eq!(RewardAccounts::balance(&account), 50);
+        assert_eq!(RewardAccounts::balance(&Address(vec![0xe0; 29])), 0);
+    });
+}
+
+/// Builds a genesis holding `output` and crediting `reward_account`, returning
+/// the error of the genesis builder.
+fn genesis_error(output: serde_json::Value, reward_account: &str) -> String {
+    let config: GenesisConfig = serde_json::from_value(serde_json::json!({
+        "zero_time": ZERO_TIME,
+        "zero_slot": 0,
+        "slot_length": SLOT_LENGTH,
+        "slots_per_epoch": SLOTS_PER_EPOCH,
+        "outputs": [output],
+        "reward_accounts": [[reward_account, 50]],
+    }))
+    .unwrap();
+
+    TestExternalities::default()
+        .execute_with(|| GriffinGenesisConfigBuilder::build(config))
+        .unwrap_err()
+}
+
+#[test]
+fn malformed_genesis_fields_are_named() {
+    let account = hex::encode(timelocked_reward_account().0);
+    let output = |field: &str, value: &str| {
+        let mut output = serde_json::json!({
+            "address": "6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6544b47329c4",
+            "coin": 100,
+            "value": [],
+            "datum": null,
+        });
+        output[field] = serde_json::json!(value);
+        output
+    };
+
+    for field in ["address", "datum", "datum_hash", "script_ref"] {
+        let error = genesis_error(output(field, "zz"), &account);
+        assert!(
+            error.starts_with(&format!("Invalid output {} zz", field)),
+            "{}",
+            error
+        );
+    }
+    let mut with_bad_policy = output("datum", "d87980");
+    with_bad_policy["value"] = serde_json::json!([{ "policy": "zz", "assets": [["tok", 1]] }]);
+    assert!(genesis_error(with_bad_policy, &account).starts_with("Invalid policy zz"));
+    assert!(genesis_error(output("datum", "d87980"), "zz").starts_with("Invalid reward account zz"));
+    // The integer 0 in two bytes, which Pallas re-encodes in one.
+    assert_eq!(
+        genesis_error(output("datum", "1800"), &account),
+        "Invalid output datum 1800: not canonically encoded"
+    );
+}
+
+#[test]
+fn withdrawal_draining_reward_account_is_applied() {
+    let account = timelocked_reward_account();
+    let (mut ext, tx) = withdraw_from_timelocked_account(50, vec![(account.clone(), 50)]);
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_griffin_transaction(&tx).unwrap();
+        assert_eq!(RewardAccounts::balance(&account), 0);
+    });
+}
+
+#[test]
+fn withdrawn_coins_must_be_preserved() {
+    let account = timelocked_reward_account();
+    let (ext, mut tx) = withdraw_from_timelocked_account(50, vec![(account, 50)]);
+    tx.transaction_body.outputs[0].value = Value::Coin(100);
+
+    assert!(matches!(
+        validate_at(ext, 1, &tx),
+        Err(UTxOError::Babbage(BabbageError::PreservationOfValue))
+    ));
+}
+
+#[test]
+fn partial_withdrawal_is_rejected() {
+    let account = timelocked_reward_account();
+    let (ext, tx) = withdraw_from_timelocked_account(50, vec![(account, 20)]);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::IncompleteWithdrawal)
+    ));
+    assert_eq!(error.code(), 249);
+}
+
+#[test]
+fn duplicate_withdrawal_is_rejected() {
+    let account = timelocked_reward_account();
+    let (ext, tx) = withdraw_from_timelocked_account(0, vec![(account.clone(), 0), (account, 0)]);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::DuplicateWithdrawal)
+    ));
+    assert_eq!(error.code(), 250);
+}
+
+#[test]
+fn withdrawal_without_its_script_is_rejected() {
+    let account = reward_account_from_script_hash(&compute_native_script_hash(
+        NativeScript::InvalidBefore(1),
+    ));
+    let (ext, tx) = withdraw_from_timelocked_account(0, vec![(account, 0)]);
+
+    assert!(matches!(
+        validate_at(ext, 1, &tx),
+        Err(UTxOError::Babbage(BabbageError::ScriptWitnessMissing))
+    ));
+}
+
+#[test]
+fn withdrawal_from_malformed_reward_account_is_rejected() {
+    let (ext, tx) = withdraw_from_timelocked_account(0, vec![(Address(vec![0xf0; 3]), 0)]);
+
+    assert!(matches!(
+        validate_at(ext, 1, &tx),
+        Err(UTxOError::Babbage(BabbageError::AddressDecoding))
+    ));
+}
+
+/// Placeholder script bytes; their content is irrelevant to phase one.
+fn sample_plutus_script(version: PlutusVersion) -> PlutusScript {
+    PlutusScript::new(version, vec![0x46, 0x01, 0x00, 0x00, 0x22, 0x00, 0x11])
+}
+
+#[test]
+fn plutus_script_hash_is_prefixed_by_its_version() {
+    for (version, prefix) in [
+        (PlutusVersion::V1, 1),
+        (PlutusVersion::V2, 2),
+        (PlutusVersion::V3, 3),
+    ] {
+        let script = sample_plutus_script(version);
+        let mut payload = vec![prefix];
+        payload.extend_from_slice(&script.bytes);
+
+        assert_eq!(
+            compute_plutus_script_hash(script),
+            PolicyId::from(PallasHasher::<224>::hash(&payload))
+        );
+    }
+}
+
+#[test]
+fn witness_scripts_are_encoded_by_version() {
+    let (_, mut tx) = spend_timelocked_output();
+    tx.transaction_witness_set.plutus_script = Some(vec![
+        sample_plutus_script(PlutusVersion::V1),
+        sample_plutus_script(PlutusVersion::V3),
+    ]);
+
+    let pallas_tx = PallasTransaction::try_from(tx).unwrap();
+    let wits = &pallas_tx.transaction_witness_set;
+    assert_eq!(wits.plutus_v1_script.as_ref().map(Vec::len), Some(1));
+    assert_eq!(wits.plutus_v2_script, None);
+    assert_eq!(wits.plutus_v3_script.as_ref().map(Vec::len), Some(1));
+
+    let conway_tx = conway_minted_tx_from_cbor(&babbage_tx_to_cbor(&pallas_tx));
+    let conway_wits = &conway_tx.transaction_witness_set;
+    assert_eq!(
+        conway_wits.plutus_v1_script.as_ref().map(|s| s.len()),
+        Some(1)
+    );
+    assert_eq!(
+        conway_wits.plutus_v3_script.as_ref().map(|s| s.len()),
+        Some(1)
+    );
+}
+
+#[test]
+fn unneeded_plutus_v3_script_is_rejected() {
+    let (mut ext, mut tx) = spend_timelocked_output();
+    // Without a collateral percentage, the missing collateral goes unnoticed.
+    set_protocol_parameters(&mut ext, |pps| pps.collateral_percentage = 0);
+    tx.transaction_witness_set.plutus_script = Some(vec![sample_plutus_script(PlutusVersion::V3)]);
+    tx.transaction_witness_set.redeemer = Some(vec![]);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::UnneededPlutusV3Script)
+    ));
+    assert_eq!(error.code(), 251);
+}
+
+#[test]
+fn reference_scripts_of_every_plutus_version_are_converted() {
+    for version in [PlutusVersion::V1, PlutusVersion::V2, PlutusVersion::V3] {
+        let mut output = Output::from((Address(vec![0x61; 29]), Value::Coin(100)));
+        output.script_ref = Some(sample_plutus_script(version));
+
+        let pallas_output = PostAlonzoTransactionOutput::try_from(output.clone()).unwrap();
+        assert_eq!(Output::try_from(pallas_output), Ok(output));
+    }
+}
+
+#[test]
+fn native_reference_script_is_not_dropped() {
+    let output = Output::from((Address(vec![0x61; 29]), Value::Coin(100)));
+    let mut pallas_output = PostAlonzoTransactionOutput::try_from(output).unwrap();
+    pallas_output.script_ref = Some(CborWrap(PseudoScript::NativeScript(
+        PallasNativeScript::from(NativeScript::InvalidBefore(1)),
+    )));
+
+    assert_eq!(
+        Output::try_from(pallas_output),
+        Err(PallasConversionError::UnsupportedScriptRef)
+    );
+}
+
+#[test]
+fn utxo_queries_are_paginated_in_key_order() {
+    new_test_ext().execute_with(|| {
+        let address = Address(vec![0x61; 29]);
+        let output = Output::from((address.clone(), Value::Coin(100)));
+        let inputs: Vec<Input> = [0, 1, 256]
+            .into_iter()
+            .map(|index| Input {
+                tx_hash: H256::repeat_byte(7),
+                index,
+            })
+            .collect();
+        for input in &inputs {
+            TransparentUtxoSet::store_utxo(input.clone(), &output);
+        }
+
+        let page = |after: Option<&Input>| -> Vec<Input> {
+            TransparentUtxoSet::peek_utxos_from_address(&address, after, 2)
+                .into_iter()
+                .map(|(input, _)| input)
+                .collect()
+        };
+        // Output indices are ordered by their little-endian encoding.
+        assert_eq!(page(None), vec![inputs[0].clone(), inputs[2].clone()]);
+        assert_eq!(page(Some(&inputs[2])), vec![inputs[1].clone()]);
+        assert_eq!(page(Some(&inputs[1])), vec![]);
+    });
+}
+
+/// Plutus V2 spending validator ignoring its arguments and evaluating `body`.
+#[test]
+fn utxo_indices_follow_stored_and_consumed_utxos() {
+    new_test_ext().execute_with(|| {
+        let address = Address(vec![0x61; 29]);
+        let policy = PolicyId::repeat_byte(3);
+        let name = AssetName::from(String::from("PATATE"));
+        let input = |index| Input {
+            tx_hash: H256::repeat_byte(7),
+            index,
+        };
+        let by_address = || TransparentUtxoSet::peek_utxos_from_address(&address, None, 10);
+        let with_asset = || TransparentUtxoSet::peek_utxos_with_asset(&name, &policy, None, 10);
+        let fingerprint = asset_fingerprint_hash(&policy, &name);
+        let with_fingerprint =
+            || TransparentUtxoSet::peek_utxos_with_fingerprint(&fingerprint, None, 10);
+
+        let coins = Output::from((address.clone(), Value::Coin(100)));
+        let tokens = Output::from((
+            address.clone(),
+            100,
+            Multiasset::from((policy, name.clone(), 1)),
+            None,
+        ));
+        TransparentUtxoSet::store_utxo(input(0), &coins);
+        TransparentUtxoSet::store_utxo(input(1), &tokens);
+        assert_eq!(
+            by_address(),
+            vec![(input(0), coins.clone()), (input(1), tokens.clone())]
+        );
+        assert_eq!(with_asset(), vec![(input(1), tokens.clone())]);
+        assert_eq!(with_fingerprint(), vec![(input(1), tokens)]);
+
+        TransparentUtxoSet::consume_utxo(&input(1));
+        assert_eq!(by_address(), vec![(input(0), coins)]);
+        assert_eq!(with_asset(), vec![]);
+        assert_eq!(with_fingerprint(), vec![]);
+    });
+}
+
+#[test]
+fn applied_transaction_moves_utxos_between_address_indices() {
+    let owner = Pair::from_seed(&[1; 32]);
+    let (mut ext, tx) = spend_paying(&owner, 0);
+    let recipient = tx.transaction_body.outputs[0].address.clone();
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_griffin_transaction(&tx).unwrap();
+
+        let owner_address = address_from_pk(&owner.public());
+        assert_eq!(
+            TransparentUtxoSet::peek_utxos_from_address(&owner_address, None, 10),
+            vec![]
+        );
+        assert_eq!(
+            TransparentUtxoSet::peek_utxos_from_address(&recipient, None, 10),
+            vec![(
+                Input {
+                    tx_hash: BlakeTwo256::hash_of(&tx.encode()),
+                    index: 0,
+                },
+                tx.transaction_body.outputs[0].clone(),
+            )]
+        );
+    });
+}
+
+fn plutus_validator(body: Term<Name>) -> PlutusScript {
+    let program: Program<DeBruijn> = Program {
+        version: (1, 0, 0),
+        term: body.lambda("context").lambda("redeemer").lambda("datum"),
+    }
+    .try_into()
+    .unwrap();
+
+    PlutusScript::new(PlutusVersion::V2, program.to_cbor().unwrap())
+}
+
+/// CBOR of the Plutus constructor `0` without fields.
+const UNIT_DATA: [u8; 3] = [0xd8, 0x79, 0x80];
+
+/// Test externalities whose genesis holds an output locked by `script` and
+/// another one of 100 coins owned by `collateral_owner`, together with the
+/// transaction spending the former and putting up the latter as collateral,
+/// which is returned whole.
+fn spend_plutus_output(
+    script: &PlutusScript,
+    collateral_owner: &Pair,
+) -> (TestExternalities, Transaction) {
+    let script_hash = compute_plutus_script_hash(script.clone());
+    let script_address = Address(hex::decode("70".to_owned() + &hex::encode(script_hash)).unwrap());
+    let collateral_address = address_from_pk(&collateral_owner.public());
+    let outputs = serde_json::json!([
+        {
+            "address": hex::encode(&script_address.0),
+            "coin": 100,
+            "value": [],
+            "datum": hex::encode(UNIT_DATA),
+        },
+        {
+            "address": hex::encode(&collateral_address.0),
+            "coin": 100,
+            "value": [],
+            "datum": null,
+        },
+    ]);
+    let genesis_outputs: Vec<TransparentOutput> = serde_json::from_value(outputs.clone()).unwrap();
+    let genesis_tx = Transaction::from((
+        vec![],
+        genesis_outputs
+            .into_iter()
+            .map(|output| transp_to_output(output).unwrap())
+            .collect(),
+    ));
+    let genesis_input = |index| Input {
+        tx_hash: BlakeTwo256::hash_of(&genesis_tx.encode()),
+        index,
+    };
+
+    let recipient = address_from_pk(&Pair::from_seed(&[0; 32]).public());
+    let mut tx = Transaction::from((
+        vec![genesis_input(0)],
+        vec![Output::from((recipient, Value::Coin(100)))],
+    ));
+    tx.transaction_body.collateral = Some(vec![genesis_input(1)]);
+    tx.transaction_body.collateral_return =
+        Some(Output::from((collateral_address, Value::Coin(100))));
+    sign(&mut tx, &[collateral_owner]);
+    tx.transaction_witness_set.redeemer = Some(vec![Redeemer {
+        tag: RedeemerTag::Spend,
+        index: 0,
+        data: PlutusData(UNIT_DATA.to_vec()),
+        ex_units: ExUnits {
+            mem: 1_000_000,
+            steps: 1_000_000_000,
+        },
+    }]);
+    tx.transaction_witness_set.plutus_script = Some(vec![script.clone()]);
+
+    (new_test_ext_with_outputs(outputs), tx)
+}
+
+/// Applies `tx` within a block at slot 1, in `ext`, and tells which of its
+/// inputs and collateral inputs are still unspent, and which of its outputs
+/// (including the collateral return) were produced.
+fn apply_and_peek(mut ext: TestExternalities, tx: &Transaction) -> (Vec<bool>, Vec<bool>) {
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_griffin_transaction(tx).unwrap();
+
+        let body = &tx.transaction_body;
+        let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+        let unspent = body
+            .inputs
+            .iter()
+            .chain(body.collateral.iter().flatten())
+            .map(|input| TransparentUtxoSet::peek_utxo(input).is_some())
+            .collect();
+        let produced = (0..=body.outputs.len() as u32)
+            .map(|index| TransparentUtxoSet::peek_utxo(&Input { tx_hash, index }).is_some())
+            .collect();
+
+        (unspent, produced)
+    })
+}
+
+#[test]
+fn succeeding_script_transaction_produces_its_outputs() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+
+    // The script input is spent and the collateral is kept.
+    assert_eq!(
+        apply_and_peek(ext, &tx),
+        (vec![false, true], vec![true, false])
+    );
+}
+
+#[test]
+fn failing_script_transaction_flagged_valid_is_rejected() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, tx) = spend_plutus_output(&plutus_validator(Term::Error), &owner);
+    assert!(tx.is_valid);
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+
+        // The pool rejects it with the script error...
+        let error = Executive::validate_griffin_transaction(&tx).unwrap_err();
+        assert!(matches!(error, UTxOError::PhaseTwo(_)));
+
+        // ...and a block including it is invalid, its collateral untouched.
+        let error = Executive::apply_griffin_transaction(&tx).unwrap_err();
+        assert!(matches!(
+            error,
+            UTxOError::Babbage(BabbageError::ValidityFlagMismatch)
+        ));
+        let collateral = tx.transaction_body.collateral.as_deref().unwrap();
+        assert!(TransparentUtxoSet::peek_utxo(&collateral[0]).is_some());
+    });
+}
+
+#[test]
+fn failing_script_transaction_provides_its_collateral_return() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, mut tx) = spend_plutus_output(&plutus_validator(Term::Error), &owner);
+    tx.is_valid = false;
+    let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        let provides = Executive::validate_griffin_transaction(&tx)
+            .unwrap()
+            .provides;
+        assert_eq!(provides, vec![Input { tx_hash, index: 1 }.encode()]);
+    });
+}
+
+#[test]
+fn failing_script_transaction_flagged_invalid_forfeits_its_collateral() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::Error), &owner);
+    tx.is_valid = false;
+
+    assert_eq!(
+        apply_and_peek(ext, &tx),
+        (vec![true, false], vec![false, true])
+    );
+}
+
+#[test]
+fn succeeding_script_transaction_flagged_invalid_is_rejected() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    tx.is_valid = false;
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::ValidityFlagMismatch)
+    ));
+    assert_eq!(error.code(), 199);
+}
+
+#[test]
+fn failing_script_transaction_flagged_invalid_without_collateral_is_rejected() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::Error), &owner);
+    tx.is_valid = false;
+    tx.transaction_body.collateral = None;
+    tx.transaction_body.collateral_return = None;
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::CollateralMissing)
+    ));
+}
+
+#[test]
+fn script_transaction_without_collateral_is_rejected() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    tx.transaction_body.collateral = None;
+    tx.transaction_body.collateral_return = None;
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::CollateralMissing)
+    ));
+    assert_eq!(error.code(), 212);
+}
+
+/// Overrides the protocol parameters stored in `ext`.
+fn set_protocol_parameters(ext: &mut TestExternalities, f: impl FnOnce(&mut ProtocolParameters)) {
+    ext.execute_with(|| {
+        let mut pps = Executive::protocol_parameters().unwrap();
+        f(&mut pps);
+        sp_io::storage::set(PROTOCOL_PARAMETERS, &pps.encode());
+    });
+}
+
+fn declare_ex_units(tx: &mut Transaction, mem: u64, steps: u64) {
+    for redeemer in tx.transaction_witness_set.redeemer.iter_mut().flatten() {
+        redeemer.ex_units = ExUnits { mem, steps };
+    }
+}
+
+#[test]
+fn script_exceeding_its_declared_ex_units_is_reported() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    declare_ex_units(&mut tx, 1, 1);
+    let pallas_tx = PallasTransaction::try_from(tx.clone()).unwrap();
+    let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
+
+    ext.execute_with(|| {
+        let (_, resolved_inputs) =
+            Executive::peek_inputs(&tx.transaction_body.inputs, &mut Vec::new());
+        let evaluated = Executive::run_scripts(&cbor_bytes, resolved_inputs).unwrap();
+        let error = Executive::phase_two_checks(&tx, &evaluated).unwrap_err();
+        assert!(matches!(
+            &error,
+            UTxOError::ExUnitsExceeded { tag, index: 0 } if tag == "Spend"
+        ));
+        assert_eq!(error.code(), 186);
+    });
+}
+
+#[test]
+fn script_exceeding_its_declared_ex_units_forfeits_its_collateral() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    declare_ex_units(&mut tx, 1, 1);
+    tx.is_valid = false;
+
+    assert_eq!(
+        apply_and_peek(ext, &tx),
+        (vec![true, false], vec![false, true])
+    );
+}
+
+#[test]
+fn evaluation_reports_the_ex_units_spent_regardless_of_those_declared() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    declare_ex_units(&mut tx, 1, 1);
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        let evaluation = Executive::evaluate_transaction(&tx);
+        let [redeemer] = &evaluation.redeemers[..] else {
+            panic!("unexpected redeemers: {:?}", evaluation.redeemers);
+        };
+        assert_eq!((redeemer.tag.as_str(), redeemer.index), ("Spend", 0));
+        assert_eq!(evaluation.error.as_ref().map(|err| err.code), Some(186));
+        assert!(redeemer.evaluates(&tx.transaction_witness_set.redeemer.as_ref().unwrap()[0]));
+        assert!(redeemer.ex_units.mem > 1 && redeemer.ex_units.steps > 1);
+
+        // Declaring the evaluated units yields the same evaluation.
+        let ExUnits { mem, steps } = redeemer.ex_units.clone();
+        declare_ex_units(&mut tx, mem, steps);
+        let declared = Executive::evaluate_transaction(&tx);
+        assert_eq!(declared.redeemers, evaluation.redeemers);
+        assert_eq!(declared.error, None);
+    });
+}
+
+#[test]
+fn evaluated_redeemers_are_told_apart_by_tag_and_index() {
+    let redeemer = |tag, index| Redeemer {
+        tag,
+        index,
+        data: PlutusData(UNIT_DATA.to_vec()),
+        ex_units: ExUnits { mem: 0, steps: 0 },
+    };
+    let evaluated = EvaluatedRedeemer {
+        tag: "Withdraw".to_string(),
+        index: 1,
+        ex_units: ExUnits { mem: 0, steps: 0 },
+    };
+
+    assert!(evaluated.evaluates(&redeemer(RedeemerTag::Reward, 1)));
+    assert!(!evaluated.evaluates(&redeemer(RedeemerTag::Reward, 0)));
+    assert!(!evaluated.evaluates(&redeemer(RedeemerTag::Spend, 1)));
+}
+
+#[test]
+fn evaluation_reports_the_min_fee() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    set_protocol_parameters(&mut ext, |pps| {
+        pps.min_fee_a = 0;
+        pps.min_fee_b = 5;
+    });
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        let evaluation = Executive::evaluate_transaction(&tx);
+        assert_eq!(evaluation.min_fee, 5);
+
+        // The transaction pays no fee, so it would be rejected.
+        let error = evaluation.error.unwrap();
+        assert_eq!(
+            error.code,
+            UTxOError::Babbage(BabbageError::FeeBelowMin).code()
+        );
+    });
+}
+
+#[test]
+fn evaluation_reports_the_error_of_scripts_that_cannot_run() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, tx) = spend_plutus_output(&plutus_validator(Term::Error), &owner);
+    let mut missing = tx.clone();
+    missing.transaction_body.inputs = vec![missing_input()];
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        let evaluation = Executive::evaluate_transaction(&missing);
+        assert_eq!(
+            evaluation.error.unwrap().code,
+            UTxOError::Babbage(BabbageError::InputNotInUTxO).code()
+        );
+
+        let evaluation = Executive::evaluate_transaction(&tx);
+        assert!(evaluation.redeemers.is_empty());
+        assert!(evaluation.error.unwrap().message.starts_with("PhaseTwo"));
+    });
+}
+
+#[test]
+fn transaction_exceeding_the_ex_units_limit_is_rejected() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (ext, mut tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    declare_ex_units(&mut tx, 14_000_001, 1_000_000_000);
+
+    let error = validate_at(ext, 1, &tx).unwrap_err();
+    assert!(matches!(
+        error,
+        UTxOError::Babbage(BabbageError::TxExUnitsExceeded)
+    ));
+    assert_eq!(error.code(), 228);
+}
+
+#[test]
+fn applied_transaction_adds_its_ex_units_to_the_block() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        Executive::apply_griffin_transaction(&tx).unwrap();
+
+        let block_ex_units =
+            sp_io::storage::get(BLOCK_EX_UNITS).and_then(|d| ExUnits::decode(&mut &*d).ok());
+        assert_eq!(
+            block_ex_units,
+            Some(ExUnits {
+                mem: 1_000_000,
+                steps: 1_000_000_000,
+            })
+        );
+    });
+}
+
+#[test]
+fn transaction_exceeding_the_block_ex_units_limit_is_not_applied() {
+    let owner = Pair::from_seed(&[9; 32]);
+    let (mut ext, tx) = spend_plutus_output(&plutus_validator(Term::unit()), &owner);
+    // The transaction fits the block on its own, but not after what the block
+    // has already spent.
+    set_protocol_parameters(&mut ext, |pps| {
+        pps.max_block_ex_units = ExUnits {
+            mem: 1_500_000,
+            steps: 1_500_000_000,
+        };
+    });
+
+    ext.execute_with(|| {
+        Executive::open_block(&header(1, Some(aura_slot(1))));
+        sp_io::storage::set(
+            BLOCK_EX_UNITS,
+            &ExUnits {
+                mem: 1_000_000,
+                steps: 0,
+            }
+            .encode(),
+        );
+        let error = Executive::apply_griffin_transaction(&tx).unwrap_err();
+        assert!(matches!(
+            error,
+            UTxOError::Babbage(BabbageError::BlockExUnitsExceeded)
+        ));
+        assert_eq!(error.code(), 234);
+        assert!(TransparentUtxoSet::peek_utxo(&tx.transaction_body.inputs[0]).is_some());
+    });
+}
@@ -1,26 +1,26 @@
 //! Custom GenesisConfigBuilder, to allow extrinsics to be added to the genesis block.
 
 use crate::{
+    committee::CommitteeSelectionConfig,
     ensure,
+    epoch::epoch_of,
     h224::H224,
-    header::PCData,
     pallas_crypto::hash::Hash,
+    protocol_parameters::{GovernanceToken, ProtocolParameters},
+    reward_accounts::RewardAccounts,
     types::{
-        address_from_hex, AssetName, Coin, EncapBTree, Input, Multiasset, Output, Transaction,
+        Address, AssetName, Coin, Datum, DatumOption, EncapBTree, Input, Multiasset, Output,
+        PlutusData, PlutusScript, PlutusVersion, Transaction,
     },
-    DATA_KEY, EXTRINSIC_KEY, SLOT_LENGTH, UTXO_SET, ZERO_SLOT, ZERO_TIME,
-};
-use alloc::{
-    collections::BTreeMap,
-    string::{String, ToString},
-    vec,
-    vec::Vec,
+    utxo_set::TransparentUtxoSet,
+    COMMITTEE_SELECTION, EPOCH_KEY, EXTRINSIC_KEY, GOVERNANCE_TOKEN, PROTOCOL_PARAMETERS,
 };
+use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
 use core::str::FromStr;
 use hex::FromHex;
 use parity_scale_codec::Encode;
 use serde::{Deserialize, Serialize};
-use sp_io::hashing::twox_128;
+use sp_core::H256;
 use sp_runtime::traits::Hash as HashT;
 pub struct GriffinGenesisConfigBuilder;
 
@@ -32,21 +32,45 @@ pub struct TransparentMultiasset<A> {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TransparentOutput {
+    /// Address in bech32, base58 or hex. Hex addresses are taken as they are,
+    /// so that outputs can be locked at addresses no key or script owns, such
+    /// as the all-zero one of the authorities UTxO.
     pub address: String,
     pub coin: Coin,
     pub value: Vec<TransparentMultiasset<Coin>>,
     pub datum: Option<String>,
+    /// Hex-encoded hash of a datum to be supplied when spending the output.
+    /// Takes precedence over `datum`.
+    #[serde(default)]
+    pub datum_hash: Option<String>,
+    #[serde(default)]
+    pub script_ref: Option<String>,
+    /// Plutus language version of `script_ref`, V2 when missing.
+    #[serde(default)]
+    pub script_ref_version: PlutusVersion,
 }
 
 /// Genesis configuration for the Griffin chain.
 /// It contains a list of outputs used to build the transactions
-/// to be included in the genesis block, the initial slot and the initial time.
+/// to be included in the genesis block, and the protocol parameters of the
+/// chain, given at the top level of the configuration.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GenesisConfig {
-    pub zero_slot: u64,
-    pub zero_time: u64,
-    pub slot_length: u32,
+    #[serde(flatten)]
+    pub protocol_parameters: ProtocolParameters,
+    /// Token whose holding UTxO carries protocol parameter updates. Parameters
+    /// can only be changed by a runtime upgrade when absent.
+    #[serde(default)]
+    pub governance_token: Option<GovernanceToken>,
+    /// Cardano data from which committees are selected. The genesis committee
+    /// stays in office forever when absent.
+    #[serde(default)]
+    pub committee_selection: Option<CommitteeSelectionConfig>,
     pub outputs: Vec<TransparentOutput>,
+    /// Initial balances of reward accounts, given in bech32 (`stake1...`) or by
+    /// their hex-encoded bytes.
+    #[serde(default)]
+    pub reward_accounts: Vec<(String, Coin)>,
 }
 
 impl GriffinGenesisConfigBuilder
@@ -56,28 +80,41 @@ where
     /// This function expects the chain's genesis configuration to be passed as a parameter.
     /// It will build the genesis block by creating transactions from the outputs provided
     /// in the genesis configuration. The transactions are created with empty inputs.
-    /// It will also store the zero slot and zero time in the storage.
+    /// It will also store the protocol parameters in the storage.
     pub fn build(genesis_config: GenesisConfig) -> sp_genesis_builder::Result {
-        let transactions = vec![Transaction::from((
-            vec![],
-            genesis_config
-                .outputs
-                .into_iter()
-                .map(transp_to_output)
-                .collect(),
-        ))];
-
-        let pc_data: PCData = PCData {
-            name: "hola".to_string(),
-            count: 0,
-        };
-
-        // The transactions, zero slot and zero time are stored under special keys.
+        genesis_config.protocol_parameters.check()?;
+        let outputs = genesis_config
+            .outputs
+            .into_iter()
+            .map(transp_to_output)
+            .collect::<Result<Vec<_>, _>>()?;
+        let transactions = vec![Transaction::from((vec![], outputs))];
+
+        // The transactions and protocol parameters are stored under special keys.
         sp_io::storage::set(EXTRINSIC_KEY, &transactions.encode());
-        sp_io::storage::set(ZERO_SLOT, &genesis_config.zero_slot.encode());
-        sp_io::storage::set(ZERO_TIME, &genesis_config.zero_time.encode());
-        sp_io::storage::set(SLOT_LENGTH, &genesis_config.slot_length.encode());
-        sp_io::storage::set(DATA_KEY, &pc_data.encode());
+        sp_io::storage::set(
+            PROTOCOL_PARAMETERS,
+            &genesis_config.protocol_parameters.encode(),
+        );
+        // The genesis block opens the epoch of the first slot.
+        let pps = &genesis_config.protocol_parameters;
+        sp_io::storage::set(
+            EPOCH_KEY,
+            &epoch_of(pps.zero_slot, pps.slots_per_epoch)
+                .ok_or("slots_per_epoch must be > 0")?
+                .encode(),
+        );
+        if let Some(token) = genesis_config.governance_token {
+            sp_io::storage::set(GOVERNANCE_TOKEN, &token.encode());
+        }
+        if let Some(selection) = genesis_config.committee_selection {
+            sp_io::storage::set(COMMITTEE_SELECTION, &selection.encode());
+        }
+        for (account, amount) in genesis_config.reward_accounts.iter() {
+            let account = Address::from_str(account)
+                .map_err(|e| format!("Invalid reward account {}: {}", account, e))?;
+            RewardAccounts::deposit(&account, *amount);
+        }
 
         for tx in transactions.into_iter() {
             // Enforce that transactions do not have any inputs.
@@ -88,14 +125,11 @@ where
             // Insert the outputs into the storage.
             let tx_hash = sp_runtime::traits::BlakeTwo256::hash_of(&tx.encode());
             for (index, utxo) in tx.transaction_body.outputs.iter().enumerate() {
-                // TODO: Thing of a way of use add_utxo_prefix function.
-                let utxo_prefix = twox_128(UTXO_SET);
                 let input = Input {
                     tx_hash,
                     index: index as u32,
                 };
-                let key = [&utxo_prefix[..], &input.encode()[..]].concat();
-                sp_io::storage::set(&key, &utxo.encode());
+                TransparentUtxoSet::store_utxo(input, utxo);
             }
         }
 
@@ -103,15 +137,43 @@ where
     }
 }
 
-pub fn transp_to_output(transp: TransparentOutput) -> Output {
-    Output::from((
-        address_from_hex(&transp.address),
+/// Builds an output from its transparent form, failing with a message that
+/// names the offending field.
+pub fn transp_to_output(transp: TransparentOutput) -> Result<Output, String> {
+    let address = match <Vec<u8>>::from_hex(&transp.address) {
+        Ok(bytes) => Address(bytes),
+        Err(_) => Address::from_str(&transp.address)
+            .map_err(|e| format!("Invalid output address {}: {}", transp.address, e))?,
+    };
+    let datum = transp
+        .datum
+        .map(|d| match <Vec<u8>>::from_hex(&d) {
+            Ok(bytes) if PlutusData(bytes.clone()).is_canonical() => Ok(bytes),
+            Ok(_) => Err(format!(
+                "Invalid output datum {}: not canonically encoded",
+                d
+            )),
+            Err(e) => Err(format!("Invalid output datum {}: {}", d, e)),
+        })
+        .transpose()?;
+    let mut output = Output::from((
+        address,
         transp.coin,
-        transp_to_multiasset(transp.value),
-        transp
-            .datum
-            .map(|v| <_>::from(<Vec<u8>>::from_hex(v).unwrap())),
-    ))
+        transp_to_multiasset(transp.value)?,
+        datum.map(Datum::from),
+    ));
+    if let Some(hash) = transp.datum_hash {
+        let hash = <[u8; 32]>::from_hex(&hash)
+            .map_err(|e| format!("Invalid output datum_hash {}: {}", hash, e))?;
+        output.datum_option = Some(DatumOption::Hash(H256::from(hash)));
+    }
+    if let Some(script) = transp.script_ref {
+        let script = <Vec<u8>>::from_hex(&script)
+            .map_err(|e| format!("Invalid output script_ref {}: {}", script, e))?;
+        output.script_ref = Some(PlutusScript::new(transp.script_ref_version, script));
+    }
+
+    Ok(output)
 }
 
 fn transp_to_assets<A>(transp: Vec<(String, A)>) -> EncapBTree<AssetName, A> {
@@ -124,15 +186,16 @@ fn transp_to_assets<A>(transp: Vec<(String, A)>) -> EncapBTree<AssetName, A> {
     EncapBTree(asset_btree)
 }
 
-pub fn transp_to_multiasset<A>(transp: Vec<TransparentMultiasset<A>>) -> Multiasset<A> {
+pub fn transp_to_multiasset<A>(
+    transp: Vec<TransparentMultiasset<A>>,
+) -> Result<Multiasset<A>, String> {
     let mut ma_btree = BTreeMap::new();
 
     for TransparentMultiasset { policy, assets } in transp {
-        ma_btree.insert(
-            H224::from(Hash::from_str(&policy).unwrap()),
-            transp_to_assets(assets),
-        );
+        let policy_id =
+            Hash::from_str(&policy).map_err(|e| format!("Invalid policy {}: {}", policy, e))?;
+        ma_btree.insert(H224::from(policy_id), transp_to_assets(assets));
     }
 
-    EncapBTree(ma_btree)
+    Ok(EncapBTree(ma_btree))
 }
@@ -1,8 +1,8 @@
 use crate::DATA_KEY;
-use alloc::string::String;
 use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
+use sp_core::H256;
 use sp_runtime::{
     generic::Digest,
     traits::{BlakeTwo256, Hash as HashT, Header as HeaderT},
@@ -12,6 +12,7 @@ pub type Hash = BlakeTwo256;
 pub type OpaqueHash = <Hash as HashT>::Output;
 pub type BlockNumber = u32;
 
+/// Partner chain data committed to by a block.
 #[derive(
     Encode,
     Decode,
@@ -25,8 +26,8 @@ pub type BlockNumber = u32;
     Deserialize,
 )]
 pub struct PCData {
-    pub name: String,
-    pub count: u32,
+    /// Hash of the stable Cardano block referenced by the block.
+    pub mc_hash: H256,
 }
 
 #[derive(
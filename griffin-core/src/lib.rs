@@ -20,9 +20,13 @@ extern crate alloc;
 mod executive;
 
 pub mod checks_interface;
+pub mod committee;
+pub mod epoch;
+pub mod evaluation;
 pub mod genesis;
 pub mod h224;
 pub mod header;
+pub mod main_chain;
 pub mod pallas_addresses;
 pub mod pallas_applying;
 pub mod pallas_codec;
@@ -30,6 +34,8 @@ pub mod pallas_crypto;
 pub mod pallas_interface;
 pub mod pallas_primitives;
 pub mod pallas_traverse;
+pub mod protocol_parameters;
+pub mod reward_accounts;
 pub mod support_macros;
 pub mod types;
 pub mod uplc;
@@ -37,14 +43,35 @@ use h224::H224;
 pub mod utxo_set;
 pub use executive::Executive;
 
-/// The Aura slot duration. When things are working well, this will also be the block time.
-pub const SLOT_LENGTH: &[u8] = b"slot-length";
+/// A storage key that will store the protocol parameters of the chain.
+pub const PROTOCOL_PARAMETERS: &[u8] = b"protocol-parameters";
 
-/// A storage key that will store the slot number of the first block in the chain.
-pub const ZERO_SLOT: &[u8] = b"zero-slot";
+/// A storage key that will store the token identifying the UTxO that carries
+/// protocol parameter updates, if the chain is governed by one.
+pub const GOVERNANCE_TOKEN: &[u8] = b"governance-token";
 
-/// A storage key that will store the start POSIX time of ZERO_SLOT, in milliseconds.
-pub const ZERO_TIME: &[u8] = b"zero-time";
+/// A storage key that will store the record of the last protocol parameters
+/// update applied.
+pub const LAST_PARAMETERS_UPDATE: &[u8] = b"last-parameters-update";
+
+/// A storage key that will store the Cardano data used to select committees,
+/// if the committee of the chain is selected from Cardano registrations.
+pub const COMMITTEE_SELECTION: &[u8] = b"committee-selection";
+
+/// A storage key that will store the committee in office, once the genesis
+/// committee has been replaced.
+pub const CURRENT_COMMITTEE: &[u8] = b"current-committee";
+
+/// A storage key that will store the committee selected for the next epoch,
+/// until it takes office.
+pub const NEXT_COMMITTEE: &[u8] = b"next-committee";
+
+/// A storage key that will store the epoch of the latest block.
+pub const EPOCH_KEY: &[u8] = b"epoch";
+
+/// A storage key that will store the slot of the latest block, as read from
+/// its Aura pre-runtime digest.
+pub const SLOT_KEY: &[u8] = b"slot";
 
 /// A Griffin-specific target for diagnostic node log messages
 const LOG_TARGET: &str = "griffin-core";
@@ -61,9 +88,36 @@ pub const HEIGHT_KEY: &[u8] = b"height";
 /// execution. This allows the information to be available in the runtime.
 pub const DATA_KEY: &[u8] = b"pcdata";
 
+/// A transient storage key that will hold the execution units declared by the
+/// extrinsics applied so far. This key is cleared before the end of the block.
+const BLOCK_EX_UNITS: &[u8] = b"block-ex-units";
+
+/// A transient storage key that will hold the digest logs deposited while a
+/// block is being built. This key is cleared before the end of the block.
+const LOGS_KEY: &[u8] = b"logs";
+
+/// A storage key that will hold the hash of the header of a block rejected by
+/// execution. The hash commits to the state root in the header, so the state
+/// holding it never has that root, and the client discards the block.
+const REJECTED_BLOCK_KEY: &[u8] = b"rejected-block";
+
 /// A transient storage key that will hold the list of extrinsics that have been applied so far.
 /// This key is cleared before the end of the block.
 pub const EXTRINSIC_KEY: &[u8] = b"extrinsics";
 
 /// A storage key prefix that will be used to classify the UTxO set.
 pub const UTXO_SET: &[u8] = b"utxos";
+
+/// A storage key prefix under which the UTxO set is indexed by address.
+pub const UTXO_BY_ADDRESS: &[u8] = b"utxos-by-address";
+
+/// A storage key prefix under which the UTxO set is indexed by policy ID and
+/// asset name.
+pub const UTXO_BY_ASSET: &[u8] = b"utxos-by-asset";
+
+/// A storage key prefix under which the UTxO set is indexed by the CIP-14
+/// fingerprint of the assets it holds.
+pub const UTXO_BY_FINGERPRINT: &[u8] = b"utxos-by-fingerprint";
+
+/// A storage key prefix under which the balances of reward accounts are kept.
+pub const REWARD_ACCOUNTS: &[u8] = b"reward-accounts";
@@ -0,0 +1,38 @@
+//! Observation of the Cardano main chain.
+//!
+//! Each block commits to a stable Cardano block, whose hash is put by the
+//! block author in a pre-runtime digest of the header through the Partner
+//! Chains toolkit's `McHashInherentDigest`. Nodes check on import, through the
+//! toolkit's `McHashInherentDataProvider`, that the referenced block is stable
+//! and does not precede the one referenced by the parent block. The runtime
+//! records the reference of each block in its [`PCData`].
+//!
+//! [`PCData`]: crate::header::PCData
+
+use sp_core::H256;
+use sp_runtime::{ConsensusEngineId, Digest, DigestItem};
+
+/// Identifier of the pre-runtime digest holding the main chain reference hash,
+/// as set by the toolkit's `McHashInherentDigest`.
+pub const MC_HASH_DIGEST_ID: ConsensusEngineId = *b"mcsh";
+
+/// The main chain reference hash in `digest`, if any.
+pub fn mc_hash_from_digest(digest: &Digest) -> Option<H256> {
+    digest.logs().iter().find_map(|log| match log {
+        DigestItem::PreRuntime(id, data) if *id == MC_HASH_DIGEST_ID => {
+            <[u8; 32]>::try_from(&data[..]).ok().map(H256)
+        }
+        _ => None,
+    })
+}
+
+mod api_declarations {
+    use super::*;
+    sp_api::decl_runtime_apis! {
+        pub trait MainChainApi {
+            /// Hash of the main chain block referenced by the latest block.
+            fn main_chain_hash() -> Option<H256>;
+        }
+    }
+}
+pub use api_declarations::*;
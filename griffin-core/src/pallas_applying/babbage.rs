@@ -1,12 +1,14 @@
 //! Utilities required for Babbage-era transaction validation.
 
-use crate::pallas_addresses::{ScriptHash, ShelleyAddress, ShelleyPaymentPart};
+use crate::pallas_addresses::{
+    Address, ScriptHash, ShelleyAddress, ShelleyPaymentPart, StakeAddress, StakePayload,
+};
 use crate::pallas_applying::utils::{
     add_minted_value, add_values, aux_data_from_babbage_minted_tx, compute_native_script_hash,
-    compute_plutus_v1_script_hash, compute_plutus_v2_script_hash, empty_value, get_babbage_tx_size,
-    get_lovelace_from_alonzo_val, get_payment_part, get_shelley_address, get_val_size_in_words,
-    is_byron_address, lovelace_diff_or_fail, mk_alonzo_vk_wits_check_list, values_are_equal,
-    verify_signature,
+    compute_plutus_v1_script_hash, compute_plutus_v2_script_hash, compute_plutus_v3_script_hash,
+    empty_value, get_babbage_tx_size, get_lovelace_from_alonzo_val, get_payment_part,
+    get_shelley_address, get_val_size_in_words, is_byron_address, lovelace_diff_or_fail,
+    values_are_equal, verify_signature,
     BabbageError::*,
     BabbageProtParams, UTxOs,
     ValidationError::{self, *},
@@ -154,7 +156,7 @@ pub fn check_fee(
 
 // The fee paid by the transaction should be greater than or equal to the
 // minimum fee.
-fn check_min_fee(
+pub fn check_min_fee(
     tx_body: &MintedTransactionBody,
     size: &u32,
     prot_pps: &BabbageProtParams,
@@ -175,10 +177,14 @@ fn presence_of_plutus_scripts(mtx: &MintedTx) -> bool {
         .plutus_v2_script
         .clone()
         .unwrap_or_default();
-    !plutus_v1_scripts.is_empty() || !plutus_v2_scripts.is_empty()
+    let plutus_v3_scripts: &[PlutusScript<3>] = &minted_witness_set
+        .plutus_v3_script
+        .clone()
+        .unwrap_or_default();
+    !plutus_v1_scripts.is_empty() || !plutus_v2_scripts.is_empty() || !plutus_v3_scripts.is_empty()
 }
 
-fn check_collaterals(
+pub fn check_collaterals(
     tx_body: &MintedTransactionBody,
     utxos: &UTxOs,
     prot_pps: &BabbageProtParams,
@@ -325,6 +331,9 @@ fn get_consumed(tx_body: &MintedTransactionBody, utxos: &UTxOs) -> Result<Value,
         let val: Value = val_from_multi_era_output(multi_era_output);
         res = add_values(&res, &val, &Babbage(NegativeValue))?;
     }
+    for (_, amount) in tx_body.withdrawals.iter().flat_map(|ws| ws.iter()) {
+        res = add_values(&res, &Value::Coin(*amount), &Babbage(NegativeValue))?;
+    }
     Ok(res)
 }
 
@@ -419,7 +428,7 @@ pub fn check_tx_size(size: &u32, prot_pps: &BabbageProtParams) -> ValidationResu
     Ok(())
 }
 
-fn check_tx_ex_units(mtx: &MintedTx, prot_pps: &BabbageProtParams) -> ValidationResult {
+pub fn check_tx_ex_units(mtx: &MintedTx, prot_pps: &BabbageProtParams) -> ValidationResult {
     let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
     if presence_of_plutus_scripts(mtx) {
         match &tx_wits.redeemer {
@@ -463,6 +472,11 @@ pub fn check_minting(tx_body: &MintedTransactionBody, mtx: &MintedTx) -> Validat
                     None => Vec::new(),
                     Some(v2_script_wits) => v2_script_wits.clone(),
                 };
+            let v3_script_wits: Vec<PlutusScript<3>> =
+                match &mtx.transaction_witness_set.plutus_v3_script {
+                    None => Vec::new(),
+                    Some(v3_script_wits) => v3_script_wits.clone(),
+                };
             for (policy, _) in minted_value.iter() {
                 if native_script_wits
                     .iter()
@@ -473,6 +487,9 @@ pub fn check_minting(tx_body: &MintedTransactionBody, mtx: &MintedTx) -> Validat
                     && v2_script_wits
                         .iter()
                         .all(|script| compute_plutus_v2_script_hash(script) != *policy)
+                    && v3_script_wits
+                        .iter()
+                        .all(|script| compute_plutus_v3_script_hash(script) != *policy)
                 {
                     return Err(Babbage(MintingLacksPolicy));
                 }
@@ -516,25 +533,43 @@ pub fn check_witness_set(mtx: &MintedTx, utxos: &UTxOs) -> ValidationResult {
             .collect(),
         None => Vec::new(),
     };
+    let plutus_v3_scripts: Vec<PolicyId> = match &tx_wits.plutus_v3_script {
+        Some(scripts) => scripts
+            .clone()
+            .iter()
+            .map(compute_plutus_v3_script_hash)
+            .collect(),
+        None => Vec::new(),
+    };
     let reference_scripts: Vec<PolicyId> = get_reference_script_hashes(tx_body, utxos);
+    let reference_native_scripts: Vec<(PolicyId, NativeScript)> =
+        get_reference_native_scripts(tx_body, utxos);
+    let reference_plutus_scripts: Vec<PolicyId> = reference_scripts
+        .iter()
+        .filter(|hash| !reference_native_scripts.iter().any(|(ns, _)| ns == *hash))
+        .cloned()
+        .collect();
     check_needed_scripts(
         tx_body,
         utxos,
         &native_scripts,
         &plutus_v1_scripts,
         &plutus_v2_scripts,
+        &plutus_v3_scripts,
         &reference_scripts,
     )?;
     check_datums(tx_body, utxos, &tx_wits.plutus_data)?;
     check_redeemers(
         &plutus_v1_scripts,
         &plutus_v2_scripts,
-        &reference_scripts,
+        &plutus_v3_scripts,
+        &reference_plutus_scripts,
         tx_body,
         tx_wits,
         utxos,
     )?;
     check_required_signers(&tx_body.required_signers, vkey_wits, tx_hash)?;
+    check_native_scripts(tx_body, tx_wits, &reference_native_scripts, utxos, tx_hash)?;
     check_vkey_input_wits(mtx, &tx_wits.vkeywitness, utxos)
 }
 
@@ -547,6 +582,7 @@ fn check_needed_scripts(
     native_scripts: &[PolicyId],
     plutus_v1_scripts: &[PolicyId],
     plutus_v2_scripts: &[PolicyId],
+    plutus_v3_scripts: &[PolicyId],
     reference_scripts: &[PolicyId],
 ) -> ValidationResult {
     let mut filtered_native_scripts: Vec<(bool, PolicyId)> = native_scripts
@@ -567,11 +603,18 @@ fn check_needed_scripts(
         .collect();
     filtered_plutus_v2_scripts
         .retain(|&(_, plutus_v2_script_hash)| !reference_scripts.contains(&plutus_v2_script_hash));
+    let mut filtered_plutus_v3_scripts: Vec<(bool, PolicyId)> = plutus_v3_scripts
+        .iter()
+        .map(|&script_hash| (false, script_hash))
+        .collect();
+    filtered_plutus_v3_scripts
+        .retain(|&(_, plutus_v3_script_hash)| !reference_scripts.contains(&plutus_v3_script_hash));
     check_input_scripts(
         tx_body,
         &mut filtered_native_scripts,
         &mut filtered_plutus_v1_scripts,
         &mut filtered_plutus_v2_scripts,
+        &mut filtered_plutus_v3_scripts,
         reference_scripts,
         utxos,
     )?;
@@ -580,6 +623,15 @@ fn check_needed_scripts(
         &mut filtered_native_scripts,
         &mut filtered_plutus_v1_scripts,
         &mut filtered_plutus_v2_scripts,
+        &mut filtered_plutus_v3_scripts,
+        reference_scripts,
+    )?;
+    check_withdrawal_scripts(
+        tx_body,
+        &mut filtered_native_scripts,
+        &mut filtered_plutus_v1_scripts,
+        &mut filtered_plutus_v2_scripts,
+        &mut filtered_plutus_v3_scripts,
         reference_scripts,
     )?;
     for (covered, _) in filtered_native_scripts.iter() {
@@ -597,6 +649,11 @@ fn check_needed_scripts(
             return Err(Babbage(UnneededPlutusV2Script));
         }
     }
+    for (covered, _) in filtered_plutus_v3_scripts.iter() {
+        if !covered {
+            return Err(Babbage(UnneededPlutusV3Script));
+        }
+    }
     Ok(())
 }
 
@@ -612,11 +669,40 @@ fn get_reference_script_hashes(tx_body: &MintedTransactionBody, utxos: &UTxOs) -
     res
 }
 
+fn get_reference_native_scripts(
+    tx_body: &MintedTransactionBody,
+    utxos: &UTxOs,
+) -> Vec<(PolicyId, NativeScript)> {
+    let mut res: Vec<(PolicyId, NativeScript)> = Vec::new();
+    if let Some(reference_inputs) = &tx_body.reference_inputs {
+        for input in reference_inputs.iter() {
+            if let Some(PseudoTransactionOutput::PostAlonzo(output)) = utxos
+                .get(&MultiEraInput::from_alonzo_compatible(input))
+                .and_then(MultiEraOutput::as_babbage)
+            {
+                if let Some(script_ref_cborwrap) = &output.script_ref {
+                    if let PseudoScript::NativeScript(native_script) =
+                        script_ref_cborwrap.clone().unwrap()
+                    {
+                        if let Some(script_hash) =
+                            get_script_hash_from_reference_input(input, utxos)
+                        {
+                            res.push((script_hash, native_script.unwrap()))
+                        }
+                    }
+                }
+            }
+        }
+    }
+    res
+}
+
 fn check_input_scripts(
     tx_body: &MintedTransactionBody,
     native_scripts: &mut [(bool, PolicyId)],
     plutus_v1_scripts: &mut [(bool, PolicyId)],
     plutus_v2_scripts: &mut [(bool, PolicyId)],
+    plutus_v3_scripts: &mut [(bool, PolicyId)],
     reference_scripts: &[PolicyId],
     utxos: &UTxOs,
 ) -> ValidationResult {
@@ -641,6 +727,12 @@ fn check_input_scripts(
                 *plutus_v2_script_covered = true;
             }
         }
+        for (plutus_v3_script_covered, plutus_v3_script_hash) in plutus_v3_scripts.iter_mut() {
+            if *hash == *plutus_v3_script_hash {
+                *covered = true;
+                *plutus_v3_script_covered = true;
+            }
+        }
     }
     for (covered, hash) in needed_input_scripts {
         if !covered && !reference_scripts.contains(&hash) {
@@ -721,6 +813,15 @@ fn get_script_hash_from_reference_input(
                             &val_to_hash,
                         ));
                     }
+                    PseudoScript::PlutusV3Script(plutus_v3_script) => {
+                        // First, the PlutusV3Script header.
+                        let mut val_to_hash: Vec<u8> = vec![3];
+                        // Then, the CBOR content.
+                        val_to_hash.extend_from_slice(plutus_v3_script.as_ref());
+                        return Some(crate::pallas_crypto::hash::Hasher::<224>::hash(
+                            &val_to_hash,
+                        ));
+                    }
                 }
             }
             None
@@ -734,6 +835,7 @@ fn check_minting_policies(
     native_scripts: &mut [(bool, PolicyId)],
     plutus_v1_scripts: &mut [(bool, PolicyId)],
     plutus_v2_scripts: &mut [(bool, PolicyId)],
+    plutus_v3_scripts: &mut [(bool, PolicyId)],
     reference_scripts: &[PolicyId],
 ) -> ValidationResult {
     match &tx_body.mint {
@@ -760,6 +862,12 @@ fn check_minting_policies(
                         *plutus_script_covered = true;
                     }
                 }
+                for (plutus_script_covered, plutus_v3_script_hash) in plutus_v3_scripts.iter_mut() {
+                    if *policy == *plutus_v3_script_hash {
+                        *policy_covered = true;
+                        *plutus_script_covered = true;
+                    }
+                }
                 for reference_script_hash in reference_scripts.iter() {
                     if *policy == *reference_script_hash {
                         *policy_covered = true;
@@ -776,6 +884,68 @@ fn check_minting_policies(
     }
 }
 
+// Each script ruling a reward account withdrawn from can be found in the
+// transaction witness set or in a reference input.
+fn check_withdrawal_scripts(
+    tx_body: &MintedTransactionBody,
+    native_scripts: &mut [(bool, PolicyId)],
+    plutus_v1_scripts: &mut [(bool, PolicyId)],
+    plutus_v2_scripts: &mut [(bool, PolicyId)],
+    plutus_v3_scripts: &mut [(bool, PolicyId)],
+    reference_scripts: &[PolicyId],
+) -> ValidationResult {
+    for script_hash in get_withdrawal_script_hashes(tx_body)? {
+        let mut covered: bool = reference_scripts.contains(&script_hash);
+        for (script_covered, hash) in native_scripts
+            .iter_mut()
+            .chain(plutus_v1_scripts.iter_mut())
+            .chain(plutus_v2_scripts.iter_mut())
+            .chain(plutus_v3_scripts.iter_mut())
+        {
+            if *hash == script_hash {
+                covered = true;
+                *script_covered = true;
+            }
+        }
+        if !covered {
+            return Err(Babbage(ScriptWitnessMissing));
+        }
+    }
+    Ok(())
+}
+
+// Reward accounts withdrawn from, sorted as their redeemer pointers expect:
+// by network, then script credentials before key ones, then by hash.
+fn sort_withdrawals(tx_body: &MintedTransactionBody) -> Result<Vec<StakeAddress>, ValidationError> {
+    let mut res: Vec<StakeAddress> = Vec::new();
+    for (reward_account, _) in tx_body.withdrawals.iter().flat_map(|ws| ws.iter()) {
+        match Address::from_bytes(reward_account) {
+            Ok(Address::Stake(stake_address)) => res.push(stake_address),
+            _ => return Err(Babbage(AddressDecoding)),
+        }
+    }
+    res.sort_by_key(|stake_address| {
+        (
+            stake_address.network().value(),
+            !stake_address.is_script(),
+            *stake_address.payload().as_hash(),
+        )
+    });
+    Ok(res)
+}
+
+fn get_withdrawal_script_hashes(
+    tx_body: &MintedTransactionBody,
+) -> Result<Vec<ScriptHash>, ValidationError> {
+    Ok(sort_withdrawals(tx_body)?
+        .iter()
+        .filter_map(|stake_address| match stake_address.payload() {
+            StakePayload::Script(script_hash) => Some(*script_hash),
+            StakePayload::Stake(_) => None,
+        })
+        .collect())
+}
+
 // Each datum hash in a Plutus script input matches the hash of a datum in the
 // transaction witness set
 fn check_datums(
@@ -921,6 +1091,7 @@ fn find_datum(hash: &Hash<32>, tx_body: &MintedTransactionBody, utxos: &UTxOs) -
 fn check_redeemers(
     plutus_v1_scripts: &[PolicyId],
     plutus_v2_scripts: &[PolicyId],
+    plutus_v3_scripts: &[PolicyId],
     reference_scripts: &[PolicyId],
     tx_body: &MintedTransactionBody,
     tx_wits: &MintedWitnessSet,
@@ -939,10 +1110,11 @@ fn check_redeemers(
     let plutus_scripts: Vec<RedeemerPointer> = mk_plutus_script_redeemer_pointers(
         plutus_v1_scripts,
         plutus_v2_scripts,
+        plutus_v3_scripts,
         reference_scripts,
         tx_body,
         utxos,
-    );
+    )?;
     redeemer_pointers_coincide(&redeemer_pointers, &plutus_scripts)
 }
 
@@ -956,14 +1128,23 @@ fn sort_inputs(unsorted_inputs: &[TransactionInput]) -> Vec<TransactionInput> {
 fn mk_plutus_script_redeemer_pointers(
     plutus_v1_scripts: &[PolicyId],
     plutus_v2_scripts: &[PolicyId],
+    plutus_v3_scripts: &[PolicyId],
     reference_scripts: &[PolicyId],
     tx_body: &MintedTransactionBody,
     utxos: &UTxOs,
-) -> Vec<RedeemerPointer> {
+) -> Result<Vec<RedeemerPointer>, ValidationError> {
     let mut res: Vec<RedeemerPointer> = Vec::new();
     let sorted_inputs: &Vec<TransactionInput> = &sort_inputs(&tx_body.inputs);
     for (index, input) in sorted_inputs.iter().enumerate() {
-        if get_script_hash_from_input(input, utxos).is_some() {
+        if get_script_hash_from_input(input, utxos).is_some_and(|script_hash| {
+            is_phase_2_script(
+                &script_hash,
+                plutus_v1_scripts,
+                plutus_v2_scripts,
+                plutus_v3_scripts,
+                reference_scripts,
+            )
+        }) {
             res.push(RedeemerPointer {
                 tag: RedeemerTag::Spend,
                 index: index as u32,
@@ -976,6 +1157,7 @@ fn mk_plutus_script_redeemer_pointers(
                 policy,
                 plutus_v1_scripts,
                 plutus_v2_scripts,
+                plutus_v3_scripts,
                 reference_scripts,
             ) {
                 res.push(RedeemerPointer {
@@ -985,7 +1167,23 @@ fn mk_plutus_script_redeemer_pointers(
             }
         }
     }
-    res
+    for (index, stake_address) in sort_withdrawals(tx_body)?.iter().enumerate() {
+        if let StakePayload::Script(script_hash) = stake_address.payload() {
+            if is_phase_2_script(
+                script_hash,
+                plutus_v1_scripts,
+                plutus_v2_scripts,
+                plutus_v3_scripts,
+                reference_scripts,
+            ) {
+                res.push(RedeemerPointer {
+                    tag: RedeemerTag::Reward,
+                    index: index as u32,
+                })
+            }
+        }
+    }
+    Ok(res)
 }
 
 // Lexicographical sorting for PolicyID's.
@@ -1004,6 +1202,7 @@ fn is_phase_2_script(
     policy: &PolicyId,
     plutus_v1_scripts: &[PolicyId],
     plutus_v2_scripts: &[PolicyId],
+    plutus_v3_scripts: &[PolicyId],
     reference_scripts: &[PolicyId],
 ) -> bool {
     plutus_v1_scripts
@@ -1012,6 +1211,9 @@ fn is_phase_2_script(
         || plutus_v2_scripts
             .iter()
             .any(|v2_script| policy == v2_script)
+        || plutus_v3_scripts
+            .iter()
+            .any(|v3_script| policy == v3_script)
         || reference_scripts
             .iter()
             .any(|ref_script| policy == ref_script)
@@ -1034,6 +1236,73 @@ fn redeemer_pointers_coincide(
     Ok(())
 }
 
+// Each native script in the transaction witness set, and each one in a
+// reference input ruling a script input, a minting policy or a withdrawal, is
+// satisfied by the correctly signed key witnesses and the validity interval.
+fn check_native_scripts(
+    tx_body: &MintedTransactionBody,
+    tx_wits: &MintedWitnessSet,
+    reference_native_scripts: &[(PolicyId, NativeScript)],
+    utxos: &UTxOs,
+    data_to_verify: &[u8],
+) -> ValidationResult {
+    let signers: Vec<AddrKeyhash> = tx_wits
+        .vkeywitness
+        .iter()
+        .flatten()
+        .filter(|vkey_wit| verify_signature(vkey_wit, data_to_verify))
+        .map(|vkey_wit| crate::pallas_crypto::hash::Hasher::<224>::hash(&vkey_wit.vkey.clone()))
+        .collect();
+    let needed_scripts: Vec<PolicyId> = get_script_hashes_from_inputs(tx_body, utxos)
+        .into_iter()
+        .map(|(_, script_hash)| script_hash)
+        .chain(
+            tx_body
+                .mint
+                .iter()
+                .flat_map(|minted_value| minted_value.iter().map(|(policy, _)| *policy)),
+        )
+        .chain(get_withdrawal_script_hashes(tx_body)?)
+        .collect();
+    let witness_scripts = tx_wits
+        .native_script
+        .iter()
+        .flatten()
+        .map(|raw_script| raw_script.deref());
+    let reference_scripts = reference_native_scripts
+        .iter()
+        .filter(|(script_hash, _)| needed_scripts.contains(script_hash))
+        .map(|(_, native_script)| native_script);
+    for native_script in witness_scripts.chain(reference_scripts) {
+        if !eval_native_script(native_script, &signers, tx_body) {
+            return Err(Babbage(NativeScriptUnsatisfied));
+        }
+    }
+    Ok(())
+}
+
+fn eval_native_script(
+    native_script: &NativeScript,
+    signers: &[AddrKeyhash],
+    tx_body: &MintedTransactionBody,
+) -> bool {
+    let eval = |script: &NativeScript| eval_native_script(script, signers, tx_body);
+    match native_script {
+        NativeScript::ScriptPubkey(key_hash) => signers.contains(key_hash),
+        NativeScript::ScriptAll(scripts) => scripts.iter().all(eval),
+        NativeScript::ScriptAny(scripts) => scripts.iter().any(eval),
+        NativeScript::ScriptNOfK(n, scripts) => {
+            scripts.iter().filter(|&script| eval(script)).count() >= *n as usize
+        }
+        NativeScript::InvalidBefore(slot) => tx_body
+            .validity_interval_start
+            .is_some_and(|lower_bound| *slot <= lower_bound),
+        NativeScript::InvalidHereafter(slot) => {
+            tx_body.ttl.is_some_and(|upper_bound| upper_bound <= *slot)
+        }
+    }
+}
+
 // All required signers (needed by a Plutus script) have a corresponding match
 // in the transaction witness set.
 fn check_required_signers(
@@ -1078,8 +1347,12 @@ fn check_vkey_input_wits(
     utxos: &UTxOs,
 ) -> ValidationResult {
     let tx_body: &MintedTransactionBody = &mtx.transaction_body;
-    let vk_wits: &mut Vec<(bool, VKeyWitness)> =
-        &mut mk_alonzo_vk_wits_check_list(vkey_wits, Babbage(VKWitnessMissing))?;
+    // Transactions spending only from native scripts may carry no key witness.
+    let vk_wits: &mut Vec<(bool, VKeyWitness)> = &mut vkey_wits
+        .iter()
+        .flatten()
+        .map(|vkey_wit| (false, vkey_wit.clone()))
+        .collect();
     let tx_hash: &Vec<u8> = &Vec::from(mtx.transaction_body.original_hash().as_ref());
     let mut inputs_and_collaterals: Vec<TransactionInput> = Vec::new();
     inputs_and_collaterals.extend(tx_body.inputs.clone());
@@ -1105,6 +1378,11 @@ fn check_vkey_input_wits(
             None => return Err(Babbage(InputNotInUTxO)),
         }
     }
+    for stake_address in sort_withdrawals(tx_body)?.iter() {
+        if let StakePayload::Stake(stake_key_hash) = stake_address.payload() {
+            check_vk_wit(stake_key_hash, vk_wits, tx_hash)?
+        }
+    }
     check_remaining_vk_wits(vk_wits, tx_hash) // required for native scripts
 }
 
@@ -1326,7 +1604,7 @@ fn tx_languages(mtx: &MintedTx, utxos: &UTxOs) -> Vec<Language> {
 }
 
 // The metadata of the transaction is valid.
-fn check_auxiliary_data(tx_body: &MintedTransactionBody, mtx: &MintedTx) -> ValidationResult {
+pub fn check_auxiliary_data(tx_body: &MintedTransactionBody, mtx: &MintedTx) -> ValidationResult {
     match (
         &tx_body.auxiliary_data_hash,
         aux_data_from_babbage_minted_tx(mtx),
@@ -357,6 +357,12 @@ pub fn compute_plutus_v2_script_hash(script: &PlutusScript<2>) -> PolicyId {
     crate::pallas_crypto::hash::Hasher::<224>::hash(&payload)
 }
 
+pub fn compute_plutus_v3_script_hash(script: &PlutusScript<3>) -> PolicyId {
+    let mut payload: Vec<u8> = Vec::from(script.as_ref());
+    payload.insert(0, 3);
+    crate::pallas_crypto::hash::Hasher::<224>::hash(&payload)
+}
+
 pub type CertificateIndex = u32;
 
 #[derive(PartialEq, Eq, Hash, Clone)]
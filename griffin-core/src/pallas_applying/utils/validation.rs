@@ -155,6 +155,12 @@ pub enum BabbageError {
     ScriptIntegrityHash,
     DuplicateInput,
     OutputAlreadyInUTxO,
+    ValidityFlagMismatch,
+    BlockExUnitsExceeded,
+    NativeScriptUnsatisfied,
+    IncompleteWithdrawal,
+    DuplicateWithdrawal,
+    UnneededPlutusV3Script,
 }
 
 pub type ValidationResult = Result<(), ValidationError>;
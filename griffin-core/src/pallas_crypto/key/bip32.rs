@@ -0,0 +1,213 @@
+//! Bip32-Ed25519 Hierarchical Deterministic Keys
+//!
+//! [`XPrv`] pairs a [`SecretKeyExtended`] with a chain code, so that child
+//! keys can be derived from it following the `V2` scheme of
+//! [BIP32-Ed25519](https://input-output-hk.github.io/adrestia/static/Ed25519_BIP.pdf),
+//! which is the one used by Cardano wallets. Master keys are obtained from
+//! BIP39 entropy as in the Icarus wallet (see CIP-3).
+
+use crate::pallas_crypto::{
+    key::ed25519::{PublicKey, SecretKeyExtended},
+    memsec::Scrubbed as _,
+};
+use cryptoxide::{ed25519, hmac::Hmac, mac::Mac, pbkdf2::pbkdf2, sha2::Sha512};
+
+/// Offset of hardened indices. Hardened derivation requires the private key.
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// Number of PBKDF2 iterations used to obtain Icarus master keys.
+const ICARUS_ITERATIONS: u32 = 4096;
+
+/// Extended private key: an Ed25519 extended secret key together with its
+/// chain code.
+#[derive(Clone)]
+pub struct XPrv {
+    key: [u8; SecretKeyExtended::SIZE],
+    chain_code: [u8; Self::CHAIN_CODE_SIZE],
+}
+
+impl XPrv {
+    pub const CHAIN_CODE_SIZE: usize = 32;
+
+    /// The Icarus master key for the given BIP39 `entropy` (that is, the bytes
+    /// encoded by the mnemonic, not its seed) and passphrase.
+    pub fn from_entropy(entropy: &[u8], passphrase: &[u8]) -> Self {
+        let mut bytes = [0; SecretKeyExtended::SIZE + Self::CHAIN_CODE_SIZE];
+        let mut mac = Hmac::new(Sha512::new(), passphrase);
+        pbkdf2(&mut mac, entropy, ICARUS_ITERATIONS, &mut bytes);
+
+        bytes[0] &= 0b1111_1000;
+        bytes[31] &= 0b0001_1111;
+        bytes[31] |= 0b0100_0000;
+
+        let mut xprv = Self {
+            key: [0; SecretKeyExtended::SIZE],
+            chain_code: [0; Self::CHAIN_CODE_SIZE],
+        };
+        xprv.key.copy_from_slice(&bytes[..SecretKeyExtended::SIZE]);
+        xprv.chain_code
+            .copy_from_slice(&bytes[SecretKeyExtended::SIZE..]);
+        bytes.scrub();
+
+        xprv
+    }
+
+    /// Derive the child key at `index`, which is hardened if it is at least
+    /// [`HARDENED`].
+    pub fn derive(&self, index: u32) -> Self {
+        let (kl, kr) = self.key.split_at(32);
+        let serialized_index = index.to_le_bytes();
+
+        let mut z_mac = Hmac::new(Sha512::new(), &self.chain_code);
+        let mut i_mac = Hmac::new(Sha512::new(), &self.chain_code);
+        if index >= HARDENED {
+            z_mac.input(&[0x00]);
+            z_mac.input(&self.key);
+            i_mac.input(&[0x01]);
+            i_mac.input(&self.key);
+        } else {
+            let public = ed25519::extended_to_public(&self.key);
+            z_mac.input(&[0x02]);
+            z_mac.input(&public);
+            i_mac.input(&[0x03]);
+            i_mac.input(&public);
+        }
+        z_mac.input(&serialized_index);
+        i_mac.input(&serialized_index);
+
+        let mut z = [0; 64];
+        z_mac.raw_result(&mut z);
+        let mut i = [0; 64];
+        i_mac.raw_result(&mut i);
+
+        let mut child = Self {
+            key: [0; SecretKeyExtended::SIZE],
+            chain_code: [0; Self::CHAIN_CODE_SIZE],
+        };
+        child.key[..32].copy_from_slice(&add_28_mul8(kl, &z[..28]));
+        child.key[32..].copy_from_slice(&add_256bits(kr, &z[32..]));
+        child.chain_code.copy_from_slice(&i[32..]);
+        z.scrub();
+        i.scrub();
+
+        child
+    }
+
+    /// Derive the key at the end of `path`, starting from this one.
+    pub fn derive_path(&self, path: &[u32]) -> Self {
+        path.iter()
+            .fold(self.clone(), |xprv, index| xprv.derive(*index))
+    }
+
+    /// The extended secret key, used for signing.
+    pub fn secret_key(&self) -> SecretKeyExtended {
+        // Derived keys may have the third highest bit set, which
+        // `SecretKeyExtended::from_bytes` rejects, but they are valid for signing.
+        unsafe { SecretKeyExtended::from_bytes_unchecked(self.key) }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from(ed25519::extended_to_public(&self.key))
+    }
+
+    pub fn chain_code(&self) -> [u8; Self::CHAIN_CODE_SIZE] {
+        self.chain_code
+    }
+}
+
+impl Drop for XPrv {
+    fn drop(&mut self) {
+        self.key.scrub();
+        self.chain_code.scrub();
+    }
+}
+
+/// `x + 8 * y`, where `y` has 28 bytes, little endian.
+fn add_28_mul8(x: &[u8], y: &[u8]) -> [u8; 32] {
+    let shifted = y[..28]
+        .iter()
+        .map(|byte| (*byte as u16) << 3)
+        .chain(core::iter::repeat(0));
+    add_with_carry(x, shifted)
+}
+
+/// `x + y` modulo 2^256, little endian.
+fn add_256bits(x: &[u8], y: &[u8]) -> [u8; 32] {
+    add_with_carry(x, y.iter().map(|byte| *byte as u16))
+}
+
+/// Little-endian addition of 32 bytes, dropping the final carry.
+fn add_with_carry(x: &[u8], y: impl Iterator<Item = u16>) -> [u8; 32] {
+    let mut out = [0; 32];
+    let mut carry: u16 = 0;
+    for ((byte, a), b) in out.iter_mut().zip(x).zip(y) {
+        let r = *a as u16 + b + carry;
+        *byte = (r & 0xff) as u8;
+        carry = r >> 8;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entropy of "test walk nut penalty hip pave soap entry language right filter choice".
+    const ENTROPY: &str = "df9ed25ed146bf43336a5d7cf7395994";
+
+    fn account() -> XPrv {
+        XPrv::from_entropy(&hex::decode(ENTROPY).unwrap(), b"").derive_path(&[
+            HARDENED + 1852,
+            HARDENED + 1815,
+            HARDENED,
+        ])
+    }
+
+    #[test]
+    fn icarus_master_key() {
+        let root = XPrv::from_entropy(&hex::decode(ENTROPY).unwrap(), b"");
+
+        assert_eq!(
+            hex::encode(root.key),
+            "608621fb4c0101feb31f6f2fd7018bee54101ff67d555079671893225ee1a45e\
+             2331497029d885b5634405f350508cd95dce3991503b10f128d04f34b7b62578"
+        );
+        assert_eq!(
+            hex::encode(root.chain_code()),
+            "3a1e3bd5dcf11fd4f989ec2cdcdea3a54db8997398174ecdcc87006c274176a0"
+        );
+    }
+
+    #[test]
+    fn cip1852_keys() {
+        let account = account();
+        let public = |path: &[u32]| hex::encode(account.derive_path(path).public_key());
+
+        assert_eq!(
+            public(&[0, 0]),
+            "73fea80d424276ad0978d4fe5310e8bc2d485f5f6bb3bf87612989f112ad5a7d"
+        );
+        assert_eq!(
+            public(&[0, 1]),
+            "f626ab887eb5f40b502463ccf2ec5a7311676ee9e5d55c492059a366c0b4d4a1"
+        );
+        assert_eq!(
+            public(&[1, 0]),
+            "1a74f57f3d04d0fa7e0be052c8669ced18a63191d6446f5f80c51279f743f37b"
+        );
+        assert_eq!(
+            public(&[2, 0]),
+            "2c041c9c6a676ac54d25e2fdce44c56581e316ae43adc4c7bf17f23214d8d892"
+        );
+    }
+
+    #[test]
+    fn derived_keys_sign() {
+        let key = account().derive_path(&[0, 0]);
+        let message = b"griffin";
+
+        assert!(key
+            .public_key()
+            .verify(message, &key.secret_key().sign(message)));
+    }
+}
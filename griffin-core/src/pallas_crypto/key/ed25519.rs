@@ -25,7 +25,7 @@ pub struct SecretKey([u8; Self::SIZE]);
 /// Ed25519 Extended Secret Key
 ///
 /// unlike [`SecretKey`], an extended key can be derived see
-/// `pallas_crypto::derivation`
+/// `pallas_crypto::key::bip32`
 #[derive(Clone)]
 pub struct SecretKeyExtended([u8; Self::SIZE]);
 
@@ -1 +1,2 @@
+pub mod bip32;
 pub mod ed25519;
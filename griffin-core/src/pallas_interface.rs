@@ -11,17 +11,19 @@ use crate::pallas_codec::{
 };
 use crate::pallas_crypto::hash::Hash as PallasHash;
 use crate::pallas_primitives::babbage::{
-    AssetName as PallasAssetName, DatumOption, ExUnits as PallasExUnits, LegacyTransactionOutput,
-    Multiasset as PallasMultiasset, PlutusData as PallasPlutusData,
-    PlutusScript as PallasPlutusScript, PolicyId as PallasPolicyId, PostAlonzoTransactionOutput,
-    PseudoDatumOption::Data, Redeemer as PallasRedeemer, RedeemerTag as PallasRedeemerTag,
+    AssetName as PallasAssetName, AuxiliaryData as PallasAuxiliaryData,
+    DatumOption as PallasDatumOption, ExUnits as PallasExUnits, LegacyTransactionOutput,
+    Multiasset as PallasMultiasset, NativeScript as PallasNativeScript,
+    PlutusData as PallasPlutusData, PlutusScript as PallasPlutusScript, PolicyId as PallasPolicyId,
+    PostAlonzoTransactionOutput, PseudoDatumOption, PseudoScript, Redeemer as PallasRedeemer,
+    RedeemerTag as PallasRedeemerTag, ScriptRef as PallasScriptRef,
     TransactionBody as PallasTransactionBody, TransactionInput as PallasInput,
     TransactionOutput as PallasOutput, Tx as PallasTransaction, VKeyWitness as PallasVKeyWitness,
     Value as PallasValue, WitnessSet as PallasWitnessSet,
 };
 use crate::pallas_primitives::conway::{
-    DatumOption as ConwayDatumOption, Multiasset as ConwayMultiasset,
-    PostAlonzoTransactionOutput as ConwayPostAlonzoTransactionOutput,
+    CostModels as ConwayCostModels, Multiasset as ConwayMultiasset,
+    PostAlonzoTransactionOutput as ConwayPostAlonzoTransactionOutput, ScriptRef as ConwayScriptRef,
     TransactionOutput as ConwayOutput, Value as ConwayValue,
 };
 use crate::pallas_primitives::{AddrKeyhash, PositiveCoin};
@@ -177,101 +179,173 @@ impl From<PallasValue> for Value {
     }
 }
 
-impl From<Output> for ConwayPostAlonzoTransactionOutput {
-    fn from(val: Output) -> Self {
-        // FIXME: Add error handling
-        let datum_option: Option<ConwayDatumOption> = val.datum_option.map(|d| {
-            Data(CborWrap(
-                Decode::decode(&mut Decoder::new(d.0.as_slice()), &mut ()).unwrap(),
-            ))
-        });
+impl TryFrom<DatumOption> for PallasDatumOption {
+    type Error = PallasConversionError;
 
-        Self {
-            address: <_>::from(val.address.0),
-            value: <_>::from(val.value),
-            datum_option,
-            script_ref: Default::default(),
+    fn try_from(val: DatumOption) -> Result<Self, Self::Error> {
+        match val {
+            DatumOption::Hash(hash) => Ok(PseudoDatumOption::Hash(PallasHash::<32>::from(
+                hash.as_bytes(),
+            ))),
+            DatumOption::Inline(d) => Ok(PseudoDatumOption::Data(CborWrap(
+                PallasPlutusData::try_from(PlutusData(d.0))?,
+            ))),
         }
     }
 }
 
-impl From<Output> for PostAlonzoTransactionOutput {
-    fn from(val: Output) -> Self {
-        // FIXME: Add error handling
-        let datum_option: Option<DatumOption> = val.datum_option.map(|d| {
-            Data(CborWrap(
-                Decode::decode(&mut Decoder::new(d.0.as_slice()), &mut ()).unwrap(),
-            ))
-        });
+impl TryFrom<Output> for ConwayPostAlonzoTransactionOutput {
+    type Error = PallasConversionError;
 
-        Self {
+    fn try_from(val: Output) -> Result<Self, Self::Error> {
+        let datum_option: Option<PallasDatumOption> =
+            val.datum_option.map(<_>::try_from).transpose()?;
+
+        Ok(Self {
             address: <_>::from(val.address.0),
             value: <_>::from(val.value),
             datum_option,
-            script_ref: Default::default(),
-        }
+            script_ref: val.script_ref.map(|s| CborWrap(ConwayScriptRef::from(s))),
+        })
     }
 }
 
-impl From<PostAlonzoTransactionOutput> for Output {
-    fn from(val: PostAlonzoTransactionOutput) -> Self {
-        let mut datum_option: Option<Datum> = None;
-        let mut datum: Vec<u8> = Vec::new();
-        if let Some(data) = val.datum_option {
-            match encode(&data, &mut datum) {
-                Ok(_) => {
-                    datum_option = Some(Datum(datum));
+impl TryFrom<Output> for PostAlonzoTransactionOutput {
+    type Error = PallasConversionError;
+
+    fn try_from(val: Output) -> Result<Self, Self::Error> {
+        let datum_option: Option<PallasDatumOption> =
+            val.datum_option.map(<_>::try_from).transpose()?;
+
+        Ok(Self {
+            address: <_>::from(val.address.0),
+            value: <_>::from(val.value),
+            datum_option,
+            script_ref: val.script_ref.map(|s| CborWrap(PallasScriptRef::from(s))),
+        })
+    }
+}
+
+/// Error converting between Griffin and Pallas transactions.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PallasConversionError {
+    /// Output carrying a reference script other than a Plutus one, which
+    /// Griffin outputs cannot hold.
+    UnsupportedScriptRef,
+    /// Inline datum, witness-set datum or redeemer data whose bytes are not
+    /// the CBOR encoding of Plutus data.
+    MalformedPlutusData,
+    /// Auxiliary data whose bytes are not the CBOR encoding of auxiliary data.
+    MalformedAuxiliaryData,
+}
+
+impl TryFrom<PostAlonzoTransactionOutput> for Output {
+    type Error = PallasConversionError;
+
+    fn try_from(val: PostAlonzoTransactionOutput) -> Result<Self, Self::Error> {
+        let datum_option: Option<DatumOption> = match val.datum_option {
+            Some(PseudoDatumOption::Hash(hash)) => {
+                Some(DatumOption::Hash(H256::from(hash.deref())))
+            }
+            Some(PseudoDatumOption::Data(CborWrap(data))) => {
+                let mut datum: Vec<u8> = Vec::new();
+                match encode(&data, &mut datum) {
+                    Ok(_) => Some(DatumOption::Inline(Datum(datum))),
+                    Err(err) => {
+                        log::info!("Unable to encode datum ({:?})", err);
+                        None
+                    }
                 }
-                Err(err) => log::info!("Unable to encode datum ({:?})", err),
-            };
+            }
+            None => None,
         };
 
-        Self {
+        // Only Plutus scripts are supported as reference scripts.
+        let script_ref: Option<PlutusScript> = match val.script_ref {
+            None => None,
+            Some(CborWrap(PseudoScript::PlutusV1Script(s))) => {
+                Some(PlutusScript::new(PlutusVersion::V1, Vec::from(s.0)))
+            }
+            Some(CborWrap(PseudoScript::PlutusV2Script(s))) => {
+                Some(PlutusScript::new(PlutusVersion::V2, Vec::from(s.0)))
+            }
+            Some(CborWrap(PseudoScript::PlutusV3Script(s))) => {
+                Some(PlutusScript::new(PlutusVersion::V3, Vec::from(s.0)))
+            }
+            Some(CborWrap(PseudoScript::NativeScript(_))) => {
+                return Err(PallasConversionError::UnsupportedScriptRef)
+            }
+        };
+
+        Ok(Self {
             address: Address(Vec::from(val.address)),
             value: Value::from(val.value),
             datum_option,
-        }
+            script_ref,
+        })
     }
 }
 
 impl From<LegacyTransactionOutput> for Output {
     fn from(val: LegacyTransactionOutput) -> Self {
-        let mut datum_option: Option<Datum> = None;
-        let mut datum: Vec<u8> = Vec::new();
-        if let Some(data) = val.datum_hash {
-            match encode(data, &mut datum) {
-                Ok(_) => {
-                    datum_option = Some(Datum(datum));
-                }
-                Err(err) => log::info!("Unable to encode datum ({:?})", err),
-            };
-        };
+        let datum_option: Option<DatumOption> = val
+            .datum_hash
+            .map(|hash| DatumOption::Hash(H256::from(hash.deref())));
 
         Self {
             address: Address(Vec::from(val.address)),
             value: Value::from(val.amount),
             datum_option,
+            script_ref: None,
         }
     }
 }
 
-impl From<Output> for PallasOutput {
-    fn from(val: Output) -> Self {
-        PallasOutput::PostAlonzo(<_>::from(val))
+impl TryFrom<Output> for PallasOutput {
+    type Error = PallasConversionError;
+
+    fn try_from(val: Output) -> Result<Self, Self::Error> {
+        Ok(PallasOutput::PostAlonzo(<_>::try_from(val)?))
     }
 }
 
-impl From<Output> for ConwayOutput {
-    fn from(val: Output) -> Self {
-        ConwayOutput::PostAlonzo(<_>::from(val))
+impl TryFrom<Output> for ConwayOutput {
+    type Error = PallasConversionError;
+
+    fn try_from(val: Output) -> Result<Self, Self::Error> {
+        Ok(ConwayOutput::PostAlonzo(<_>::try_from(val)?))
     }
 }
 
-impl From<PallasOutput> for Output {
-    fn from(val: PallasOutput) -> Self {
+impl TryFrom<PallasOutput> for Output {
+    type Error = PallasConversionError;
+
+    fn try_from(val: PallasOutput) -> Result<Self, Self::Error> {
         match val {
-            PallasOutput::PostAlonzo(pat) => Output::from(pat),
-            PallasOutput::Legacy(leg) => Output::from(leg),
+            PallasOutput::PostAlonzo(pat) => Output::try_from(pat),
+            PallasOutput::Legacy(leg) => Ok(Output::from(leg)),
+        }
+    }
+}
+
+impl From<PlutusScript> for PallasScriptRef {
+    fn from(val: PlutusScript) -> Self {
+        let bytes = <_>::from(val.bytes);
+        match val.version {
+            PlutusVersion::V1 => PallasScriptRef::PlutusV1Script(PallasPlutusScript::<1>(bytes)),
+            PlutusVersion::V2 => PallasScriptRef::PlutusV2Script(PallasPlutusScript::<2>(bytes)),
+            PlutusVersion::V3 => PallasScriptRef::PlutusV3Script(PallasPlutusScript::<3>(bytes)),
+        }
+    }
+}
+
+impl From<PlutusScript> for ConwayScriptRef {
+    fn from(val: PlutusScript) -> Self {
+        let bytes = <_>::from(val.bytes);
+        match val.version {
+            PlutusVersion::V1 => ConwayScriptRef::PlutusV1Script(PallasPlutusScript::<1>(bytes)),
+            PlutusVersion::V2 => ConwayScriptRef::PlutusV2Script(PallasPlutusScript::<2>(bytes)),
+            PlutusVersion::V3 => ConwayScriptRef::PlutusV3Script(PallasPlutusScript::<3>(bytes)),
         }
     }
 }
@@ -305,14 +379,17 @@ impl From<RedeemerTag> for PallasRedeemerTag {
         match val {
             RedeemerTag::Spend => PallasRedeemerTag::Spend,
             RedeemerTag::Mint => PallasRedeemerTag::Mint,
+            RedeemerTag::Reward => PallasRedeemerTag::Reward,
         }
     }
 }
 
-impl From<PlutusData> for PallasPlutusData {
-    fn from(data: PlutusData) -> Self {
-        // FIXME: Add error handling
-        Decode::decode(&mut Decoder::new(data.0.as_slice()), &mut ()).unwrap()
+impl TryFrom<PlutusData> for PallasPlutusData {
+    type Error = PallasConversionError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        Decode::decode(&mut Decoder::new(data.0.as_slice()), &mut ())
+            .map_err(|_| PallasConversionError::MalformedPlutusData)
     }
 }
 
@@ -327,39 +404,120 @@ impl From<PallasPlutusData> for PlutusData {
     }
 }
 
-impl From<Redeemer> for PallasRedeemer {
-    fn from(Redeemer { tag, index, data }: Redeemer) -> Self {
-        Self {
+impl TryFrom<Redeemer> for PallasRedeemer {
+    type Error = PallasConversionError;
+
+    fn try_from(
+        Redeemer {
+            tag,
+            index,
+            data,
+            ex_units,
+        }: Redeemer,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
             tag: <_>::from(tag),
             index,
-            ex_units: Default::default(),
-            data: <_>::from(data),
+            ex_units: <_>::from(ex_units),
+            data: <_>::try_from(data)?,
+        })
+    }
+}
+
+impl From<CostModels> for ConwayCostModels {
+    fn from(
+        CostModels {
+            plutus_v1,
+            plutus_v2,
+            plutus_v3,
+        }: CostModels,
+    ) -> Self {
+        Self {
+            plutus_v1,
+            plutus_v2,
+            plutus_v3,
         }
     }
 }
 
-impl From<WitnessSet> for PallasWitnessSet {
-    fn from(val: WitnessSet) -> Self {
+impl From<NativeScript> for PallasNativeScript {
+    fn from(val: NativeScript) -> Self {
+        let convert = |scripts: Vec<NativeScript>| -> Vec<PallasNativeScript> {
+            scripts.into_iter().map(<_>::from).collect()
+        };
+
+        match val {
+            NativeScript::ScriptPubkey(hash) => Self::ScriptPubkey(<_>::from(hash)),
+            NativeScript::ScriptAll(scripts) => Self::ScriptAll(convert(scripts)),
+            NativeScript::ScriptAny(scripts) => Self::ScriptAny(convert(scripts)),
+            NativeScript::ScriptNOfK(n, scripts) => Self::ScriptNOfK(n, convert(scripts)),
+            NativeScript::InvalidBefore(slot) => Self::InvalidBefore(slot),
+            NativeScript::InvalidHereafter(slot) => Self::InvalidHereafter(slot),
+        }
+    }
+}
+
+impl From<PallasNativeScript> for NativeScript {
+    fn from(val: PallasNativeScript) -> Self {
+        let convert = |scripts: Vec<PallasNativeScript>| -> Vec<NativeScript> {
+            scripts.into_iter().map(<_>::from).collect()
+        };
+
+        match val {
+            PallasNativeScript::ScriptPubkey(hash) => Self::ScriptPubkey(<_>::from(hash)),
+            PallasNativeScript::ScriptAll(scripts) => Self::ScriptAll(convert(scripts)),
+            PallasNativeScript::ScriptAny(scripts) => Self::ScriptAny(convert(scripts)),
+            PallasNativeScript::ScriptNOfK(n, scripts) => Self::ScriptNOfK(n, convert(scripts)),
+            PallasNativeScript::InvalidBefore(slot) => Self::InvalidBefore(slot),
+            PallasNativeScript::InvalidHereafter(slot) => Self::InvalidHereafter(slot),
+        }
+    }
+}
+
+impl TryFrom<WitnessSet> for PallasWitnessSet {
+    type Error = PallasConversionError;
+
+    fn try_from(val: WitnessSet) -> Result<Self, Self::Error> {
         let vkeywitness: Option<Vec<PallasVKeyWitness>> = val
             .vkeywitness
             .map(|vks| vks.into_iter().map(<_>::from).collect());
         let redeemer: Option<Vec<PallasRedeemer>> = val
             .redeemer
-            .map(|vks| vks.into_iter().map(<_>::from).collect());
-        let plutus_v2_script: Option<Vec<PallasPlutusScript<2>>> = val.plutus_script.map(|vks| {
-            vks.into_iter()
-                .map(|vk| PallasPlutusScript::<2>(<_>::from(vk.0)))
-                .collect()
-        });
-        Self {
+            .map(|vks| vks.into_iter().map(<_>::try_from).collect())
+            .transpose()?;
+        let plutus_scripts: Vec<PlutusScript> = val.plutus_script.unwrap_or_default();
+        // Each language version goes to its own field of the witness set.
+        let scripts_of = |version: PlutusVersion| -> Option<Vec<Bytes>> {
+            let scripts: Vec<Bytes> = plutus_scripts
+                .iter()
+                .filter(|script| script.version == version)
+                .map(|script| <_>::from(script.bytes.clone()))
+                .collect();
+            (!scripts.is_empty()).then_some(scripts)
+        };
+        let plutus_v1_script: Option<Vec<PallasPlutusScript<1>>> = scripts_of(PlutusVersion::V1)
+            .map(|scripts| scripts.into_iter().map(PallasPlutusScript::<1>).collect());
+        let plutus_v2_script: Option<Vec<PallasPlutusScript<2>>> = scripts_of(PlutusVersion::V2)
+            .map(|scripts| scripts.into_iter().map(PallasPlutusScript::<2>).collect());
+        let plutus_v3_script: Option<Vec<PallasPlutusScript<3>>> = scripts_of(PlutusVersion::V3)
+            .map(|scripts| scripts.into_iter().map(PallasPlutusScript::<3>).collect());
+        let native_script: Option<Vec<PallasNativeScript>> = val
+            .native_script
+            .map(|nss| nss.into_iter().map(<_>::from).collect());
+        let plutus_data: Option<Vec<PallasPlutusData>> = val
+            .plutus_data
+            .map(|pds| pds.into_iter().map(<_>::try_from).collect())
+            .transpose()?;
+        Ok(Self {
             vkeywitness,
-            native_script: None,
+            native_script,
             bootstrap_witness: None,
-            plutus_v1_script: None,
-            plutus_data: None,
+            plutus_v1_script,
+            plutus_data,
             redeemer,
             plutus_v2_script,
-        }
+            plutus_v3_script,
+        })
     }
 }
 
@@ -372,43 +530,78 @@ impl From<PallasWitnessSet> for WitnessSet {
             // FIXME: does not work as a `From`. Revise or eliminate all From<Pallas...>!
             plutus_script: None,
             redeemer: None,
+            native_script: val
+                .native_script
+                .map(|v| v.into_iter().map(<_>::from).collect()),
+            plutus_data: val
+                .plutus_data
+                .map(|v| v.into_iter().map(<_>::from).collect()),
         }
     }
 }
 
-impl From<TransactionBody> for PallasTransactionBody {
-    fn from(val: TransactionBody) -> Self {
-        Self {
+impl TryFrom<TransactionBody> for PallasTransactionBody {
+    type Error = PallasConversionError;
+
+    fn try_from(val: TransactionBody) -> Result<Self, Self::Error> {
+        Ok(Self {
             inputs: val.inputs.into_iter().map(<_>::from).collect(),
-            outputs: val.outputs.into_iter().map(<_>::from).collect(),
-            fee: 0,
+            outputs: val
+                .outputs
+                .into_iter()
+                .map(<_>::try_from)
+                .collect::<Result<_, _>>()?,
+            fee: val.fee,
             ttl: val.ttl,
             certificates: None,
-            withdrawals: None,
+            // Conway bodies reject empty withdrawal maps.
+            withdrawals: val.withdrawals.filter(|ws| !ws.is_empty()).map(|ws| {
+                KeyValuePairs::from(
+                    ws.into_iter()
+                        .map(|(account, amount)| (Bytes::from(account.0), amount))
+                        .collect::<Vec<_>>(),
+                )
+            }),
             update: None,
-            auxiliary_data_hash: None,
+            auxiliary_data_hash: val
+                .auxiliary_data_hash
+                .map(|hash| Bytes::from(Vec::from(hash.as_bytes()))),
             validity_interval_start: val.validity_interval_start,
             mint: val.mint.map(PallasMultiasset::from),
             script_data_hash: None,
-            collateral: None,
             required_signers: val.required_signers.map(|rss| {
                 rss.into_iter()
                     .map(PallasRequiredSigner::from)
                     .collect::<Vec<_>>()
             }),
             network_id: None,
-            collateral_return: None,
-            total_collateral: None,
-            reference_inputs: None,
-        }
-    }
-}
-
-impl From<PallasTransactionBody> for TransactionBody {
-    fn from(val: PallasTransactionBody) -> Self {
-        Self {
+            collateral: val
+                .collateral
+                .map(|cs| cs.into_iter().map(<_>::from).collect()),
+            collateral_return: val
+                .collateral_return
+                .map(PallasOutput::try_from)
+                .transpose()?,
+            total_collateral: val.total_collateral,
+            reference_inputs: val
+                .reference_inputs
+                .map(|ris| ris.into_iter().map(<_>::from).collect()),
+        })
+    }
+}
+
+impl TryFrom<PallasTransactionBody> for TransactionBody {
+    type Error = PallasConversionError;
+
+    fn try_from(val: PallasTransactionBody) -> Result<Self, Self::Error> {
+        Ok(Self {
             inputs: val.inputs.into_iter().map(Input::from).collect(),
-            outputs: val.outputs.into_iter().map(Output::from).collect(),
+            outputs: val
+                .outputs
+                .into_iter()
+                .map(Output::try_from)
+                .collect::<Result<_, _>>()?,
+            fee: val.fee,
             ttl: val.ttl,
             validity_interval_start: val.validity_interval_start,
             mint: val.mint.map(Multiasset::from),
@@ -417,27 +610,64 @@ impl From<PallasTransactionBody> for TransactionBody {
                     .map(RequiredSigner::from)
                     .collect::<Vec<_>>()
             }),
-        }
+            reference_inputs: val
+                .reference_inputs
+                .map(|ris| ris.into_iter().map(Input::from).collect()),
+            collateral: val
+                .collateral
+                .map(|cs| cs.into_iter().map(Input::from).collect()),
+            collateral_return: val.collateral_return.map(Output::try_from).transpose()?,
+            total_collateral: val.total_collateral,
+            auxiliary_data_hash: val
+                .auxiliary_data_hash
+                .filter(|hash| hash.len() == 32)
+                .map(|hash| H256::from_slice(&hash)),
+            withdrawals: val.withdrawals.map(|ws| {
+                ws.iter()
+                    .map(|(account, amount)| (Address(account.to_vec()), *amount))
+                    .collect()
+            }),
+        })
     }
 }
 
-impl From<Transaction> for PallasTransaction {
-    fn from(val: Transaction) -> Self {
-        Self {
-            transaction_body: <_>::from(val.transaction_body),
-            transaction_witness_set: <_>::from(val.transaction_witness_set),
-            success: true,
-            auxiliary_data: Nullable::Undefined,
-        }
+impl TryFrom<Transaction> for PallasTransaction {
+    type Error = PallasConversionError;
+
+    fn try_from(val: Transaction) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction_body: <_>::try_from(val.transaction_body)?,
+            transaction_witness_set: <_>::try_from(val.transaction_witness_set)?,
+            success: val.is_valid,
+            auxiliary_data: match val.auxiliary_data {
+                Some(aux_data) => Nullable::Some(
+                    Decode::decode(&mut Decoder::new(aux_data.0.as_slice()), &mut ())
+                        .map_err(|_| PallasConversionError::MalformedAuxiliaryData)?,
+                ),
+                None => Nullable::Undefined,
+            },
+        })
     }
 }
 
-impl From<PallasTransaction> for Transaction {
-    fn from(val: PallasTransaction) -> Self {
-        Self {
-            transaction_body: <_>::from(val.transaction_body),
+impl TryFrom<PallasTransaction> for Transaction {
+    type Error = PallasConversionError;
+
+    fn try_from(val: PallasTransaction) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction_body: <_>::try_from(val.transaction_body)?,
             transaction_witness_set: <_>::from(val.transaction_witness_set),
+            is_valid: val.success,
+            auxiliary_data: Option::<PallasAuxiliaryData>::from(val.auxiliary_data).map(
+                |aux_data| {
+                    let mut bytes: Vec<u8> = Vec::new();
+                    if let Err(err) = encode(&aux_data, &mut bytes) {
+                        log::error!("Unable to encode auxiliary data ({:?})", err)
+                    }
+                    AuxiliaryData(bytes)
+                },
+            ),
             function: <_>::from(0),
-        }
+        })
     }
 }
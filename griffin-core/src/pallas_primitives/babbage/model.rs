@@ -441,6 +441,9 @@ pub struct WitnessSet {
 
     #[n(6)]
     pub plutus_v2_script: Option<Vec<PlutusScript<2>>>,
+
+    #[n(7)]
+    pub plutus_v3_script: Option<Vec<PlutusScript<3>>>,
 }
 
 #[derive(Encode, Decode, Debug, PartialEq, Clone)]
@@ -466,6 +469,9 @@ pub struct MintedWitnessSet<'b> {
 
     #[n(6)]
     pub plutus_v2_script: Option<Vec<PlutusScript<2>>>,
+
+    #[n(7)]
+    pub plutus_v3_script: Option<Vec<PlutusScript<3>>>,
 }
 
 impl<'b> From<MintedWitnessSet<'b>> for WitnessSet {
@@ -482,6 +488,7 @@ impl<'b> From<MintedWitnessSet<'b>> for WitnessSet {
                 .map(|x| x.into_iter().map(|x| x.unwrap()).collect()),
             redeemer: x.redeemer,
             plutus_v2_script: x.plutus_v2_script,
+            plutus_v3_script: x.plutus_v3_script,
         }
     }
 }
@@ -563,12 +570,14 @@ pub type PlutusV1Script = PlutusScript<1>;
 #[deprecated(since = "0.31.0", note = "use `PlutusScript<2>` instead")]
 pub type PlutusV2Script = PlutusScript<2>;
 
-// script = [ 0, native_script // 1, plutus_v1_script // 2, plutus_v2_script ]
+// script = [ 0, native_script // 1, plutus_v1_script // 2, plutus_v2_script
+//          // 3, plutus_v3_script ]
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum PseudoScript<T1> {
     NativeScript(T1),
     PlutusV1Script(PlutusScript<1>),
     PlutusV2Script(PlutusScript<2>),
+    PlutusV3Script(PlutusScript<3>),
 }
 
 // script_ref = #6.24(bytes .cbor script)
@@ -582,6 +591,7 @@ impl<'b> From<MintedScriptRef<'b>> for ScriptRef {
             PseudoScript::NativeScript(x) => Self::NativeScript(x.unwrap()),
             PseudoScript::PlutusV1Script(x) => Self::PlutusV1Script(x),
             PseudoScript::PlutusV2Script(x) => Self::PlutusV2Script(x),
+            PseudoScript::PlutusV3Script(x) => Self::PlutusV3Script(x),
         }
     }
 }
@@ -600,6 +610,7 @@ where
             0 => Ok(Self::NativeScript(d.decode()?)),
             1 => Ok(Self::PlutusV1Script(d.decode()?)),
             2 => Ok(Self::PlutusV2Script(d.decode()?)),
+            3 => Ok(Self::PlutusV3Script(d.decode()?)),
             _ => Err(minicbor::decode::Error::message(
                 "invalid variant for script enum",
             )),
@@ -620,6 +631,7 @@ where
             Self::NativeScript(x) => e.encode_with((0, x), ctx)?,
             Self::PlutusV1Script(x) => e.encode_with((1, x), ctx)?,
             Self::PlutusV2Script(x) => e.encode_with((2, x), ctx)?,
+            Self::PlutusV3Script(x) => e.encode_with((3, x), ctx)?,
         };
 
         Ok(())
@@ -1469,6 +1469,7 @@ impl<'b> From<babbage::MintedScriptRef<'b>> for MintedScriptRef<'b> {
             babbage::MintedScriptRef::NativeScript(x) => Self::NativeScript(x),
             babbage::MintedScriptRef::PlutusV1Script(x) => Self::PlutusV1Script(x),
             babbage::MintedScriptRef::PlutusV2Script(x) => Self::PlutusV2Script(x),
+            babbage::MintedScriptRef::PlutusV3Script(x) => Self::PlutusV3Script(x),
         }
     }
 }
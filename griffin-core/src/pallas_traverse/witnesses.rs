@@ -214,7 +214,12 @@ impl<'b> MultiEraTx<'b> {
         match self {
             Self::Byron(_) => &[],
             Self::AlonzoCompatible(_, _) => &[],
-            Self::Babbage(_) => &[],
+            Self::Babbage(x) => x
+                .transaction_witness_set
+                .plutus_v3_script
+                .as_ref()
+                .map(|x| x.as_ref())
+                .unwrap_or(&[]),
             Self::Conway(x) => x
                 .transaction_witness_set
                 .plutus_v3_script
@@ -0,0 +1,338 @@
+//! Ledger parameters of a Griffin chain.
+//!
+//! They are set at genesis and persisted in state under the
+//! `PROTOCOL_PARAMETERS` key, from where every ledger check reads them.
+//!
+//! Chains configured with a governance token can update them without a runtime
+//! upgrade: the datum of the unique UTxO holding that token carries a
+//! [`PendingUpdate`], which is applied at the first epoch boundary at or after
+//! the epoch it names.
+
+use crate::pallas_codec::minicbor::{self, Decode as MiniDecode, Encode as MiniEncode};
+use crate::types::{AssetName, Coin, CostModels, Datum, DatumOption, ExUnits, Input, PolicyId};
+use crate::utxo_set::TransparentUtxoSet;
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// Parameters ruling the Griffin ledger. Apart from the slot configuration,
+/// all fields take default values when absent from the genesis configuration.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct ProtocolParameters {
+    /// Slot number of the first block in the chain.
+    pub zero_slot: u64,
+    /// Start POSIX time of `zero_slot`, in milliseconds.
+    pub zero_time: u64,
+    /// The Aura slot duration, in milliseconds.
+    pub slot_length: u32,
+    /// Number of slots in an epoch.
+    #[serde(default = "default_slots_per_epoch")]
+    pub slots_per_epoch: u64,
+    /// Network ID of the chain, which sets the human-readable part of the
+    /// bech32 addresses shown by the wallet and the RPC: `addr` and `stake`
+    /// for [`MAINNET_NETWORK_ID`], and `addr_test` and `stake_test` otherwise.
+    #[serde(default = "default_network_id")]
+    pub network_id: u8,
+    /// Per-byte coefficient `a` of the linear minimum fee `a * tx_size + b`.
+    #[serde(default)]
+    pub min_fee_a: u32,
+    /// Constant coefficient `b` of the linear minimum fee `a * tx_size + b`.
+    #[serde(default)]
+    pub min_fee_b: u32,
+    /// Maximum size of a transaction, in bytes.
+    #[serde(default = "default_max_tx_size")]
+    pub max_tx_size: u32,
+    /// Maximum size of the value of an output, in words.
+    #[serde(default = "default_max_value_size")]
+    pub max_value_size: u32,
+    /// Every output must contain this many `Coin`s.
+    #[serde(default = "default_min_coin_per_output")]
+    pub min_coin_per_output: Coin,
+    /// `Coin`s that an output must contain per word of its value, on top of a
+    /// fixed overhead of 160 words.
+    #[serde(default)]
+    pub coins_per_utxo_word: u64,
+    /// Percentage of the fee that collateral inputs must cover. Collateral is
+    /// only mandatory for script transactions when it is non-zero.
+    #[serde(default = "default_collateral_percentage")]
+    pub collateral_percentage: u32,
+    /// Maximum number of collateral inputs.
+    #[serde(default = "default_max_collateral_inputs")]
+    pub max_collateral_inputs: u32,
+    /// Cost models used to evaluate Plutus scripts. Scripts are evaluated with
+    /// the default costs when none is given.
+    #[serde(default)]
+    pub cost_models: CostModels,
+    /// Maximum execution units of a transaction.
+    #[serde(default = "default_max_tx_ex_units")]
+    pub max_tx_ex_units: ExUnits,
+    /// Maximum execution units of a block.
+    #[serde(default = "default_max_block_ex_units")]
+    pub max_block_ex_units: ExUnits,
+}
+
+impl ProtocolParameters {
+    /// Fails if the slot configuration cannot be used to split time into
+    /// slots and epochs.
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.slot_length == 0 {
+            return Err("slot_length must be > 0");
+        }
+        if self.slots_per_epoch == 0 {
+            return Err("slots_per_epoch must be > 0");
+        }
+        Ok(())
+    }
+}
+
+fn default_slots_per_epoch() -> u64 {
+    600
+}
+
+/// Network ID of Cardano mainnet.
+pub const MAINNET_NETWORK_ID: u8 = 1;
+
+fn default_network_id() -> u8 {
+    MAINNET_NETWORK_ID
+}
+
+fn default_max_tx_size() -> u32 {
+    32_768
+}
+
+fn default_max_value_size() -> u32 {
+    5_000
+}
+
+fn default_min_coin_per_output() -> Coin {
+    10
+}
+
+fn default_collateral_percentage() -> u32 {
+    150
+}
+
+fn default_max_collateral_inputs() -> u32 {
+    3
+}
+
+fn default_max_tx_ex_units() -> ExUnits {
+    ExUnits {
+        mem: 14_000_000,
+        steps: 10_000_000_000,
+    }
+}
+
+fn default_max_block_ex_units() -> ExUnits {
+    ExUnits {
+        mem: 62_000_000,
+        steps: 20_000_000_000,
+    }
+}
+
+/// Update to the protocol parameters. Absent fields are left unchanged; the
+/// slot configuration cannot be updated, since consensus reads it only once.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+    MiniEncode,
+    MiniDecode,
+)]
+#[cbor(map)]
+pub struct ProtocolParametersUpdate {
+    #[n(0)]
+    pub min_fee_a: Option<u32>,
+    #[n(1)]
+    pub min_fee_b: Option<u32>,
+    #[n(2)]
+    pub max_tx_size: Option<u32>,
+    #[n(3)]
+    pub max_value_size: Option<u32>,
+    #[n(4)]
+    pub min_coin_per_output: Option<Coin>,
+    #[n(5)]
+    pub coins_per_utxo_word: Option<u64>,
+    #[n(6)]
+    pub collateral_percentage: Option<u32>,
+    #[n(7)]
+    pub max_collateral_inputs: Option<u32>,
+    #[n(8)]
+    pub cost_models: Option<CostModels>,
+    #[n(9)]
+    pub max_tx_ex_units: Option<ExUnits>,
+    #[n(10)]
+    pub max_block_ex_units: Option<ExUnits>,
+}
+
+impl ProtocolParametersUpdate {
+    /// Parameters resulting from applying this update to `pps`, which must
+    /// pass [`ProtocolParameters::check`].
+    pub fn apply_to(
+        &self,
+        pps: &ProtocolParameters,
+    ) -> Result<ProtocolParameters, GovernanceError> {
+        let update = self.clone();
+        let updated = ProtocolParameters {
+            min_fee_a: update.min_fee_a.unwrap_or(pps.min_fee_a),
+            min_fee_b: update.min_fee_b.unwrap_or(pps.min_fee_b),
+            max_tx_size: update.max_tx_size.unwrap_or(pps.max_tx_size),
+            max_value_size: update.max_value_size.unwrap_or(pps.max_value_size),
+            min_coin_per_output: update
+                .min_coin_per_output
+                .unwrap_or(pps.min_coin_per_output),
+            coins_per_utxo_word: update
+                .coins_per_utxo_word
+                .unwrap_or(pps.coins_per_utxo_word),
+            collateral_percentage: update
+                .collateral_percentage
+                .unwrap_or(pps.collateral_percentage),
+            max_collateral_inputs: update
+                .max_collateral_inputs
+                .unwrap_or(pps.max_collateral_inputs),
+            cost_models: update
+                .cost_models
+                .unwrap_or_else(|| pps.cost_models.clone()),
+            max_tx_ex_units: update
+                .max_tx_ex_units
+                .unwrap_or_else(|| pps.max_tx_ex_units.clone()),
+            max_block_ex_units: update
+                .max_block_ex_units
+                .unwrap_or_else(|| pps.max_block_ex_units.clone()),
+            ..pps.clone()
+        };
+        updated
+            .check()
+            .map_err(GovernanceError::InvalidParameters)?;
+
+        Ok(updated)
+    }
+}
+
+/// Datum of the governance UTxO: an update to be applied at the beginning of
+/// `epoch`, encoded as the CBOR array `[epoch, update]` where `update` is a
+/// map from field indices of [`ProtocolParametersUpdate`] to new values.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+    MiniEncode,
+    MiniDecode,
+)]
+pub struct PendingUpdate {
+    #[n(0)]
+    pub epoch: u64,
+    #[n(1)]
+    pub update: ProtocolParametersUpdate,
+}
+
+/// Record of the last update applied to the protocol parameters.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct AppliedUpdate {
+    /// Epoch at whose beginning the update was applied.
+    pub epoch: u64,
+    /// Reference of the governance UTxO carrying the update.
+    pub source: Input,
+    /// Parameters in force before the update.
+    pub previous: ProtocolParameters,
+}
+
+/// Token identifying the UTxO that carries protocol parameter updates.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub struct GovernanceToken {
+    pub policy: PolicyId,
+    pub name: AssetName,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GovernanceError {
+    /// More than one UTxO holds the governance token.
+    MoreThanOneOutput,
+    /// The datum of the governance UTxO is not a `PendingUpdate`.
+    BadDatum,
+    /// The updated parameters do not pass [`ProtocolParameters::check`].
+    InvalidParameters(&'static str),
+}
+
+/// Looks up the UTxO holding `token` and decodes the update carried by its
+/// datum, if any.
+pub fn fetch_pending_update(
+    token: &GovernanceToken,
+) -> Result<Option<(Input, PendingUpdate)>, GovernanceError> {
+    // Two utxos are enough to tell whether the governance utxo is unique.
+    let utxos = TransparentUtxoSet::peek_utxos_with_asset(&token.name, &token.policy, None, 2);
+    let (input, output) = match utxos.as_slice() {
+        [] => return Ok(None),
+        [utxo] => utxo.clone(),
+        _ => return Err(GovernanceError::MoreThanOneOutput),
+    };
+
+    match output.datum_option {
+        None => Ok(None),
+        Some(DatumOption::Inline(Datum(bytes))) => minicbor::decode::<PendingUpdate>(&bytes)
+            .map(|pending| Some((input, pending)))
+            .map_err(|_| GovernanceError::BadDatum),
+        Some(DatumOption::Hash(_)) => Err(GovernanceError::BadDatum),
+    }
+}
+
+mod api_declarations {
+    use super::*;
+    sp_api::decl_runtime_apis! {
+        pub trait ProtocolParametersApi {
+            fn protocol_parameters() -> Option<ProtocolParameters>;
+            fn last_parameters_update() -> Option<AppliedUpdate>;
+            fn pending_parameters_update() -> Option<PendingUpdate>;
+        }
+    }
+}
+pub use api_declarations::*;
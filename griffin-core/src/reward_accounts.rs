@@ -0,0 +1,64 @@
+//! Reward accounts interface to storage.
+
+use crate::{
+    types::{Coin, RewardAccount},
+    LOG_TARGET, REWARD_ACCOUNTS,
+};
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, Encode};
+use sp_io::hashing::{blake2_256, twox_128};
+
+pub struct RewardAccounts;
+
+mod api_declarations {
+    use super::*;
+    sp_api::decl_runtime_apis! {
+        pub trait RewardAccountsApi {
+            fn reward_balance(account: &RewardAccount) -> Coin;
+        }
+    }
+}
+pub use api_declarations::*;
+
+impl RewardAccounts {
+    /// Balance of a reward account. Accounts never credited hold nothing.
+    pub fn balance(account: &RewardAccount) -> Coin {
+        let key = Self::add_account_prefix(account);
+        sp_io::storage::get(&key)
+            .and_then(|d| Coin::decode(&mut &*d).ok())
+            .unwrap_or_default()
+    }
+
+    /// Credit `amount` to a reward account.
+    pub fn deposit(account: &RewardAccount, amount: Coin) {
+        let balance = Self::balance(account).saturating_add(amount);
+        log::debug!(
+            target: LOG_TARGET,
+            "Reward account {:?} balance set to {}",
+            sp_core::hexdisplay::HexDisplay::from(&account.0),
+            balance
+        );
+        Self::set_balance(account, balance);
+    }
+
+    /// Debit `amount` from a reward account. Withdrawals are validated to
+    /// drain the account, so this only saturates on unchecked input.
+    pub fn withdraw(account: &RewardAccount, amount: Coin) {
+        let balance = Self::balance(account).saturating_sub(amount);
+        Self::set_balance(account, balance);
+    }
+
+    fn set_balance(account: &RewardAccount, balance: Coin) {
+        let key = Self::add_account_prefix(account);
+        if balance == 0 {
+            sp_io::storage::clear(&key);
+        } else {
+            sp_io::storage::set(&key, &balance.encode());
+        }
+    }
+
+    fn add_account_prefix(account: &RewardAccount) -> Vec<u8> {
+        let account_prefix = twox_128(REWARD_ACCOUNTS);
+        [&account_prefix[..], &blake2_256(&account.0)[..]].concat()
+    }
+}
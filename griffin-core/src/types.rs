@@ -1,16 +1,23 @@
 //! Types used to construct Griffin transactions.
 use crate::h224::H224;
 use crate::header::ExtendedHeader;
+use crate::pallas_addresses::{Address as PallasAddress, Error as AddressError};
 use crate::pallas_applying::utils::BabbageError;
 use crate::pallas_codec::minicbor::{
     self, decode::Error as MiniDecError, encode::Error as MiniEncError, encode::Write as MiniWrite,
     Decode as MiniDecode, Decoder, Encode as MiniEncode, Encoder,
 };
 use crate::pallas_crypto::hash::Hash as PallasHash;
-use crate::pallas_primitives::babbage::PlutusScript as PallasPlutusScript;
+use crate::pallas_interface::PallasConversionError;
+use crate::pallas_primitives::babbage::{
+    NativeScript as PallasNativeScript, PlutusData as PallasPlutusData,
+    PlutusScript as PallasPlutusScript,
+};
+use crate::protocol_parameters::MAINNET_NETWORK_ID;
+use crate::uplc::{machine::Error as MachineError, tx::error::Error as PhaseTwoError};
 use alloc::{collections::BTreeMap, string::String, vec::Vec};
 use core::ops::{Add, AddAssign, Sub, SubAssign};
-use core::{fmt, ops::Deref};
+use core::{fmt, ops::Deref, str::FromStr};
 use frame_support::{
     dispatch::{DispatchClass, DispatchInfo, GetDispatchInfo, Pays},
     traits::{InherentBuilder, SignedTransactionBuilder},
@@ -18,7 +25,10 @@ use frame_support::{
 };
 use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
 use scale_info::TypeInfo;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use sp_core::{ed25519::Public, H256};
 use sp_runtime::{
     traits::{
@@ -65,6 +75,10 @@ pub struct Input {
 
 pub type RequiredSigner = H224;
 
+/// Bytes of a Cardano reward address: a header (`0xe0` for key hashes, `0xf0`
+/// for script hashes, plus the network ID) followed by a stake credential.
+pub type RewardAccount = Address;
+
 #[derive(
     Serialize,
     Deserialize,
@@ -81,10 +95,26 @@ pub type RequiredSigner = H224;
 pub struct TransactionBody {
     pub inputs: Vec<Input>,
     pub outputs: Vec<Output>,
+    pub fee: Coin,
     pub ttl: Option<u64>,
     pub validity_interval_start: Option<u64>,
     pub mint: Option<Mint>,
     pub required_signers: Option<Vec<RequiredSigner>>,
+    /// Outputs that are visible to scripts but are not consumed.
+    pub reference_inputs: Option<Vec<Input>>,
+    /// Inputs consumed instead of the regular ones if a script fails.
+    pub collateral: Option<Vec<Input>>,
+    /// Output produced instead of the regular ones if a script fails.
+    pub collateral_return: Option<Output>,
+    /// Amount of `Coin`s lost if a script fails.
+    pub total_collateral: Option<Coin>,
+    /// Hash of the auxiliary data of the transaction, which must be present
+    /// exactly when this is.
+    pub auxiliary_data_hash: Option<H256>,
+    /// Amounts withdrawn from reward accounts. Each account must be drained
+    /// completely, so withdrawing zero from an empty account is the way to
+    /// run the script ruling it.
+    pub withdrawals: Option<Vec<(RewardAccount, Coin)>>,
 }
 
 /// Hash of a 28-byte Cardano policy ID.
@@ -166,7 +196,7 @@ impl<T: Clone + fmt::Display> fmt::Display for Multiasset<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut res = String::new();
         for (p, n, a) in Vec::from(self).iter() {
-            res += &format!("  ({p}) {}: {a}\n", n.0);
+            res += &format!("  ({p}) {} [{}]: {a}\n", n.0, asset_fingerprint(p, n));
         }
         res.pop(); // Remove the last newline.
         write!(f, "{res}")
@@ -242,16 +272,56 @@ pub struct VKeyWitness {
     TypeInfo,
     Hash,
 )]
-pub struct PlutusScript(pub Vec<u8>);
+pub struct PlutusScript {
+    pub version: PlutusVersion,
+    pub bytes: Vec<u8>,
+}
+
+/// Version of the Plutus language a script is written in. It determines the
+/// prefix used for the script hash and the script context built for it.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    TypeInfo,
+    Hash,
+)]
+pub enum PlutusVersion {
+    V1,
+    #[default]
+    V2,
+    V3,
+}
 
-pub fn compute_plutus_v2_script_hash(script: PlutusScript) -> PolicyId {
-    PolicyId::from(
-        crate::pallas_applying::utils::compute_plutus_v2_script_hash(&PallasPlutusScript::<2>(
-            <_>::from(script.0),
-        )),
-    )
+impl PlutusScript {
+    pub fn new(version: PlutusVersion, bytes: Vec<u8>) -> Self {
+        Self { version, bytes }
+    }
 }
 
+pub fn compute_plutus_script_hash(script: PlutusScript) -> PolicyId {
+    use crate::pallas_applying::utils::{
+        compute_plutus_v1_script_hash, compute_plutus_v2_script_hash, compute_plutus_v3_script_hash,
+    };
+
+    let bytes = <_>::from(script.bytes);
+    PolicyId::from(match script.version {
+        PlutusVersion::V1 => compute_plutus_v1_script_hash(&PallasPlutusScript::<1>(bytes)),
+        PlutusVersion::V2 => compute_plutus_v2_script_hash(&PallasPlutusScript::<2>(bytes)),
+        PlutusVersion::V3 => compute_plutus_v3_script_hash(&PallasPlutusScript::<3>(bytes)),
+    })
+}
+
+/// Native script, ruling a script address or a minting policy by the keys
+/// signing the transaction and by its validity interval.
 #[derive(
     Serialize,
     Deserialize,
@@ -265,15 +335,99 @@ pub fn compute_plutus_v2_script_hash(script: PlutusScript) -> PolicyId {
     TypeInfo,
     Hash,
 )]
+pub enum NativeScript {
+    /// Satisfied if the transaction is signed by the key with this hash.
+    ScriptPubkey(H224),
+    /// Satisfied if all the scripts are.
+    ScriptAll(Vec<NativeScript>),
+    /// Satisfied if any of the scripts is.
+    ScriptAny(Vec<NativeScript>),
+    /// Satisfied if at least the given number of scripts are.
+    ScriptNOfK(u32, Vec<NativeScript>),
+    /// Satisfied if the validity interval starts at this slot or later.
+    InvalidBefore(u64),
+    /// Satisfied if the validity interval ends at this slot or earlier.
+    InvalidHereafter(u64),
+}
+
+pub fn compute_native_script_hash(script: NativeScript) -> PolicyId {
+    PolicyId::from(crate::pallas_applying::utils::compute_native_script_hash(
+        &PallasNativeScript::from(script),
+    ))
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Default,
+    TypeInfo,
+    Hash,
+    MiniEncode,
+    MiniDecode,
+)]
 pub struct ExUnits {
+    #[n(0)]
     pub mem: u64,
+    #[n(1)]
     pub steps: u64,
 }
 
+impl Add for ExUnits {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        ExUnits {
+            mem: self.mem.saturating_add(other.mem),
+            steps: self.steps.saturating_add(other.steps),
+        }
+    }
+}
+
+impl ExUnits {
+    /// Whether these units fit within `limit`, both in memory and in steps.
+    pub fn fits(&self, limit: &ExUnits) -> bool {
+        self.mem <= limit.mem && self.steps <= limit.steps
+    }
+}
+
+/// Cost model parameters for each Plutus language version, in the order
+/// given by the Cardano ledger specification.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+    Hash,
+    MiniEncode,
+    MiniDecode,
+)]
+#[cbor(map)]
+pub struct CostModels {
+    #[n(0)]
+    pub plutus_v1: Option<Vec<i64>>,
+    #[n(1)]
+    pub plutus_v2: Option<Vec<i64>>,
+    #[n(2)]
+    pub plutus_v3: Option<Vec<i64>>,
+}
+
 /// Cardano-like redeemer tag.
 ///
-/// We are not using the `Cert` nor the `Rewards`
-/// variants.
+/// We are not using the `Cert` variant.
 #[derive(
     Serialize,
     Deserialize,
@@ -290,6 +444,8 @@ pub struct ExUnits {
 pub enum RedeemerTag {
     Spend,
     Mint,
+    /// Redeemer of the script ruling a reward account withdrawn from.
+    Reward,
 }
 
 #[derive(
@@ -308,6 +464,40 @@ pub enum RedeemerTag {
 )]
 pub struct PlutusData(pub Vec<u8>);
 
+impl PlutusData {
+    /// Whether the data is encoded as Pallas encodes it. The ledger decodes
+    /// and re-encodes Plutus data before hashing it, so data that does not
+    /// round-trip is hashed differently than by [`compute_datum_hash`].
+    pub fn is_canonical(&self) -> bool {
+        PallasPlutusData::try_from(self.clone()).is_ok_and(|data| PlutusData::from(data) == *self)
+    }
+}
+
+/// CBOR of the auxiliary data of a transaction, that is, its metadata
+/// (possibly along with scripts).
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+    Hash,
+)]
+pub struct AuxiliaryData(pub Vec<u8>);
+
+/// Hash of auxiliary data, as stored in the `auxiliary_data_hash` of a body.
+/// The bytes are hashed as they are, while the ledger hashes the auxiliary
+/// data as re-encoded by Pallas, so both only agree on canonically encoded
+/// auxiliary data (such as the one encoded by Pallas itself).
+pub fn compute_auxiliary_data_hash(aux_data: &AuxiliaryData) -> H256 {
+    BlakeTwo256::hash(&aux_data.0)
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -325,6 +515,8 @@ pub struct Redeemer {
     pub tag: RedeemerTag,
     pub index: u32,
     pub data: PlutusData,
+    /// Execution budget declared for the script run by this redeemer.
+    pub ex_units: ExUnits,
 }
 
 /// Fragment of a Cardano witness set.
@@ -346,6 +538,9 @@ pub struct WitnessSet {
     pub vkeywitness: Option<Vec<VKeyWitness>>,
     pub redeemer: Option<Vec<Redeemer>>,
     pub plutus_script: Option<Vec<PlutusScript>>,
+    pub native_script: Option<Vec<NativeScript>>,
+    /// Datums whose hashes are referenced by spent or created outputs.
+    pub plutus_data: Option<Vec<PlutusData>>,
 }
 
 impl From<Vec<VKeyWitness>> for WitnessSet {
@@ -354,17 +549,23 @@ impl From<Vec<VKeyWitness>> for WitnessSet {
             vkeywitness: Some(wits),
             plutus_script: None,
             redeemer: None,
+            native_script: None,
+            plutus_data: None,
         }
     }
 }
 
 /// Griffin transaction type. It is divided in a body and a witness set.
-#[derive(
-    Serialize, Deserialize, DecodeWithMemTracking, Default, Debug, PartialEq, Eq, Clone, TypeInfo,
-)]
+#[derive(Serialize, Deserialize, DecodeWithMemTracking, Debug, PartialEq, Eq, Clone, TypeInfo)]
 pub struct Transaction {
     pub transaction_body: TransactionBody,
     pub transaction_witness_set: WitnessSet,
+    /// Whether the scripts of the transaction are expected to succeed. When
+    /// set to `false`, scripts must fail and only the collateral is consumed.
+    pub is_valid: bool,
+    /// Metadata attached to the transaction. It is not part of the body, but
+    /// bound to it by `auxiliary_data_hash`.
+    pub auxiliary_data: Option<AuxiliaryData>,
 
     // The function that should be called
     pub function: TransactionCall,
@@ -376,13 +577,20 @@ impl Encode for Transaction {
     fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
         let transaction_body = self.transaction_body.encode();
         let transaction_witness_set = self.transaction_witness_set.encode();
+        let is_valid = self.is_valid.encode();
+        let auxiliary_data = self.auxiliary_data.encode();
 
-        let total_len = (transaction_body.len() + transaction_witness_set.len()) as u32;
+        let total_len = (transaction_body.len()
+            + transaction_witness_set.len()
+            + is_valid.len()
+            + auxiliary_data.len()) as u32;
         let size = parity_scale_codec::Compact::<u32>(total_len).encode();
 
         dest.write(&size);
         dest.write(&transaction_body);
         dest.write(&transaction_witness_set);
+        dest.write(&is_valid);
+        dest.write(&auxiliary_data);
     }
 }
 
@@ -395,11 +603,15 @@ impl Decode for Transaction {
 
         let transaction_body = <TransactionBody as Decode>::decode(input)?;
         let transaction_witness_set = <WitnessSet as Decode>::decode(input)?;
+        let is_valid = <bool as Decode>::decode(input)?;
+        let auxiliary_data = <Option<AuxiliaryData> as Decode>::decode(input)?;
         let function = TransactionCall::from(0);
 
         Ok(Transaction {
             transaction_body,
             transaction_witness_set,
+            is_valid,
+            auxiliary_data,
             function,
         })
     }
@@ -431,7 +643,11 @@ impl GetDispatchInfo for Transaction {
             call_weight: Weight::zero(),
             extension_weight: Weight::zero(),
             class: DispatchClass::Normal,
-            pays_fee: Pays::No,
+            pays_fee: if self.transaction_body.fee > 0 {
+                Pays::Yes
+            } else {
+                Pays::No
+            },
         }
     }
 }
@@ -462,74 +678,209 @@ pub enum UTxOError {
     /// A Babbage era validation error returned by Pallas.
     Babbage(BabbageError),
     /// An phase two validation error returned by UPLC.
-    PhaseTwo(crate::uplc::tx::error::Error),
+    PhaseTwo(PhaseTwoError),
+    /// The script run by the redeemer with the given tag and index spent more
+    /// than the execution units declared by the redeemer.
+    ExUnitsExceeded { tag: String, index: u32 },
+    /// The transaction cannot be converted into a Pallas one.
+    Conversion(PallasConversionError),
     /// No other kind of error should be received.
     Fail,
 }
 
-/// `UTxOError`s are mapped to custom Substrate errors.
-impl From<UTxOError> for InvalidTransaction {
-    fn from(utxo_error: UTxOError) -> Self {
+impl From<PallasConversionError> for UTxOError {
+    fn from(err: PallasConversionError) -> Self {
+        UTxOError::Conversion(err)
+    }
+}
+
+impl UTxOError {
+    /// Stable numeric code of the error, preserved through the transaction
+    /// pool as the code of a custom `InvalidTransaction`.
+    pub fn code(&self) -> u8 {
         use BabbageError::*;
-        use InvalidTransaction::Custom;
         use UTxOError::*;
 
-        match utxo_error {
-            Fail => Custom(32),
+        match self {
+            Fail => 32,
             Babbage(err) => match err {
-                TxWrongNetworkID => Custom(64),
-                OutputWrongNetworkID => Custom(65),
-                BlockPrecedesValInt => Custom(128),
-                BlockExceedsValInt => Custom(129),
-                AddressDecoding => Custom(192),
-                InputDecoding => Custom(193),
-                MaxTxSizeExceeded => Custom(194),
-                UnknownTxSize => Custom(195),
-                DuplicateInput => Custom(196),
-                TxInsEmpty => Custom(197),
-                OutputAlreadyInUTxO => Custom(198),
-                InputNotInUTxO => Custom(208),
-                CollateralNotInUTxO => Custom(209),
-                ReferenceInputNotInUTxO => Custom(210),
-                RefInputNotInUTxO => Custom(211),
-                CollateralMissing => Custom(212),
-                TooManyCollaterals => Custom(213),
-                CollateralNotVKeyLocked => Custom(214),
-                CollateralMinLovelace => Custom(215),
-                NonLovelaceCollateral => Custom(216),
-                CollateralWrongAssets => Custom(217),
-                CollateralAnnotation => Custom(218),
-                FeeBelowMin => Custom(219),
-                NegativeValue => Custom(220),
-                PreservationOfValue => Custom(221),
-                MinLovelaceUnreached => Custom(222),
-                MaxValSizeExceeded => Custom(223),
-                UnneededDatum => Custom(224),
-                UnneededNativeScript => Custom(225),
-                UnneededPlutusV1Script => Custom(226),
-                UnneededPlutusV2Script => Custom(227),
-                TxExUnitsExceeded => Custom(228),
-                MintingLacksPolicy => Custom(229),
-                MetadataHash => Custom(230),
-                DatumMissing => Custom(231),
-                UnsupportedPlutusLanguage => Custom(232),
-                ScriptIntegrityHash => Custom(233),
-                RedeemerMissing => Custom(240),
-                ReqSignerMissing => Custom(241),
-                VKWitnessMissing => Custom(242),
-                ScriptWitnessMissing => Custom(243),
-                UnneededRedeemer => Custom(244),
-                ReqSignerWrongSig => Custom(245),
-                VKWrongSignature => Custom(246),
+                TxWrongNetworkID => 64,
+                OutputWrongNetworkID => 65,
+                BlockPrecedesValInt => 128,
+                BlockExceedsValInt => 129,
+                AddressDecoding => 192,
+                InputDecoding => 193,
+                MaxTxSizeExceeded => 194,
+                UnknownTxSize => 195,
+                DuplicateInput => 196,
+                TxInsEmpty => 197,
+                OutputAlreadyInUTxO => 198,
+                ValidityFlagMismatch => 199,
+                InputNotInUTxO => 208,
+                CollateralNotInUTxO => 209,
+                ReferenceInputNotInUTxO => 210,
+                RefInputNotInUTxO => 211,
+                CollateralMissing => 212,
+                TooManyCollaterals => 213,
+                CollateralNotVKeyLocked => 214,
+                CollateralMinLovelace => 215,
+                NonLovelaceCollateral => 216,
+                CollateralWrongAssets => 217,
+                CollateralAnnotation => 218,
+                FeeBelowMin => 219,
+                NegativeValue => 220,
+                PreservationOfValue => 221,
+                MinLovelaceUnreached => 222,
+                MaxValSizeExceeded => 223,
+                UnneededDatum => 224,
+                UnneededNativeScript => 225,
+                UnneededPlutusV1Script => 226,
+                UnneededPlutusV2Script => 227,
+                TxExUnitsExceeded => 228,
+                MintingLacksPolicy => 229,
+                MetadataHash => 230,
+                DatumMissing => 231,
+                UnsupportedPlutusLanguage => 232,
+                ScriptIntegrityHash => 233,
+                BlockExUnitsExceeded => 234,
+                RedeemerMissing => 240,
+                ReqSignerMissing => 241,
+                VKWitnessMissing => 242,
+                ScriptWitnessMissing => 243,
+                UnneededRedeemer => 244,
+                ReqSignerWrongSig => 245,
+                VKWrongSignature => 246,
+                NativeScriptUnsatisfied => 248,
+                IncompleteWithdrawal => 249,
+                DuplicateWithdrawal => 250,
+                UnneededPlutusV3Script => 251,
             },
-            PhaseTwo(_) => Custom(247),
+            PhaseTwo(err) => phase_two_code(err),
+            ExUnitsExceeded { .. } => 186,
+            Conversion(PallasConversionError::MalformedPlutusData) => 187,
+            Conversion(PallasConversionError::UnsupportedScriptRef) => 188,
+            Conversion(PallasConversionError::MalformedAuxiliaryData) => 189,
         }
     }
+
+    /// Trace logs of the failing script, if any.
+    pub fn traces(&self) -> Vec<String> {
+        match self {
+            UTxOError::PhaseTwo(err) => phase_two_traces(err),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Codes of phase two errors. Scripts failing on their own are reported with
+/// code 247, and redeemer errors with the code of the underlying error.
+fn phase_two_code(err: &PhaseTwoError) -> u8 {
+    use PhaseTwoError::*;
+
+    match err {
+        Address(_) => 160,
+        BadWithdrawalAddress => 161,
+        FlatDecode(_) => 162,
+        FragmentDecode(_) => 163,
+        WrongEra(_) => 164,
+        NativeScriptPhaseTwo => 165,
+        NoRedeemers => 166,
+        RequiredRedeemersMismatch { .. } => 167,
+        ExtraneousRedeemer => 168,
+        ResolvedInputNotFound(_) => 169,
+        NonScriptWithdrawal => 170,
+        NonScriptStakeCredential => 171,
+        NoGuardrailScriptForProcedure => 172,
+        CostModelNotFound(_) => 173,
+        ByronAddressNotAllowed => 174,
+        InlineDatumNotAllowed => 175,
+        ScriptAndInputRefNotAllowed => 176,
+        NoPaymentCredential => 177,
+        MissingRequiredDatum { .. } => 178,
+        MissingRequiredScript { .. } => 179,
+        MissingRequiredInlineDatumOrHash => 180,
+        UnsupportedCertificateType => 181,
+        MissingScriptForRedeemer => 182,
+        ApplyParamsError => 183,
+        SlotTooFarInThePast { .. } => 184,
+        Machine(MachineError::OutOfExError(_), _, _) => 185,
+        Machine(..) => 247,
+        RedeemerError { err, .. } => phase_two_code(err),
+    }
+}
+
+fn phase_two_traces(err: &PhaseTwoError) -> Vec<String> {
+    match err {
+        PhaseTwoError::Machine(_, _, traces) => traces.clone(),
+        PhaseTwoError::RedeemerError { err, .. } => phase_two_traces(err),
+        _ => Vec::new(),
+    }
+}
+
+/// `UTxOError`s are mapped to custom Substrate errors.
+impl From<UTxOError> for InvalidTransaction {
+    fn from(utxo_error: UTxOError) -> Self {
+        InvalidTransaction::Custom(utxo_error.code())
+    }
 }
 
 /// The Result of dispatching a UTXO transaction.
 pub type DispatchResult = Result<(), UTxOError>;
 
+/// Reasons to reject a block.
+#[derive(Debug)]
+pub enum BlockError {
+    /// The extrinsic at position `index` of the block, with hash `tx_hash`,
+    /// could not be applied.
+    InvalidExtrinsic {
+        index: u32,
+        tx_hash: H256,
+        error: UTxOError,
+    },
+    /// The block selects the committee of epoch `announced`, while the
+    /// committee due to be selected is that of `due`, if any.
+    UnexpectedCommitteeSelection { due: Option<u64>, announced: u64 },
+    /// The state root in the header is not the one resulting from execution.
+    StateRootMismatch { expected: H256, computed: H256 },
+    /// The extrinsics root in the header is not the one of the extrinsics.
+    ExtrinsicsRootMismatch { expected: H256, computed: H256 },
+    /// The committee and authority set logs in the header are not the ones
+    /// deposited by execution.
+    DigestLogsMismatch,
+    /// The state the block is executed on holds no protocol parameters.
+    MissingProtocolParameters,
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockError::InvalidExtrinsic {
+                index,
+                tx_hash,
+                error,
+            } => write!(
+                f,
+                "extrinsic {index} ({tx_hash:?}) is invalid: {error:?} (code {})",
+                error.code()
+            ),
+            BlockError::UnexpectedCommitteeSelection { due, announced } => write!(
+                f,
+                "unexpected committee selection for epoch {announced} (due: {due:?})"
+            ),
+            BlockError::StateRootMismatch { expected, computed } => write!(
+                f,
+                "state root mismatch: header has {expected:?}, computed {computed:?}"
+            ),
+            BlockError::ExtrinsicsRootMismatch { expected, computed } => write!(
+                f,
+                "extrinsics root mismatch: header has {expected:?}, computed {computed:?}"
+            ),
+            BlockError::DigestLogsMismatch => write!(f, "digest logs mismatch"),
+            BlockError::MissingProtocolParameters => write!(f, "missing protocol parameters"),
+        }
+    }
+}
+
 /// Bytes of the Plutus Data.
 #[derive(
     Serialize,
@@ -562,6 +913,50 @@ impl<C> MiniEncode<C> for Datum {
     }
 }
 
+/// Datum attached to an output: either the datum itself or the hash of a
+/// datum that must be supplied in the witness set when the output is spent.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    TypeInfo,
+)]
+pub enum DatumOption {
+    /// Blake2b-256 hash of the CBOR of the datum.
+    Hash(H256),
+    /// Datum stored in the output.
+    Inline(Datum),
+}
+
+impl DatumOption {
+    /// The inline datum, if any.
+    pub fn inline(&self) -> Option<&Datum> {
+        match self {
+            DatumOption::Inline(datum) => Some(datum),
+            DatumOption::Hash(_) => None,
+        }
+    }
+}
+
+impl From<Datum> for DatumOption {
+    fn from(datum: Datum) -> Self {
+        DatumOption::Inline(datum)
+    }
+}
+
+/// Hash of a datum, as stored in `DatumOption::Hash`. The bytes are hashed as
+/// they are, so the hash only agrees with the one checked by the ledger for
+/// datums that are canonically encoded (see [`PlutusData::is_canonical`]).
+pub fn compute_datum_hash(datum: &Datum) -> H256 {
+    BlakeTwo256::hash(&datum.0)
+}
+
 /// Sample data type to used to demonstrate {en,de}coding from the Datum.
 #[derive(Debug, PartialEq, Eq, Clone, MiniEncode, MiniDecode)]
 pub enum FakeDatum {
@@ -582,18 +977,15 @@ pub struct AssetClass {
 }
 
 /// Bytes of a Cardano address.
-#[derive(
-    Serialize,
-    Deserialize,
-    Encode,
-    Decode,
-    DecodeWithMemTracking,
-    PartialEq,
-    Eq,
-    Clone,
-    TypeInfo,
-    Hash,
-)]
+///
+/// Addresses are displayed and serialized in the format customary for their
+/// kind: bech32 for Shelley addresses and reward accounts, with the
+/// human-readable part given by their network ID (e.g. `addr` or `addr_test`),
+/// base58 for Byron addresses, and hex for anything else. User-facing output
+/// takes the human-readable part from the network ID of the chain instead,
+/// through [`Address::to_string_on`]. Addresses are parsed from any of these
+/// formats, and from the hex encoding of any valid Cardano address.
+#[derive(Encode, Decode, DecodeWithMemTracking, PartialEq, Eq, Clone, TypeInfo, Hash)]
 pub struct Address(pub Vec<u8>);
 
 /// Transaction outputs.
@@ -612,12 +1004,92 @@ pub struct Address(pub Vec<u8>);
 pub struct Output {
     pub address: Address,
     pub value: Value,
-    pub datum_option: Option<Datum>,
+    pub datum_option: Option<DatumOption>,
+    /// A script that can be used through reference inputs instead of being
+    /// attached to the witness set.
+    pub script_ref: Option<PlutusScript>,
 }
 
 impl fmt::Display for Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(self.0.as_slice()))
+        match PallasAddress::from_bytes(&self.0) {
+            Ok(address) => write!(f, "{address}"),
+            Err(_) => write!(f, "{}", hex::encode(self.0.as_slice())),
+        }
+    }
+}
+
+impl Address {
+    /// The address as displayed on a chain with network ID `network_id`.
+    /// Shelley addresses and reward accounts are given in bech32 with the
+    /// human-readable part of that network, whatever the network ID of their
+    /// header; other addresses are displayed as usual.
+    pub fn to_string_on(&self, network_id: u8) -> String {
+        use bech32::ToBase32;
+
+        let mainnet = network_id == MAINNET_NETWORK_ID;
+        let hrp = match PallasAddress::from_bytes(&self.0) {
+            Ok(PallasAddress::Shelley(_)) if mainnet => "addr",
+            Ok(PallasAddress::Shelley(_)) => "addr_test",
+            Ok(PallasAddress::Stake(_)) if mainnet => "stake",
+            Ok(PallasAddress::Stake(_)) => "stake_test",
+            _ => return format!("{}", self),
+        };
+
+        bech32::encode(hrp, self.0.to_base32(), bech32::Variant::Bech32)
+            .expect("address HRPs are valid")
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    /// Parses hex, optionally prefixed by `0x`, and otherwise bech32 or base58.
+    /// Hex input must encode a valid Cardano address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(bytes) = hex::decode(s.strip_prefix("0x").unwrap_or(s)) {
+            PallasAddress::from_bytes(&bytes)?;
+            return Ok(Address(bytes));
+        }
+
+        PallasAddress::from_str(s).map(|address| Address(address.to_vec()))
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepts addresses as strings and, as they used to be serialized, as
+/// sequences of bytes.
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AddressVisitor;
+
+        impl<'de> Visitor<'de> for AddressVisitor {
+            type Value = Address;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an address in bech32, base58 or hex, or its bytes")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Address, E> {
+                Address::from_str(s).map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Address, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+
+                Ok(Address(bytes))
+            }
+        }
+
+        deserializer.deserialize_any(AddressVisitor)
     }
 }
 
@@ -634,6 +1106,7 @@ impl From<(Address, Coin)> for Output {
             address: a_c.0,
             value: Value::Coin(a_c.1),
             datum_option: None,
+            script_ref: None,
         }
     }
 }
@@ -644,6 +1117,7 @@ impl From<(Address, Value)> for Output {
             address,
             value,
             datum_option: None,
+            script_ref: None,
         }
     }
 }
@@ -653,7 +1127,8 @@ impl From<(Address, Coin, Datum)> for Output {
         Self {
             address: a_c_d.0,
             value: Value::Coin(a_c_d.1),
-            datum_option: Some(a_c_d.2),
+            datum_option: Some(DatumOption::Inline(a_c_d.2)),
+            script_ref: None,
         }
     }
 }
@@ -663,7 +1138,8 @@ impl From<(Address, Value, Datum)> for Output {
         Self {
             address,
             value,
-            datum_option: Some(datum),
+            datum_option: Some(DatumOption::Inline(datum)),
+            script_ref: None,
         }
     }
 }
@@ -673,7 +1149,8 @@ impl From<(Address, Coin, Option<Datum>)> for Output {
         Self {
             address,
             value: Value::Coin(coin),
-            datum_option,
+            datum_option: datum_option.map(DatumOption::Inline),
+            script_ref: None,
         }
     }
 }
@@ -689,7 +1166,8 @@ impl From<(Address, Coin, Multiasset<Coin>, Option<Datum>)> for Output {
             } else {
                 Value::Multiasset(coin, ma)
             },
-            datum_option,
+            datum_option: datum_option.map(DatumOption::Inline),
+            script_ref: None,
         }
     }
 }
@@ -699,7 +1177,8 @@ impl From<(Address, Value, Option<Datum>)> for Output {
         Self {
             address,
             value,
-            datum_option,
+            datum_option: datum_option.map(DatumOption::Inline),
+            script_ref: None,
         }
     }
 }
@@ -728,23 +1207,56 @@ impl From<(Vec<Input>, Vec<Output>)> for Transaction {
             transaction_body: TransactionBody {
                 inputs,
                 outputs,
+                fee: 0,
                 ttl: None,
                 validity_interval_start: None,
                 mint: None,
                 required_signers: None,
+                reference_inputs: None,
+                collateral: None,
+                collateral_return: None,
+                total_collateral: None,
+                auxiliary_data_hash: None,
+                withdrawals: None,
             },
             transaction_witness_set: WitnessSet::default(),
+            is_valid: true,
+            auxiliary_data: None,
             function: TransactionCall::default(),
         }
     }
 }
 
+impl Default for Transaction {
+    fn default() -> Self {
+        Transaction::from((vec![], vec![]))
+    }
+}
+
 pub fn address_from_hex(hex: &str) -> Address {
     use hex::FromHex;
 
     Address(<Vec<u8>>::from_hex(hex).unwrap())
 }
 
+/// Enterprise address locked by a native script. Such addresses always start
+/// with `0x71`.
+pub fn address_from_native_script(script: NativeScript) -> Address {
+    let mut hash_with_header: Vec<u8> = alloc::vec![0x71];
+    hash_with_header.extend_from_slice(compute_native_script_hash(script).as_bytes());
+
+    Address(hash_with_header)
+}
+
+/// Reward account ruled by the script with hash `script_hash`. Such accounts
+/// always start with `0xf0`.
+pub fn reward_account_from_script_hash(script_hash: &PolicyId) -> RewardAccount {
+    let mut hash_with_header: Vec<u8> = alloc::vec![0xf0];
+    hash_with_header.extend_from_slice(script_hash.as_bytes());
+
+    Address(hash_with_header)
+}
+
 pub fn address_from_pk(pk: &Public) -> Address {
     use crate::pallas_crypto::hash::Hasher as PallasHasher;
 
@@ -755,6 +1267,52 @@ pub fn address_from_pk(pk: &Public) -> Address {
     Address(keyhash_with_header)
 }
 
+/// Base address with payment key `payment_pk` and staking key `stake_pk`.
+/// Such addresses always start with `0x01`.
+pub fn base_address_from_pks(payment_pk: &Public, stake_pk: &Public) -> Address {
+    use crate::pallas_crypto::hash::Hasher as PallasHasher;
+
+    let mut keyhashes_with_header: Vec<u8> = alloc::vec![0x01];
+    keyhashes_with_header.extend_from_slice(PallasHasher::<224>::hash(&payment_pk.0).as_ref());
+    keyhashes_with_header.extend_from_slice(PallasHasher::<224>::hash(&stake_pk.0).as_ref());
+
+    Address(keyhashes_with_header)
+}
+
+/// [CIP-14](https://cips.cardano.org/cip/CIP-0014) fingerprint of an asset,
+/// such as `asset1rjklcrnsdzqp65wjgrg55sy9723kw09mlgvlc3`.
+pub fn asset_fingerprint(policy: &PolicyId, name: &AssetName) -> String {
+    use bech32::ToBase32;
+
+    let digest = asset_fingerprint_hash(policy, name);
+    bech32::encode("asset", digest.to_base32(), bech32::Variant::Bech32)
+        .expect("the asset HRP is valid")
+}
+
+/// The Blake2b-160 digest of the policy ID and name of an asset, which its
+/// [`asset_fingerprint`] encodes.
+pub fn asset_fingerprint_hash(policy: &PolicyId, name: &AssetName) -> [u8; 20] {
+    use crate::pallas_crypto::hash::Hasher as PallasHasher;
+
+    let mut hasher = PallasHasher::<160>::new();
+    hasher.input(policy.as_bytes());
+    hasher.input(name.0.as_bytes());
+    *hasher.finalize()
+}
+
+/// The digest encoded by a CIP-14 fingerprint, or `None` if `fingerprint` is
+/// not one.
+pub fn decode_asset_fingerprint(fingerprint: &str) -> Option<[u8; 20]> {
+    use bech32::FromBase32;
+
+    match bech32::decode(fingerprint).ok()? {
+        (hrp, data, bech32::Variant::Bech32) if hrp == "asset" => {
+            Vec::<u8>::from_base32(&data).ok()?.try_into().ok()
+        }
+        _ => None,
+    }
+}
+
 impl<A> From<(PolicyId, AssetName, A)> for Multiasset<A> {
     fn from((policy, name, amount): (PolicyId, AssetName, A)) -> Self {
         EncapBTree::<PolicyId, EncapBTree<AssetName, A>>(BTreeMap::from(
@@ -1063,3 +1621,101 @@ impl From<Transaction> for TransactionCall {
         Self(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+    const SHAWN_ADDRESS: &str = "6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6544b47329c4";
+
+    const SHAWN_BECH32: &str = "addr1vyq7vvqhtznt4hatq5p4el7gudpckwhl9f8dce2yk3ejn3qk40m29";
+
+    #[test]
+    fn address_formats() {
+        let address = address_from_hex(SHAWN_ADDRESS);
+
+        assert_eq!(address.to_string(), SHAWN_BECH32);
+        assert_eq!(Address::from_str(SHAWN_BECH32).unwrap(), address);
+        assert_eq!(Address::from_str(SHAWN_ADDRESS).unwrap(), address);
+        assert_eq!(
+            Address::from_str(&format!("0x{SHAWN_ADDRESS}")).unwrap(),
+            address
+        );
+        assert!(Address::from_str("addr1invalid").is_err());
+        // Hex that is not a Cardano address, here missing its stake part.
+        assert!(Address::from_str(&format!("01{}", &SHAWN_ADDRESS[2..])).is_err());
+
+        assert_eq!(address.to_string_on(MAINNET_NETWORK_ID), SHAWN_BECH32);
+        let on_testnet = address.to_string_on(0);
+        assert!(on_testnet.starts_with("addr_test1"));
+        assert_eq!(Address::from_str(&on_testnet).unwrap(), address);
+
+        let reward_account = address_from_hex(&format!("e0{}", &SHAWN_ADDRESS[2..]));
+        assert_eq!(
+            reward_account.to_string(),
+            "stake_test1uqq7vvqhtznt4hatq5p4el7gudpckwhl9f8dce2yk3ejn3qd49lj2"
+        );
+        assert!(reward_account
+            .to_string_on(MAINNET_NETWORK_ID)
+            .starts_with("stake1"));
+    }
+
+    #[test]
+    fn address_serde() {
+        let address = address_from_hex(SHAWN_ADDRESS);
+
+        assert_tokens(&address, &[Token::Str(SHAWN_BECH32)]);
+        assert_de_tokens(&address, &[Token::Str(SHAWN_ADDRESS)]);
+
+        let mut legacy = alloc::vec![Token::Seq { len: Some(29) }];
+        legacy.extend(address.0.iter().map(|byte| Token::U8(*byte)));
+        legacy.push(Token::SeqEnd);
+        assert_de_tokens(&address, &legacy);
+    }
+
+    #[test]
+    fn cip14_fingerprints() {
+        let policy = PolicyId::from_slice(
+            &hex::decode("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373").unwrap(),
+        );
+
+        assert_eq!(
+            asset_fingerprint(&policy, &AssetName::default()),
+            "asset1rjklcrnsdzqp65wjgrg55sy9723kw09mlgvlc3"
+        );
+        assert_eq!(
+            asset_fingerprint(&policy, &AssetName::from(String::from("PATATE"))),
+            "asset13n25uv0yaf5kus35fm2k86cqy60z58d9xmde92"
+        );
+        assert_eq!(
+            decode_asset_fingerprint("asset13n25uv0yaf5kus35fm2k86cqy60z58d9xmde92"),
+            Some(asset_fingerprint_hash(
+                &policy,
+                &AssetName::from(String::from("PATATE"))
+            ))
+        );
+        assert_eq!(decode_asset_fingerprint(SHAWN_BECH32), None);
+        assert_eq!(decode_asset_fingerprint("PATATE"), None);
+    }
+
+    #[test]
+    fn error_codes_survive_the_transaction_pool() {
+        let err = UTxOError::Babbage(BabbageError::InputNotInUTxO);
+        assert_eq!(err.code(), 208);
+        assert_eq!(
+            InvalidTransaction::from(err),
+            InvalidTransaction::Custom(208)
+        );
+        assert_eq!(UTxOError::Fail.code(), 32);
+    }
+
+    #[test]
+    fn only_fee_paying_transactions_pay() {
+        let mut tx = Transaction::from((vec![], vec![]));
+        assert_eq!(tx.get_dispatch_info().pays_fee, Pays::No);
+
+        tx.transaction_body.fee = 1;
+        assert_eq!(tx.get_dispatch_info().pays_fee, Pays::Yes);
+    }
+}
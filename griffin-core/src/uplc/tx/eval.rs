@@ -55,7 +55,7 @@ pub fn eval_redeemer(
         let mut eval_result = if let Some(costs) = cost_mdl_opt {
             program.eval_as(lang, costs, Some(initial_budget))
         } else {
-            program.eval_version(ExBudget::default(), lang)
+            program.eval_version(*initial_budget, lang)
         };
 
         let cost = eval_result.cost();
@@ -94,7 +94,7 @@ pub fn eval_redeemer(
                     cost_mdls
                         .plutus_v1
                         .as_ref()
-                        .ok_or(Error::CostModelNotFound(Language::PlutusV2))
+                        .ok_or(Error::CostModelNotFound(Language::PlutusV1))
                 })
                 .transpose()?,
             initial_budget,
@@ -1,36 +1,89 @@
 //! UTxO interface to storage.
 
 use crate::{
-    types::{Address, AssetName, Input, Output, PolicyId},
-    LOG_TARGET, UTXO_SET,
+    types::{asset_fingerprint_hash, Address, AssetName, Input, Output, PolicyId, Value},
+    LOG_TARGET, UTXO_BY_ADDRESS, UTXO_BY_ASSET, UTXO_BY_FINGERPRINT, UTXO_SET,
 };
 use alloc::vec::Vec;
 use parity_scale_codec::{Decode, Encode};
-use sp_io::hashing::twox_128;
+use sp_io::hashing::{blake2_256, twox_128};
 
 pub struct TransparentUtxoSet;
 
 mod api_declarations {
     use super::*;
     sp_api::decl_runtime_apis! {
+        /// Version 2 returns UTxOs together with their references, one
+        /// page at a time. Version 3 looks UTxOs up by the CIP-14 fingerprint
+        /// of their assets.
+        #[api_version(3)]
         pub trait TransparentUtxoSetApi {
             fn peek_utxo(input: &Input) -> Option<Output>;
+            #[changed_in(2)]
             fn peek_utxo_by_address(addr: &Address) -> Vec<Output>;
+            fn peek_utxo_by_address(
+                addr: &Address,
+                after: Option<Input>,
+                limit: u32,
+            ) -> Vec<(Input, Output)>;
+            #[changed_in(2)]
             fn peek_utxo_with_asset(asset_name: &AssetName, asset_policy: &PolicyId) -> Vec<Output>;
+            fn peek_utxo_with_asset(
+                asset_name: &AssetName,
+                asset_policy: &PolicyId,
+                after: Option<Input>,
+                limit: u32,
+            ) -> Vec<(Input, Output)>;
+            #[api_version(3)]
+            fn peek_utxo_with_fingerprint(
+                fingerprint: [u8; 20],
+                after: Option<Input>,
+                limit: u32,
+            ) -> Vec<(Input, Output)>;
         }
     }
 }
 pub use api_declarations::*;
 
 impl TransparentUtxoSet {
-    /// Fetch a utxo from the set with matching addr.
-    pub fn peek_utxos_from_address(addr: &Address) -> Vec<Output> {
-        Self::peek_utxos_by(|o| o.address == *addr)
+    /// Fetch at most `limit` utxos from the set with matching addr, together
+    /// with their references. Utxos are ordered by the SCALE encoding of their
+    /// references, i.e., by transaction hash bytes and then by the
+    /// little-endian bytes of the output index (so index 256 comes before
+    /// index 1). If `after` is given, only those following it are returned.
+    pub fn peek_utxos_from_address(
+        addr: &Address,
+        after: Option<&Input>,
+        limit: u32,
+    ) -> Vec<(Input, Output)> {
+        Self::peek_indexed(&Self::address_index_prefix(addr), after, limit)
     }
 
-    /// Fetch utxos from the set containing a given asset.
-    pub fn peek_utxos_with_asset(asset_name: &AssetName, asset_policy: &PolicyId) -> Vec<Output> {
-        Self::peek_utxos_by(|o| o.value.contains_asset(asset_name, asset_policy))
+    /// Fetch at most `limit` utxos from the set containing a given asset,
+    /// together with their references. Pagination works as in
+    /// `peek_utxos_from_address`.
+    pub fn peek_utxos_with_asset(
+        asset_name: &AssetName,
+        asset_policy: &PolicyId,
+        after: Option<&Input>,
+        limit: u32,
+    ) -> Vec<(Input, Output)> {
+        Self::peek_indexed(
+            &Self::asset_index_prefix(asset_name, asset_policy),
+            after,
+            limit,
+        )
+    }
+
+    /// Fetch at most `limit` utxos from the set containing an asset with the
+    /// given fingerprint digest (see [`asset_fingerprint_hash`]), together with
+    /// their references. Pagination works as in `peek_utxos_from_address`.
+    pub fn peek_utxos_with_fingerprint(
+        fingerprint: &[u8; 20],
+        after: Option<&Input>,
+        limit: u32,
+    ) -> Vec<(Input, Output)> {
+        Self::peek_indexed(&Self::fingerprint_index_prefix(fingerprint), after, limit)
     }
 
     /// Fetch a utxo from the set.
@@ -43,10 +96,16 @@ impl TransparentUtxoSet {
     pub fn consume_utxo(input: &Input) -> Option<Output> {
         let maybe_output = Self::peek_utxo(input);
         Self::remove_utxo(input);
+        if let Some(output) = &maybe_output {
+            for key in Self::index_keys(input, output) {
+                sp_io::storage::clear(&key);
+            }
+        }
         maybe_output
     }
 
-    /// Add a utxo into the set.
+    /// Add a utxo into the set, and index it by address, by asset and by asset
+    /// fingerprint.
     pub fn store_utxo(input: Input, output: &Output) {
         let key = Self::add_utxo_prefix(&input);
         log::debug!(
@@ -55,30 +114,95 @@ impl TransparentUtxoSet {
             sp_core::hexdisplay::HexDisplay::from(&key)
         );
         sp_io::storage::set(&key, &output.encode());
+        for index_key in Self::index_keys(&input, output) {
+            sp_io::storage::set(&index_key, &input.encode());
+        }
     }
 
-    /// Fetch a utxo from the set filtering by `f`.
-    fn peek_utxos_by<F>(f: F) -> Vec<Output>
-    where
-        F: Fn(&Output) -> bool,
-    {
-        let mut outputs: Vec<Output> = vec![];
+    /// Fetch at most `limit` utxos whose index keys start with `prefix`,
+    /// skipping those up to and including `after`.
+    ///
+    /// Only the index entries under `prefix` are visited, so the cost does
+    /// not depend on the size of the whole UTxO set.
+    fn peek_indexed(prefix: &[u8], after: Option<&Input>, limit: u32) -> Vec<(Input, Output)> {
+        let mut utxos: Vec<(Input, Output)> = vec![];
+        let start_key = match after {
+            Some(input) => [prefix, &input.encode()[..]].concat(),
+            None => prefix.to_vec(),
+        };
+        let mut some_key = sp_io::storage::next_key(&start_key);
 
-        let utxo_prefix = twox_128(UTXO_SET);
-        let mut some_key = Some(utxo_prefix.to_vec());
-
-        while let Some(key) = some_key.clone().filter(|k| k.starts_with(&utxo_prefix)) {
-            if let Some(val) = sp_io::storage::get(&key) {
-                if let Ok(o) = Output::decode(&mut &*val) {
-                    if f(&o) {
-                        outputs.push(o);
-                    }
+        while let Some(key) = some_key.filter(|k| k.starts_with(prefix)) {
+            if utxos.len() >= limit as usize {
+                break;
+            }
+            if let Some(input) =
+                sp_io::storage::get(&key).and_then(|d| Input::decode(&mut &*d).ok())
+            {
+                if let Some(output) = Self::peek_utxo(&input) {
+                    utxos.push((input, output));
                 }
             }
             some_key = sp_io::storage::next_key(&key);
         }
 
-        outputs
+        utxos
+    }
+
+    /// Keys of the index entries pointing to the utxo `input`, one for its
+    /// address and two for each asset in its value, by policy ID and name and
+    /// by fingerprint.
+    fn index_keys(input: &Input, output: &Output) -> Vec<Vec<u8>> {
+        let encoded_input = input.encode();
+        let mut keys = vec![[
+            &Self::address_index_prefix(&output.address)[..],
+            &encoded_input[..],
+        ]
+        .concat()];
+
+        if let Value::Multiasset(_, ma) = &output.value {
+            for (policy, assets) in ma.0.iter() {
+                for name in assets.0.keys() {
+                    keys.push(
+                        [
+                            &Self::asset_index_prefix(name, policy)[..],
+                            &encoded_input[..],
+                        ]
+                        .concat(),
+                    );
+                    keys.push(
+                        [
+                            &Self::fingerprint_index_prefix(&asset_fingerprint_hash(policy, name))
+                                [..],
+                            &encoded_input[..],
+                        ]
+                        .concat(),
+                    );
+                }
+            }
+        }
+
+        keys
+    }
+
+    fn address_index_prefix(addr: &Address) -> Vec<u8> {
+        let index_prefix = twox_128(UTXO_BY_ADDRESS);
+        [&index_prefix[..], &blake2_256(&addr.0)[..]].concat()
+    }
+
+    fn asset_index_prefix(asset_name: &AssetName, asset_policy: &PolicyId) -> Vec<u8> {
+        let index_prefix = twox_128(UTXO_BY_ASSET);
+        [
+            &index_prefix[..],
+            asset_policy.as_bytes(),
+            &blake2_256(&asset_name.encode())[..],
+        ]
+        .concat()
+    }
+
+    fn fingerprint_index_prefix(fingerprint: &[u8; 20]) -> Vec<u8> {
+        let index_prefix = twox_128(UTXO_BY_FINGERPRINT);
+        [&index_prefix[..], &fingerprint[..]].concat()
     }
 
     fn remove_utxo(input: &Input) {
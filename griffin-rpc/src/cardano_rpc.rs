@@ -1,19 +1,26 @@
 use derive_new::new;
 use griffin_core::checks_interface::babbage_minted_tx_from_cbor_checked;
+use griffin_core::evaluation::{EvaluationError, TransactionEvaluationApi, TxEvaluation};
 use griffin_core::pallas_primitives::babbage::Tx as BPallasTransaction;
 use griffin_core::types::Transaction;
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     proc_macros::rpc,
+    types::ErrorObjectOwned,
 };
 use parity_scale_codec::{Decode, Encode};
-use sc_transaction_pool_api::{error::IntoPoolError, TransactionPool, TransactionSource, TxHash};
+use sc_transaction_pool_api::{
+    error::{Error as PoolError, IntoPoolError},
+    TransactionPool, TransactionSource, TxHash,
+};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_core::Bytes;
+use sp_runtime::transaction_validity::InvalidTransaction;
 use std::sync::Arc;
 
-use crate::error::error_object_from;
+use crate::error::{error_object_from, rejection_object_from};
+use crate::evaluation::evaluate_at;
 
 const TX_SOURCE: TransactionSource = TransactionSource::External;
 
@@ -21,6 +28,10 @@ const TX_SOURCE: TransactionSource = TransactionSource::External;
 pub trait CardanoRpcApi<Hash> {
     #[method(name = "submit_tx")]
     async fn submit_cardano_tx(&self, ext: Bytes) -> RpcResult<Hash>;
+    /// Evaluates a transaction in CBOR format against the best block, without
+    /// submitting it.
+    #[method(name = "evaluate_tx")]
+    fn evaluate_cardano_tx(&self, ext: Bytes) -> RpcResult<TxEvaluation>;
 }
 
 #[derive(new)]
@@ -35,31 +46,68 @@ where
     P: TransactionPool + Sync + Send + 'static,
     C: Send + Sync + 'static,
     C: ProvideRuntimeApi<P::Block> + HeaderBackend<P::Block> + 'static,
+    C::Api: TransactionEvaluationApi<P::Block>,
 {
     async fn submit_cardano_tx(&self, ctx_bytes: Bytes) -> RpcResult<TxHash<P>> {
-        match babbage_minted_tx_from_cbor_checked(&ctx_bytes) {
-            Ok(mtx) => {
-                let ptx = BPallasTransaction::from(mtx);
-                let tx = Transaction::from(ptx);
-                let tx_bytes = Encode::encode(&tx);
-                let xt = match Decode::decode(&mut &tx_bytes[..]) {
-                    Ok(xt) => xt,
-                    Err(err) => return Err(error_object_from(err)),
-                };
+        let tx = griffin_tx_from_cbor(&ctx_bytes)?;
+        let tx_bytes = Encode::encode(&tx);
+        let xt = match Decode::decode(&mut &tx_bytes[..]) {
+            Ok(xt) => xt,
+            Err(err) => return Err(error_object_from(err)),
+        };
+
+        let best_block = self.client.info().best_hash;
+
+        self.pool
+            .submit_one(best_block, TX_SOURCE, xt)
+            .await
+            .map_err(|e| match e.into_pool_error() {
+                Ok(PoolError::InvalidTransaction(InvalidTransaction::Custom(code))) => {
+                    self.rejection(tx, code)
+                }
+                Ok(e) => error_object_from(e),
+                Err(e) => error_object_from(e),
+            })
+    }
 
-                let best_block = self.client.info().best_hash;
+    fn evaluate_cardano_tx(&self, ctx_bytes: Bytes) -> RpcResult<TxEvaluation> {
+        let tx = griffin_tx_from_cbor(&ctx_bytes)?;
+        let best_block = self.client.info().best_hash;
+
+        evaluate_at(&*self.client, tx, best_block)
+    }
+}
 
-                self.pool
-                    .submit_one(best_block, TX_SOURCE, xt)
-                    .await
-                    .map_err(|e| {
-                        e.into_pool_error()
-                            .map(|e| error_object_from(e))
-                            .unwrap_or_else(|e| error_object_from(e))
-                            .into()
-                    })
+impl<C, P> CardanoRpc<C, P>
+where
+    P: TransactionPool,
+    C: ProvideRuntimeApi<P::Block> + HeaderBackend<P::Block>,
+    C::Api: TransactionEvaluationApi<P::Block>,
+{
+    /// Error object for a transaction rejected by the pool with a custom
+    /// `code`. The pool only keeps the code, so the transaction is evaluated
+    /// again to recover the details of the error.
+    fn rejection(&self, tx: Transaction, code: u8) -> ErrorObjectOwned {
+        let best_block = self.client.info().best_hash;
+        let details = self
+            .client
+            .runtime_api()
+            .evaluate_transaction(best_block, tx)
+            .ok()
+            .and_then(|evaluation| evaluation.error);
+
+        rejection_object_from(details.filter(|err| err.code == code).unwrap_or_else(|| {
+            EvaluationError {
+                code,
+                message: format!("Custom error: {code}"),
+                traces: Vec::new(),
             }
-            Err(e) => Err(error_object_from(e)),
-        }
+        }))
     }
 }
+
+fn griffin_tx_from_cbor(ctx_bytes: &Bytes) -> RpcResult<Transaction> {
+    let mtx = babbage_minted_tx_from_cbor_checked(ctx_bytes).map_err(error_object_from)?;
+
+    Transaction::try_from(BPallasTransaction::from(mtx)).map_err(error_object_from)
+}
@@ -0,0 +1,38 @@
+use derive_new::new;
+use griffin_core::epoch::{EpochApi, EpochInfo};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+use crate::error::error_object_from;
+
+/// Sidechain epoch queries.
+#[rpc(client, server, namespace = "griffin")]
+pub trait EpochRpcApi<BlockHash> {
+    /// The epoch and slot of the block `at`, defaulting to the best block.
+    #[method(name = "get_current_epoch")]
+    fn current_epoch(&self, at: Option<BlockHash>) -> RpcResult<EpochInfo>;
+}
+
+#[derive(new)]
+pub struct EpochRpc<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> EpochRpcApiServer<Block::Hash> for EpochRpc<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + 'static,
+    C::Api: EpochApi<Block>,
+{
+    fn current_epoch(&self, at: Option<Block::Hash>) -> RpcResult<EpochInfo> {
+        let api = self.client.runtime_api();
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.current_epoch(block_hash).map_err(error_object_from)
+    }
+}
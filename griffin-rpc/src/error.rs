@@ -1,5 +1,43 @@
+use griffin_core::evaluation::EvaluationError;
 use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
 
+/// Code of the JSON-RPC errors reporting transactions rejected by the ledger,
+/// the same used by Substrate for invalid transactions.
+pub const TX_REJECTED_CODE: i32 = 1010;
+
 pub fn error_object_from<T: std::fmt::Debug>(err: T) -> ErrorObjectOwned {
     ErrorObject::owned::<u8>(-1, format!("{err:?}"), None)
 }
+
+/// Error object for a transaction rejected by the ledger. Its data holds the
+/// stable code of the ledger error and the trace logs of the failing script.
+pub fn rejection_object_from(err: EvaluationError) -> ErrorObjectOwned {
+    ErrorObject::owned(TX_REJECTED_CODE, err.message.clone(), Some(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use griffin_core::{pallas_applying::utils::BabbageError, types::UTxOError};
+
+    #[test]
+    fn rejection_carries_the_stable_code_of_the_error() {
+        let err = EvaluationError::from(UTxOError::Babbage(BabbageError::InputNotInUTxO));
+        let object = rejection_object_from(err.clone());
+
+        assert_eq!(object.code(), TX_REJECTED_CODE);
+        assert_eq!(object.message(), err.message);
+        let data: EvaluationError = serde_json::from_str(object.data().unwrap().get()).unwrap();
+        assert_eq!(data.code, 208);
+        assert_eq!(data, err);
+    }
+
+    #[test]
+    fn other_errors_have_no_code() {
+        let object = error_object_from("unknown block");
+
+        assert_eq!(object.code(), -1);
+        assert_eq!(object.message(), "\"unknown block\"");
+        assert!(object.data().is_none());
+    }
+}
@@ -0,0 +1,59 @@
+use derive_new::new;
+use griffin_core::evaluation::{TransactionEvaluationApi, TxEvaluation};
+use griffin_core::types::Transaction;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use parity_scale_codec::Decode;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+use crate::error::error_object_from;
+
+/// Transaction dry runs. Every method takes an optional block hash `at`,
+/// defaulting to the best block.
+#[rpc(client, server, namespace = "griffin")]
+pub trait EvaluationRpcApi<BlockHash> {
+    /// Evaluates a SCALE-encoded Griffin transaction without submitting it.
+    #[method(name = "evaluate_tx")]
+    fn evaluate_tx(&self, tx: Bytes, at: Option<BlockHash>) -> RpcResult<TxEvaluation>;
+}
+
+#[derive(new)]
+pub struct EvaluationRpc<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+/// Evaluates `transaction` on the state of block `at`.
+pub(crate) fn evaluate_at<C, Block>(
+    client: &C,
+    transaction: Transaction,
+    at: Block::Hash,
+) -> RpcResult<TxEvaluation>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: TransactionEvaluationApi<Block>,
+{
+    client
+        .runtime_api()
+        .evaluate_transaction(at, transaction)
+        .map_err(error_object_from)
+}
+
+impl<C, Block> EvaluationRpcApiServer<Block::Hash> for EvaluationRpc<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + 'static,
+    C::Api: TransactionEvaluationApi<Block>,
+{
+    fn evaluate_tx(&self, tx_bytes: Bytes, at: Option<Block::Hash>) -> RpcResult<TxEvaluation> {
+        let transaction = Transaction::decode(&mut &tx_bytes[..]).map_err(error_object_from)?;
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        evaluate_at(&*self.client, transaction, block_hash)
+    }
+}
@@ -1,3 +1,7 @@
 pub mod cardano_rpc;
+pub mod epoch;
 mod error;
+pub mod evaluation;
+pub mod protocol_parameters;
+pub mod reward_accounts;
 pub mod rpc;
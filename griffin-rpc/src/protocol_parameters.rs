@@ -0,0 +1,74 @@
+use derive_new::new;
+use griffin_core::protocol_parameters::{
+    AppliedUpdate, PendingUpdate, ProtocolParameters, ProtocolParametersApi,
+};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::ErrorObject};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+use crate::error::error_object_from;
+
+/// Protocol parameters queries. Every method takes an optional block hash
+/// `at`, defaulting to the best block.
+#[rpc(client, server, namespace = "griffin")]
+pub trait ProtocolParametersRpcApi<BlockHash> {
+    #[method(name = "get_protocol_parameters")]
+    fn protocol_parameters(&self, at: Option<BlockHash>) -> RpcResult<ProtocolParameters>;
+    #[method(name = "get_protocol_parameters_update")]
+    fn protocol_parameters_update(
+        &self,
+        at: Option<BlockHash>,
+    ) -> RpcResult<ProtocolParametersUpdateInfo>;
+}
+
+/// Governance state of the protocol parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProtocolParametersUpdateInfo {
+    /// The last update applied, together with the parameters it replaced.
+    pub last: Option<AppliedUpdate>,
+    /// The update carried by the governance UTxO, if not applied yet.
+    pub pending: Option<PendingUpdate>,
+}
+
+#[derive(new)]
+pub struct ProtocolParametersRpc<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> ProtocolParametersRpcApiServer<Block::Hash> for ProtocolParametersRpc<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + 'static,
+    C::Api: ProtocolParametersApi<Block>,
+{
+    fn protocol_parameters(&self, at: Option<Block::Hash>) -> RpcResult<ProtocolParameters> {
+        let api = self.client.runtime_api();
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.protocol_parameters(block_hash)
+            .map_err(error_object_from)?
+            .ok_or_else(|| ErrorObject::owned::<u8>(-1, "Protocol parameters not found", None))
+    }
+
+    fn protocol_parameters_update(
+        &self,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<ProtocolParametersUpdateInfo> {
+        let api = self.client.runtime_api();
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        Ok(ProtocolParametersUpdateInfo {
+            last: api
+                .last_parameters_update(block_hash)
+                .map_err(error_object_from)?,
+            pending: api
+                .pending_parameters_update(block_hash)
+                .map_err(error_object_from)?,
+        })
+    }
+}
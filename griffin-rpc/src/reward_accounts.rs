@@ -0,0 +1,41 @@
+use derive_new::new;
+use griffin_core::reward_accounts::RewardAccountsApi;
+use griffin_core::types::{Address, Coin};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+use crate::error::error_object_from;
+
+/// Reward account queries.
+#[rpc(client, server, namespace = "griffin")]
+pub trait RewardAccountsRpcApi<BlockHash> {
+    /// The balance of a reward account at the block `at`, defaulting to the
+    /// best block. The account is given in bech32 (`stake1...`) or hex.
+    #[method(name = "get_reward_balance")]
+    fn reward_balance(&self, account: Address, at: Option<BlockHash>) -> RpcResult<Coin>;
+}
+
+#[derive(new)]
+pub struct RewardAccountsRpc<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> RewardAccountsRpcApiServer<Block::Hash> for RewardAccountsRpc<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + 'static,
+    C::Api: RewardAccountsApi<Block>,
+{
+    fn reward_balance(&self, account: Address, at: Option<Block::Hash>) -> RpcResult<Coin> {
+        let api = self.client.runtime_api();
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.reward_balance(block_hash, &account)
+            .map_err(error_object_from)
+    }
+}
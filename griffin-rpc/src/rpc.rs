@@ -1,12 +1,14 @@
 use alloc::vec::Vec;
 use core::str::FromStr;
 use derive_new::new;
-use griffin_core::types::{Address, AssetName, Input, Output, PolicyId};
+use griffin_core::protocol_parameters::ProtocolParametersApi;
+use griffin_core::types::{decode_asset_fingerprint, Address, AssetName, Input, Output, PolicyId};
 use griffin_core::uplc::Hash;
 use griffin_core::utxo_set::TransparentUtxoSetApi;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use parity_scale_codec::Decode;
-use sp_api::ProvideRuntimeApi;
+use serde_json::Value as JsonValue;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::Bytes;
 use sp_runtime::traits::Block as BlockT;
@@ -16,18 +18,49 @@ use crate::error::error_object_from;
 
 extern crate alloc;
 
+/// Number of UTxOs returned by a query when no limit is given.
+pub const DEFAULT_UTXO_PAGE_SIZE: u32 = 100;
+
+/// Maximum number of UTxOs returned by a single query.
+pub const MAX_UTXO_PAGE_SIZE: u32 = 1000;
+
+/// UTxO queries. Every method takes an optional block hash `at`, defaulting
+/// to the best block.
+///
+/// Queries returning several UTxOs are paginated: at most `limit` pairs are
+/// returned (up to `MAX_UTXO_PAGE_SIZE`), ordered by the SCALE encoding of the
+/// output reference (transaction hash, then little-endian output index), which
+/// is not the numeric order of indices. The next page is obtained by passing
+/// the last reference received as `after`. These queries need version 2 of
+/// `TransparentUtxoSetApi` at block `at`.
+///
+/// Output addresses are given in bech32 with the human-readable part of the
+/// network ID of the chain.
+///
+/// UTxOs with an asset are looked up either by the name and policy ID of the
+/// asset or, when no policy ID is given, by its CIP-14 fingerprint, which
+/// needs version 3 of `TransparentUtxoSetApi`.
 #[rpc(client, server, namespace = "utxorpc")]
-pub trait TransparentUtxoSetRpcApi {
+pub trait TransparentUtxoSetRpcApi<BlockHash> {
     #[method(name = "get_utxo")]
-    fn peek_utxo(&self, input: Bytes) -> RpcResult<Output>;
+    fn peek_utxo(&self, input: Bytes, at: Option<BlockHash>) -> RpcResult<JsonValue>;
     #[method(name = "get_utxo_by_address")]
-    fn peek_utxos_by_address(&self, input: Bytes) -> RpcResult<Vec<Output>>;
+    fn peek_utxos_by_address(
+        &self,
+        address: Address,
+        after: Option<Bytes>,
+        limit: Option<u32>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Input, JsonValue)>>;
     #[method(name = "get_utxo_with_asset")]
     fn peek_utxos_with_asset(
         &self,
         asset_name: String,
-        raw_policy_id: String,
-    ) -> RpcResult<Vec<Output>>;
+        raw_policy_id: Option<String>,
+        after: Option<Bytes>,
+        limit: Option<u32>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Input, JsonValue)>>;
 }
 
 #[derive(new)]
@@ -36,59 +69,167 @@ pub struct TransparentUtxoSetRpc<C, Block> {
     _marker: std::marker::PhantomData<Block>,
 }
 
-impl<C, Block> TransparentUtxoSetRpcApiServer for TransparentUtxoSetRpc<C, Block>
+impl<C, Block> TransparentUtxoSetRpc<C, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    fn block_hash(&self, at: Option<Block::Hash>) -> Block::Hash {
+        at.unwrap_or_else(|| self.client.info().best_hash)
+    }
+}
+
+impl<C, Block> TransparentUtxoSetRpc<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: TransparentUtxoSetApi<Block> + ProtocolParametersApi<Block>,
+{
+    /// Fails with `error` unless the runtime at `block_hash` implements at
+    /// least version `min_version` of `TransparentUtxoSetApi`.
+    fn ensure_api_version(
+        &self,
+        block_hash: Block::Hash,
+        min_version: u32,
+        error: &str,
+    ) -> RpcResult<()> {
+        let version = self
+            .client
+            .runtime_api()
+            .api_version::<dyn TransparentUtxoSetApi<Block>>(block_hash)
+            .map_err(error_object_from)?;
+
+        match version {
+            Some(v) if v >= min_version => Ok(()),
+            _ => Err(error_object_from(error)),
+        }
+    }
+
+    /// Fails unless the runtime at `block_hash` serves paginated queries.
+    fn ensure_paginated_api(&self, block_hash: Block::Hash) -> RpcResult<()> {
+        self.ensure_api_version(
+            block_hash,
+            2,
+            "Runtime does not support paginated UTxO queries",
+        )
+    }
+
+    /// Network ID of the chain at `block_hash`.
+    fn network_id(&self, block_hash: Block::Hash) -> RpcResult<u8> {
+        self.client
+            .runtime_api()
+            .protocol_parameters(block_hash)
+            .map_err(error_object_from)?
+            .map(|pps| pps.network_id)
+            .ok_or_else(|| error_object_from("Protocol parameters not found"))
+    }
+}
+
+/// `output` as JSON, with its address displayed on a chain with network ID
+/// `network_id`.
+fn output_json(output: &Output, network_id: u8) -> RpcResult<JsonValue> {
+    let mut json = serde_json::to_value(output).map_err(error_object_from)?;
+    json["address"] = output.address.to_string_on(network_id).into();
+
+    Ok(json)
+}
+
+fn utxos_json(utxos: Vec<(Input, Output)>, network_id: u8) -> RpcResult<Vec<(Input, JsonValue)>> {
+    utxos
+        .into_iter()
+        .map(|(input, output)| Ok((input, output_json(&output, network_id)?)))
+        .collect()
+}
+
+fn decode_input(input_bytes: &Bytes) -> RpcResult<Input> {
+    Input::decode(&mut &input_bytes[..]).map_err(error_object_from)
+}
+
+fn decode_page(after: Option<Bytes>, limit: Option<u32>) -> RpcResult<(Option<Input>, u32)> {
+    let after = after.as_ref().map(decode_input).transpose()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_UTXO_PAGE_SIZE)
+        .min(MAX_UTXO_PAGE_SIZE);
+
+    Ok((after, limit))
+}
+
+impl<C, Block> TransparentUtxoSetRpcApiServer<Block::Hash> for TransparentUtxoSetRpc<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static,
     C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + 'static,
-    C::Api: griffin_core::utxo_set::TransparentUtxoSetApi<Block>,
+    C::Api: griffin_core::utxo_set::TransparentUtxoSetApi<Block> + ProtocolParametersApi<Block>,
 {
-    fn peek_utxo(&self, input_bytes: Bytes) -> RpcResult<Output> {
+    fn peek_utxo(&self, input_bytes: Bytes, at: Option<Block::Hash>) -> RpcResult<JsonValue> {
         let api = self.client.runtime_api();
-        let best_block = self.client.info().best_hash;
-
-        let input = match Input::decode(&mut &input_bytes[..]) {
-            Ok(input) => input,
-            Err(e) => return Err(error_object_from(e)),
-        };
-        match api.peek_utxo(best_block, &input) {
-            Ok(Some(outxo)) => Ok(outxo),
+        let block_hash = self.block_hash(at);
+
+        let input = decode_input(&input_bytes)?;
+        match api.peek_utxo(block_hash, &input) {
+            Ok(Some(outxo)) => output_json(&outxo, self.network_id(block_hash)?),
             Ok(None) => Err(error_object_from("No UTxO Found")),
             Err(e) => Err(error_object_from(e)),
         }
     }
 
-    fn peek_utxos_by_address(&self, addr_bytes: Bytes) -> RpcResult<Vec<Output>> {
+    fn peek_utxos_by_address(
+        &self,
+        addr: Address,
+        after: Option<Bytes>,
+        limit: Option<u32>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Input, JsonValue)>> {
         let api = self.client.runtime_api();
-        let best_block = self.client.info().best_hash;
+        let block_hash = self.block_hash(at);
+        self.ensure_paginated_api(block_hash)?;
 
-        let addr = Address::from(addr_bytes.to_vec());
-        let utxos: Vec<Output> = api
-            .peek_utxo_by_address(best_block, &addr)
+        let (after, limit) = decode_page(after, limit)?;
+        let utxos: Vec<(Input, Output)> = api
+            .peek_utxo_by_address(block_hash, &addr, after, limit)
             .map_err(error_object_from)?;
 
-        Ok(utxos)
+        utxos_json(utxos, self.network_id(block_hash)?)
     }
 
     fn peek_utxos_with_asset(
         &self,
         asset_name: String,
-        raw_policy_id: String,
-    ) -> RpcResult<Vec<Output>> {
+        raw_policy_id: Option<String>,
+        after: Option<Bytes>,
+        limit: Option<u32>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Input, JsonValue)>> {
         let api = self.client.runtime_api();
-        let best_block = self.client.info().best_hash;
+        let block_hash = self.block_hash(at);
+        self.ensure_paginated_api(block_hash)?;
 
-        let policy_id = match Hash::from_str(&raw_policy_id) {
-            Ok(hash) => hash,
-            Err(e) => return Err(error_object_from(e)),
-        };
+        let (after, limit) = decode_page(after, limit)?;
+        let utxos: Vec<(Input, Output)> = match raw_policy_id {
+            Some(raw_policy_id) => {
+                let policy_id = match Hash::from_str(&raw_policy_id) {
+                    Ok(hash) => hash,
+                    Err(e) => return Err(error_object_from(e)),
+                };
 
-        let name = AssetName::from(asset_name);
-        let policy = PolicyId::from(policy_id);
-        let utxos: Vec<Output> = api
-            .peek_utxo_with_asset(best_block, &name, &policy)
-            .map_err(error_object_from)?;
+                let name = AssetName::from(asset_name);
+                let policy = PolicyId::from(policy_id);
+                api.peek_utxo_with_asset(block_hash, &name, &policy, after, limit)
+            }
+            None => {
+                let fingerprint = decode_asset_fingerprint(&asset_name).ok_or_else(|| {
+                    error_object_from(format!("Invalid asset fingerprint {asset_name}"))
+                })?;
+                self.ensure_api_version(
+                    block_hash,
+                    3,
+                    "Runtime does not support UTxO queries by asset fingerprint",
+                )?;
+                api.peek_utxo_with_fingerprint(block_hash, fingerprint, after, limit)
+            }
+        }
+        .map_err(error_object_from)?;
 
-        Ok(utxos)
+        utxos_json(utxos, self.network_id(block_hash)?)
     }
 }
@@ -0,0 +1,236 @@
+//! Committee selection inputs observed on Cardano.
+//!
+//! The inputs of the selection made by a block are those the data sources
+//! report for the main chain epoch of the main chain block the block
+//! references, so that every node reads the same, stable data. Block authors
+//! provide them as inherent data when a selection is due, to build the
+//! committee inherent of the block, and importing nodes provide them to check
+//! that inherent. Cardano data is read from Db-Sync through the data sources
+//! of the Partner Chains toolkit, which nodes of chains selecting their
+//! committees cannot run without.
+
+use authority_selection_inherents::{
+    AriadneInherentDataProvider, AriadneParameters, AuthoritySelectionDataSource,
+    AuthoritySelectionInputs,
+};
+use griffin_core::{
+    committee::{CommitteeApi, CommitteeSelectionConfig, NextCommittee, COMMITTEE_ENGINE_ID},
+    types::OpaqueBlock as Block,
+};
+use partner_chains_db_sync_data_sources::{
+    get_connection_from_env, BlockDataSourceImpl, CandidatesDataSourceImpl, McHashDataSourceImpl,
+};
+use sc_consensus::{BlockCheckParams, BlockImport, BlockImportParams, ImportResult};
+use sc_telemetry::log;
+use sidechain_domain::{EpochNonce, MainchainBlock};
+use sidechain_mc_hash::{McHashDataSource, McHashInherentDigest};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::Error as ConsensusError;
+use sp_partner_chains_consensus_aura::inherent_digest::InherentDigest;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{error::Error, sync::Arc};
+
+/// Environment variable holding the Db-Sync Postgres connection string.
+pub const DB_SYNC_CONNECTION_VAR: &str = "DB_SYNC_POSTGRES_CONNECTION_STRING";
+
+const LOG_TARGET: &str = "committee";
+
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Sources of the Cardano data observed by the node.
+#[derive(Clone)]
+pub struct DataSources {
+    pub mc_hash: Arc<dyn McHashDataSource + Send + Sync>,
+    pub authority_selection: Arc<dyn AuthoritySelectionDataSource + Send + Sync>,
+}
+
+impl DataSources {
+    /// Connects to Db-Sync as configured by the environment, or returns `None`
+    /// if no connection string is set. All of the sources share the same
+    /// connection pool.
+    pub async fn from_env() -> Result<Option<Self>, BoxError> {
+        if std::env::var(DB_SYNC_CONNECTION_VAR).is_err() {
+            return Ok(None);
+        }
+        let pool = get_connection_from_env().await?;
+        let blocks = Arc::new(BlockDataSourceImpl::new_from_env(pool.clone()).await?);
+        let authority_selection = CandidatesDataSourceImpl::new(pool, None).await?;
+
+        Ok(Some(Self {
+            mc_hash: Arc::new(McHashDataSourceImpl::new(blocks, None)),
+            authority_selection: Arc::new(authority_selection),
+        }))
+    }
+}
+
+/// Inputs for the selection of a committee made by a block referencing
+/// `mc_block`.
+pub async fn selection_inputs(
+    data_source: &(dyn AuthoritySelectionDataSource + Send + Sync),
+    config: &CommitteeSelectionConfig,
+    mc_block: &MainchainBlock,
+) -> Result<AuthoritySelectionInputs, BoxError> {
+    let epoch = mc_block.epoch;
+    let AriadneParameters {
+        d_parameter,
+        permissioned_candidates,
+    } = data_source
+        .get_ariadne_parameters(
+            epoch,
+            config.d_parameter_policy_id.clone(),
+            config.permissioned_candidates_policy_id.clone(),
+        )
+        .await?;
+    let permissioned_candidates = match permissioned_candidates {
+        Some(candidates) => candidates,
+        None if d_parameter.num_permissioned_candidates == 0 => Vec::new(),
+        None => return Err("Permissioned candidates not found".into()),
+    };
+    let registered_candidates = data_source
+        .get_candidates(epoch, config.committee_candidate_address.clone())
+        .await?;
+    let epoch_nonce = data_source
+        .get_epoch_nonce(epoch)
+        .await?
+        .unwrap_or(EpochNonce(vec![]));
+
+    Ok(AuthoritySelectionInputs {
+        d_parameter,
+        permissioned_candidates,
+        registered_candidates,
+        epoch_nonce,
+    })
+}
+
+/// Creates the provider of committee selection inputs for a block proposed or
+/// imported on top of `parent_hash` and referencing `mc_block`. It provides no
+/// data unless a selection is due.
+pub async fn ariadne_inherent_data_provider<C>(
+    client: &C,
+    data_source: &(dyn AuthoritySelectionDataSource + Send + Sync),
+    parent_hash: <Block as BlockT>::Hash,
+    mc_block: &MainchainBlock,
+) -> Result<AriadneInherentDataProvider, BoxError>
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: CommitteeApi<Block>,
+{
+    let api = client.runtime_api();
+    let data = match (
+        api.committee_selection(parent_hash)?,
+        api.next_unset_epoch(parent_hash)?,
+    ) {
+        (Some(config), Some(_)) => Some(selection_inputs(data_source, &config, mc_block).await?),
+        _ => None,
+    };
+
+    Ok(AriadneInherentDataProvider { data })
+}
+
+/// Block import checking the committees selected by blocks before handing them
+/// to the wrapped import.
+pub struct CommitteeBlockImport<I, C> {
+    inner: I,
+    client: Arc<C>,
+    data_sources: Option<DataSources>,
+}
+
+impl<I: Clone, C> Clone for CommitteeBlockImport<I, C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            client: self.client.clone(),
+            data_sources: self.data_sources.clone(),
+        }
+    }
+}
+
+impl<I, C> CommitteeBlockImport<I, C> {
+    pub fn new(inner: I, client: Arc<C>, data_sources: Option<DataSources>) -> Self {
+        Self {
+            inner,
+            client,
+            data_sources,
+        }
+    }
+}
+
+impl<I, C> CommitteeBlockImport<I, C>
+where
+    C: HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+    C::Api: CommitteeApi<Block>,
+{
+    async fn verify(
+        &self,
+        data_sources: &DataSources,
+        header: &<Block as BlockT>::Header,
+        next: NextCommittee,
+    ) -> Result<(), String> {
+        let parent_hash = *header.parent_hash();
+        let api = self.client.runtime_api();
+        let config = api
+            .committee_selection(parent_hash)
+            .map_err(|e| e.to_string())?
+            .ok_or("Committee selection is not configured")?;
+        let mc_hash = McHashInherentDigest::value_from_digest(&header.digest().logs)
+            .map_err(|e| e.to_string())?;
+        let mc_block = data_sources
+            .mc_hash
+            .get_block_by_hash(mc_hash.clone())
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Main chain block {mc_hash} not found"))?;
+
+        let inputs = selection_inputs(&*data_sources.authority_selection, &config, &mc_block)
+            .await
+            .map_err(|e| e.to_string())?;
+        let expected = api
+            .calculate_committee(parent_hash, inputs, next.epoch)
+            .map_err(|e| e.to_string())?;
+        if expected.as_ref() != Some(&next.members) {
+            return Err(format!(
+                "Committee of epoch {} does not match the selection",
+                next.epoch
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<I, C> BlockImport<Block> for CommitteeBlockImport<I, C>
+where
+    I: BlockImport<Block, Error = ConsensusError> + Send + Sync,
+    C: HeaderBackend<Block> + ProvideRuntimeApi<Block> + Send + Sync,
+    C::Api: CommitteeApi<Block>,
+{
+    type Error = ConsensusError;
+
+    async fn check_block(
+        &self,
+        block: BlockCheckParams<Block>,
+    ) -> Result<ImportResult, Self::Error> {
+        self.inner.check_block(block).await
+    }
+
+    async fn import_block(
+        &self,
+        block: BlockImportParams<Block>,
+    ) -> Result<ImportResult, Self::Error> {
+        let next = block
+            .header
+            .digest()
+            .convert_first(|log| log.consensus_try_to::<NextCommittee>(&COMMITTEE_ENGINE_ID));
+        if let (Some(data_sources), Some(next)) = (&self.data_sources, next) {
+            self.verify(data_sources, &block.header, next)
+                .await
+                .map_err(|e| {
+                    log::warn!(target: LOG_TARGET, "Rejected block: {e}");
+                    ConsensusError::ClientImport(e)
+                })?;
+        }
+        self.inner.import_block(block).await
+    }
+}
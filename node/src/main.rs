@@ -4,6 +4,7 @@
 mod chain_spec;
 mod cli;
 mod command;
+mod committee;
 mod rpc;
 mod service;
 
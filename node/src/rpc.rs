@@ -6,6 +6,10 @@
 #![warn(missing_docs)]
 
 use griffin_rpc::cardano_rpc::{CardanoRpc, CardanoRpcApiServer};
+use griffin_rpc::epoch::{EpochRpc, EpochRpcApiServer};
+use griffin_rpc::evaluation::{EvaluationRpc, EvaluationRpcApiServer};
+use griffin_rpc::protocol_parameters::{ProtocolParametersRpc, ProtocolParametersRpcApiServer};
+use griffin_rpc::reward_accounts::{RewardAccountsRpc, RewardAccountsRpcApiServer};
 use griffin_rpc::rpc::{TransparentUtxoSetRpc, TransparentUtxoSetRpcApiServer};
 use jsonrpsee::RpcModule;
 use sc_transaction_pool_api::TransactionPool;
@@ -41,6 +45,16 @@ where
     C::Api: griffin_core::utxo_set::TransparentUtxoSetApi<
         <P as sc_transaction_pool_api::TransactionPool>::Block,
     >,
+    C::Api: griffin_core::protocol_parameters::ProtocolParametersApi<
+        <P as sc_transaction_pool_api::TransactionPool>::Block,
+    >,
+    C::Api: griffin_core::epoch::EpochApi<<P as sc_transaction_pool_api::TransactionPool>::Block>,
+    C::Api: griffin_core::evaluation::TransactionEvaluationApi<
+        <P as sc_transaction_pool_api::TransactionPool>::Block,
+    >,
+    C::Api: griffin_core::reward_accounts::RewardAccountsApi<
+        <P as sc_transaction_pool_api::TransactionPool>::Block,
+    >,
     P: TransactionPool + 'static,
 {
     let mut module = RpcModule::new(());
@@ -48,6 +62,10 @@ where
 
     module.merge(CardanoRpc::new(client.clone(), pool.clone()).into_rpc())?;
     module.merge(TransparentUtxoSetRpc::new(client.clone()).into_rpc())?;
+    module.merge(ProtocolParametersRpc::new(client.clone()).into_rpc())?;
+    module.merge(EvaluationRpc::new(client.clone()).into_rpc())?;
+    module.merge(EpochRpc::new(client.clone()).into_rpc())?;
+    module.merge(RewardAccountsRpc::new(client.clone()).into_rpc())?;
 
     Ok(module)
 }
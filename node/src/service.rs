@@ -1,6 +1,10 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use griffin_core::{genesis::GriffinGenesisBlockBuilder, types::OpaqueBlock as Block};
+use crate::committee::{self, CommitteeBlockImport, DataSources};
+use futures::FutureExt;
+use griffin_core::{
+    committee::CommitteeApi, genesis::GriffinGenesisBlockBuilder, types::OpaqueBlock as Block,
+};
 use griffin_partner_chains_runtime::{self, RuntimeApi};
 use sc_client_api::client::BlockBackend;
 use sc_consensus_aura::ImportQueueParams;
@@ -9,8 +13,16 @@ use sc_network::peer_store::LOG_TARGET;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{log, Telemetry, TelemetryWorker};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
-use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use sp_runtime::traits::Block as BlockT;
+use sidechain_domain::McBlockHash;
+use sidechain_mc_hash::{McHashInherentDataProvider, McHashInherentDigest};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus_aura::{
+    sr25519::{AuthorityPair as AuraPair, AuthoritySignature},
+    Slot,
+};
+use sp_partner_chains_consensus_aura::block_proposal::PartnerChainsProposerFactory;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 use std::{
     sync::Arc,
     thread::sleep,
@@ -30,6 +42,10 @@ pub(crate) type FullClient =
 
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullBlockImport = CommitteeBlockImport<
+    sc_consensus_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+    FullClient,
+>;
 
 /// Assembly of PartialComponents (enough to run chain ops subcommands)
 pub type Service = sc_service::PartialComponents<
@@ -39,9 +55,10 @@ pub type Service = sc_service::PartialComponents<
     sc_consensus::DefaultImportQueue<Block>,
     sc_transaction_pool::TransactionPoolHandle<Block, FullClient>,
     (
-        sc_consensus_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+        FullBlockImport,
         sc_consensus_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
         Option<Telemetry>,
+        Option<DataSources>,
     ),
 >;
 
@@ -106,36 +123,133 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
         telemetry.as_ref().map(|x| x.handle()),
     )?;
 
+    let data_sources =
+        tokio::task::block_in_place(|| config.tokio_handle.block_on(DataSources::from_env()))
+            .map_err(|e| ServiceError::Other(format!("Failed to set up Db-Sync: {e}")))?;
+    let block_import = CommitteeBlockImport::new(
+        grandpa_block_import.clone(),
+        client.clone(),
+        data_sources.clone(),
+    );
+
     let cidp_client = client.clone();
-    let import_queue =
-        sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
-            block_import: grandpa_block_import.clone(),
-            justification_import: Some(Box::new(grandpa_block_import.clone())),
-            client: client.clone(),
-            create_inherent_data_providers: move |parent_hash, _| {
-                let cidp_client = cidp_client.clone();
-                async move {
-                    let slot_duration = sc_consensus_aura::standalone::slot_duration_at(
-                        &*cidp_client,
-                        parent_hash,
-                    )?;
-                    let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-                    let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-							*timestamp,
-							slot_duration,
-						);
-
-                    Ok((slot, timestamp))
-                }
-            },
-            spawner: &task_manager.spawn_essential_handle(),
-            registry: config.prometheus_registry(),
-            check_for_equivocation: Default::default(),
-            telemetry: telemetry.as_ref().map(|x| x.handle()),
-            compatibility_mode: Default::default(),
-        })?;
+    let import_queue = match &data_sources {
+        // Blocks reference the main chain in their header, which is checked
+        // against Db-Sync when creating the inherent data providers.
+        Some(sources) => {
+            let mc_hash_source = sources.mc_hash.clone();
+            let authority_selection_source = sources.authority_selection.clone();
+            sc_partner_chains_consensus_aura::import_queue::<
+                AuraPair,
+                _,
+                _,
+                _,
+                _,
+                _,
+                McHashInherentDigest,
+            >(ImportQueueParams {
+                block_import: block_import.clone(),
+                justification_import: Some(Box::new(grandpa_block_import.clone())),
+                client: client.clone(),
+                create_inherent_data_providers: move |parent_hash,
+                                                      (verified_block_slot, mc_hash): (
+                    Slot,
+                    McBlockHash,
+                )| {
+                    let cidp_client = cidp_client.clone();
+                    let mc_hash_source = mc_hash_source.clone();
+                    let authority_selection_source = authority_selection_source.clone();
+                    async move {
+                        let slot_duration = sc_consensus_aura::standalone::slot_duration_at(
+                            &*cidp_client,
+                            parent_hash,
+                        )?;
+                        let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+                        let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+								*timestamp,
+								slot_duration,
+							);
+
+                        let parent_header = cidp_client
+                            .header(parent_hash)?
+                            .ok_or_else(|| format!("Parent block {parent_hash} not found"))?;
+                        // The genesis block has no slot and references no main chain block.
+                        let parent_slot = match *parent_header.number() {
+                            0 => None,
+                            _ => Some(
+                                sc_consensus_aura::find_pre_digest::<Block, AuthoritySignature>(
+                                    &parent_header,
+                                )
+                                .map_err(|e| format!("{e:?}"))?,
+                            ),
+                        };
+                        let mc_hash = McHashInherentDataProvider::new_verification(
+                            parent_header,
+                            parent_slot,
+                            verified_block_slot,
+                            mc_hash,
+                            slot_duration,
+                            &*mc_hash_source,
+                        )
+                        .await?;
+                        // The committee selected by the block, if any, is
+                        // checked against these inputs.
+                        let ariadne = committee::ariadne_inherent_data_provider(
+                            &*cidp_client,
+                            &*authority_selection_source,
+                            parent_hash,
+                            &mc_hash,
+                        )
+                        .await?;
+
+                        Ok((slot, timestamp, mc_hash, ariadne))
+                    }
+                },
+                spawner: &task_manager.spawn_essential_handle(),
+                registry: config.prometheus_registry(),
+                check_for_equivocation: Default::default(),
+                telemetry: telemetry.as_ref().map(|x| x.handle()),
+                compatibility_mode: Default::default(),
+            })?
+        }
+        None => {
+            log::warn!(
+                target: LOG_TARGET,
+                "{} is not set: blocks will neither reference the main chain nor select committees",
+                committee::DB_SYNC_CONNECTION_VAR
+            );
+            sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
+                block_import: block_import.clone(),
+                justification_import: Some(Box::new(grandpa_block_import.clone())),
+                client: client.clone(),
+                create_inherent_data_providers: move |parent_hash, _| {
+                    let cidp_client = cidp_client.clone();
+                    async move {
+                        let slot_duration = sc_consensus_aura::standalone::slot_duration_at(
+                            &*cidp_client,
+                            parent_hash,
+                        )?;
+                        let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+                        let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+								*timestamp,
+								slot_duration,
+							);
+
+                        Ok((slot, timestamp))
+                    }
+                },
+                spawner: &task_manager.spawn_essential_handle(),
+                registry: config.prometheus_registry(),
+                check_for_equivocation: Default::default(),
+                telemetry: telemetry.as_ref().map(|x| x.handle()),
+                compatibility_mode: Default::default(),
+            })?
+        }
+    };
 
     Ok(sc_service::PartialComponents {
         client,
@@ -145,7 +259,7 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
         keystore_container,
         select_chain,
         transaction_pool,
-        other: (grandpa_block_import, grandpa_link, telemetry),
+        other: (block_import, grandpa_link, telemetry, data_sources),
     })
 }
 
@@ -161,9 +275,26 @@ pub fn new_full<Network: sc_network::NetworkBackend<Block, <Block as BlockT>::Ha
         keystore_container,
         select_chain,
         transaction_pool,
-        other: (block_import, grandpa_link, mut telemetry),
+        other: (block_import, grandpa_link, mut telemetry, data_sources),
     } = new_partial(&config)?;
 
+    // Without Db-Sync, the committees selected by imported blocks could not be
+    // checked against the Cardano registrations.
+    if data_sources.is_none()
+        && client
+            .runtime_api()
+            .committee_selection(client.info().best_hash)
+            .map_err(|e| {
+                ServiceError::Other(format!("Failed to read the committee selection: {e}"))
+            })?
+            .is_some()
+    {
+        return Err(ServiceError::Other(format!(
+            "The chain selects its committees from Cardano: {} must be set",
+            committee::DB_SYNC_CONNECTION_VAR
+        )));
+    }
+
     let mut net_config = sc_network::config::FullNetworkConfiguration::<
         Block,
         <Block as BlockT>::Hash,
@@ -282,36 +413,105 @@ pub fn new_full<Network: sc_network::NetworkBackend<Block, <Block as BlockT>::Ha
             .unwrap();
 
         let slot_duration = sc_consensus_aura::SlotDuration::from_millis(sc);
+        let cidp_client = client.clone();
 
-        let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(
-            sc_consensus_aura::StartAuraParams {
-                slot_duration,
-                client: client.clone(),
-                select_chain,
-                block_import,
-                proposer_factory,
-                create_inherent_data_providers: move |_, ()| async move {
-                    let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-                    let slot =
-                    sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-                        *timestamp,
-                        slot_duration,
-                    );
+        let aura = match data_sources {
+            // Blocks reference the main chain in their header, put there by
+            // the proposer from the inherent data.
+            Some(sources) => {
+                let proposer_factory: PartnerChainsProposerFactory<_, _, McHashInherentDigest> =
+                    PartnerChainsProposerFactory::new(proposer_factory);
 
-                    Ok((slot, timestamp))
+                sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(
+                    sc_consensus_aura::StartAuraParams {
+                        slot_duration,
+                        client: client.clone(),
+                        select_chain,
+                        block_import,
+                        proposer_factory,
+                        create_inherent_data_providers: move |parent_hash, ()| {
+                            let cidp_client = cidp_client.clone();
+                            let sources = sources.clone();
+                            async move {
+                                let timestamp =
+                                    sp_timestamp::InherentDataProvider::from_system_time();
+
+                                let slot =
+                                sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+                                    *timestamp,
+                                    slot_duration,
+                                );
+
+                                let parent_header =
+                                    cidp_client.header(parent_hash)?.ok_or_else(|| {
+                                        format!("Parent block {parent_hash} not found")
+                                    })?;
+                                let mc_hash = McHashInherentDataProvider::new_proposal(
+                                    parent_header,
+                                    &*sources.mc_hash,
+                                    *slot,
+                                    slot_duration,
+                                )
+                                .await?;
+                                let ariadne = committee::ariadne_inherent_data_provider(
+                                    &*cidp_client,
+                                    &*sources.authority_selection,
+                                    parent_hash,
+                                    &mc_hash,
+                                )
+                                .await?;
+
+                                Ok((slot, timestamp, mc_hash, ariadne))
+                            }
+                        },
+                        force_authoring,
+                        backoff_authoring_blocks,
+                        keystore: keystore_container.keystore(),
+                        sync_oracle: sync_service.clone(),
+                        justification_sync_link: sync_service.clone(),
+                        block_proposal_slot_portion: sc_consensus_aura::SlotProportion::new(
+                            2f32 / 3f32,
+                        ),
+                        max_block_proposal_slot_portion: None,
+                        telemetry: telemetry.as_ref().map(|x| x.handle()),
+                        compatibility_mode: Default::default(),
+                    },
+                )?
+                .boxed()
+            }
+            None => sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(
+                sc_consensus_aura::StartAuraParams {
+                    slot_duration,
+                    client: client.clone(),
+                    select_chain,
+                    block_import,
+                    proposer_factory,
+                    create_inherent_data_providers: move |_, ()| async move {
+                        let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+                        let slot =
+                        sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+                            *timestamp,
+                            slot_duration,
+                        );
+
+                        Ok((slot, timestamp))
+                    },
+                    force_authoring,
+                    backoff_authoring_blocks,
+                    keystore: keystore_container.keystore(),
+                    sync_oracle: sync_service.clone(),
+                    justification_sync_link: sync_service.clone(),
+                    block_proposal_slot_portion: sc_consensus_aura::SlotProportion::new(
+                        2f32 / 3f32,
+                    ),
+                    max_block_proposal_slot_portion: None,
+                    telemetry: telemetry.as_ref().map(|x| x.handle()),
+                    compatibility_mode: Default::default(),
                 },
-                force_authoring,
-                backoff_authoring_blocks,
-                keystore: keystore_container.keystore(),
-                sync_oracle: sync_service.clone(),
-                justification_sync_link: sync_service.clone(),
-                block_proposal_slot_portion: sc_consensus_aura::SlotProportion::new(2f32 / 3f32),
-                max_block_proposal_slot_portion: None,
-                telemetry: telemetry.as_ref().map(|x| x.handle()),
-                compatibility_mode: Default::default(),
-            },
-        )?;
+            )?
+            .boxed(),
+        };
 
         // the AURA authoring task is considered essential, i.e. if it
         // fails we take down the service with it.
@@ -0,0 +1,100 @@
+//! Selection of committees from Cardano registrations.
+
+use crate::{opaque::SessionKeys, Block, CrossChainPublic, Executive, Transaction};
+use alloc::vec::Vec;
+use authority_selection_inherents::{
+    select_authorities, AuthoritySelectionInputs, CommitteeMember as SelectedMember,
+};
+use griffin_core::committee::{CommitteeMember, NextCommittee};
+use parity_scale_codec::{Decode, Encode};
+use sidechain_domain::ScEpochNumber;
+use sp_core::ConstU32;
+use sp_inherents::{CheckInherentsResult, InherentData, IsFatalError};
+use sp_session_validator_management::INHERENT_IDENTIFIER;
+
+/// Maximum number of seats of a committee.
+type MaxCommitteeSize = ConstU32<1024>;
+
+/// The committee selected for `epoch` from `inputs` by the Ariadne algorithm,
+/// or `None` if no candidate is valid.
+pub fn calculate_committee(
+    inputs: AuthoritySelectionInputs,
+    epoch: u64,
+) -> Option<Vec<CommitteeMember>> {
+    let selection = Executive::committee_selection()?;
+    let selected = select_authorities::<CrossChainPublic, SessionKeys, MaxCommitteeSize>(
+        selection.genesis_utxo,
+        inputs,
+        ScEpochNumber(epoch),
+    )?;
+
+    let members = selected
+        .into_iter()
+        .map(|member| match member {
+            SelectedMember::Permissioned { keys, .. } | SelectedMember::Registered { keys, .. } => {
+                CommitteeMember {
+                    aura: keys.aura,
+                    grandpa: keys.grandpa,
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    (!members.is_empty()).then_some(members)
+}
+
+/// Reasons for an importing node to reject the committee inherent of a block.
+#[derive(Encode, Decode, Debug)]
+pub enum InherentError {
+    /// The committee selected for the given epoch is not the one selected from
+    /// the inputs provided as inherent data, or no inputs were provided.
+    CommitteeMismatch(u64),
+}
+
+impl IsFatalError for InherentError {
+    fn is_fatal_error(&self) -> bool {
+        true
+    }
+}
+
+/// The inherent selecting the committee of the next epoch from the inputs
+/// provided as inherent data, if a selection is due.
+pub fn committee_inherent(data: &InherentData) -> Option<Transaction> {
+    let epoch = Executive::next_unset_epoch()?;
+    let inputs = data
+        .get_data::<AuthoritySelectionInputs>(&INHERENT_IDENTIFIER)
+        .ok()??;
+    let members = calculate_committee(inputs, epoch)?;
+
+    Some(NextCommittee { epoch, members }.to_inherent())
+}
+
+/// Checks that the committee selected by `block`, if any, is the one selected
+/// from the inputs provided as inherent data. Whether that committee is due is
+/// checked when executing the block.
+pub fn check_committee_inherent(block: &Block, data: &InherentData) -> CheckInherentsResult {
+    let mut result = CheckInherentsResult::new();
+    let Some(next) = block
+        .extrinsics
+        .iter()
+        .find_map(NextCommittee::from_inherent)
+    else {
+        return result;
+    };
+
+    let expected = data
+        .get_data::<AuthoritySelectionInputs>(&INHERENT_IDENTIFIER)
+        .ok()
+        .flatten()
+        .and_then(|inputs| calculate_committee(inputs, next.epoch));
+    if expected.as_ref() != Some(&next.members) {
+        result
+            .put_error(
+                INHERENT_IDENTIFIER,
+                &InherentError::CommitteeMismatch(next.epoch),
+            )
+            .expect("The committee inherent is checked only once; qed");
+    }
+
+    result
+}
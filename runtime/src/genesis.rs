@@ -8,11 +8,27 @@ use serde_json;
 
 /// The default genesis. It can be replaced by a custom one by providing the
 /// node with an analogous JSON file through the `--chain` flag
+///
+/// Its fees are zero, as the game transactions pay none, so transactions whose
+/// scripts fail forfeit no collateral either. A chain open to untrusted users
+/// should set `min_fee_a` and `min_fee_b`.
 pub const GENESIS_DEFAULT_JSON: &str = r#"
 {
     "zero_time": 1747081100000,
     "zero_slot": 0,
     "slot_length": 3000,
+    "slots_per_epoch": 600,
+    "network_id": 1,
+    "min_fee_a": 0,
+    "min_fee_b": 0,
+    "max_tx_size": 32768,
+    "max_value_size": 5000,
+    "min_coin_per_output": 10,
+    "coins_per_utxo_word": 0,
+    "collateral_percentage": 150,
+    "max_collateral_inputs": 3,
+    "max_tx_ex_units": { "mem": 14000000, "steps": 10000000000 },
+    "max_block_ex_units": { "mem": 62000000, "steps": 20000000000 },
     "outputs": [
         {
             "address": "6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6544b47329c4",
@@ -8,11 +8,20 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 extern crate alloc;
 
+mod committee;
 pub mod genesis;
 
 use alloc::{string::ToString, vec, vec::Vec};
+use authority_selection_inherents::AuthoritySelectionInputs;
+use griffin_core::committee::{
+    Committee, CommitteeMember, CommitteeSelectionConfig, NextCommittee,
+};
+use griffin_core::epoch::EpochInfo;
+use griffin_core::evaluation::TxEvaluation;
 use griffin_core::genesis::config_builder::GenesisConfig;
-use griffin_core::types::{Address, AssetName, Input, PolicyId};
+use griffin_core::protocol_parameters::{AppliedUpdate, PendingUpdate, ProtocolParameters};
+use griffin_core::reward_accounts::RewardAccounts;
+use griffin_core::types::{Address, AssetName, Coin, Input, PolicyId, RewardAccount};
 use griffin_core::utxo_set::TransparentUtxoSet;
 pub use opaque::SessionKeys;
 
@@ -21,7 +30,7 @@ use polkadot_sdk_frame::runtime::apis;
 use scale_info::TypeInfo;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{crypto::KeyTypeId, OpaqueMetadata, H256};
 use sp_inherents::InherentData;
 use sp_runtime::{
     impl_opaque_keys,
@@ -169,12 +178,97 @@ impl_runtime_apis! {
             TransparentUtxoSet::peek_utxo(input)
         }
 
-        fn peek_utxo_by_address(addr: &Address) -> Vec<Output> {
-            TransparentUtxoSet::peek_utxos_from_address(addr)
+        fn peek_utxo_by_address(
+            addr: &Address,
+            after: Option<Input>,
+            limit: u32,
+        ) -> Vec<(Input, Output)> {
+            TransparentUtxoSet::peek_utxos_from_address(addr, after.as_ref(), limit)
+        }
+
+        fn peek_utxo_with_asset(
+            asset_name: &AssetName,
+            asset_policy: &PolicyId,
+            after: Option<Input>,
+            limit: u32,
+        ) -> Vec<(Input, Output)> {
+            TransparentUtxoSet::peek_utxos_with_asset(
+                asset_name,
+                asset_policy,
+                after.as_ref(),
+                limit,
+            )
+        }
+
+        fn peek_utxo_with_fingerprint(
+            fingerprint: [u8; 20],
+            after: Option<Input>,
+            limit: u32,
+        ) -> Vec<(Input, Output)> {
+            TransparentUtxoSet::peek_utxos_with_fingerprint(&fingerprint, after.as_ref(), limit)
+        }
+    }
+
+    impl griffin_core::protocol_parameters::ProtocolParametersApi<Block> for Runtime {
+        fn protocol_parameters() -> Option<ProtocolParameters> {
+            Executive::protocol_parameters()
+        }
+
+        fn last_parameters_update() -> Option<AppliedUpdate> {
+            Executive::last_parameters_update()
         }
 
-        fn peek_utxo_with_asset(asset_name: &AssetName, asset_policy: &PolicyId) -> Vec<Output> {
-            TransparentUtxoSet::peek_utxos_with_asset(asset_name, asset_policy)
+        fn pending_parameters_update() -> Option<PendingUpdate> {
+            Executive::pending_parameters_update()
+        }
+    }
+
+    impl griffin_core::reward_accounts::RewardAccountsApi<Block> for Runtime {
+        fn reward_balance(account: &RewardAccount) -> Coin {
+            RewardAccounts::balance(account)
+        }
+    }
+
+    impl griffin_core::epoch::EpochApi<Block> for Runtime {
+        fn current_epoch() -> EpochInfo {
+            Executive::epoch_info()
+        }
+    }
+
+    impl griffin_core::main_chain::MainChainApi<Block> for Runtime {
+        fn main_chain_hash() -> Option<H256> {
+            Executive::main_chain_hash()
+        }
+    }
+
+    impl griffin_core::committee::CommitteeApi<Block> for Runtime {
+        fn current_committee() -> Option<Committee> {
+            Executive::current_committee()
+        }
+
+        fn next_committee() -> Option<NextCommittee> {
+            Executive::next_committee()
+        }
+
+        fn committee_selection() -> Option<CommitteeSelectionConfig> {
+            Executive::committee_selection()
+        }
+
+        fn next_unset_epoch() -> Option<u64> {
+            Executive::next_unset_epoch()
+        }
+
+        fn calculate_committee(
+            inputs: AuthoritySelectionInputs,
+            epoch: u64,
+        ) -> Option<Vec<CommitteeMember>> {
+            committee::calculate_committee(inputs, epoch)
+        }
+    }
+
+    impl griffin_core::evaluation::TransactionEvaluationApi<Block> for Runtime {
+        fn evaluate_transaction(transaction: Transaction) -> TxEvaluation {
+            Executive::evaluate_transaction(&transaction)
         }
     }
 
@@ -216,15 +310,15 @@ impl_runtime_apis! {
             Executive::close_block()
         }
 
-        fn inherent_extrinsics(_data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
-            Vec::new()
+        fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
+            committee::committee_inherent(&data).into_iter().collect()
         }
 
         fn check_inherents(
-            _block: Block,
-            _data: InherentData
+            block: Block,
+            data: InherentData
         ) -> sp_inherents::CheckInherentsResult {
-            sp_inherents::CheckInherentsResult::new()
+            committee::check_committee_inherent(&block, &data)
         }
     }
 
@@ -256,17 +350,27 @@ impl_runtime_apis! {
         }
 
         fn authorities() -> Vec<AuraId> {
-            Authorities::aura_authorities()
+            match Executive::current_committee() {
+                Some(committee) => committee.members.into_iter().map(|member| member.aura).collect(),
+                None => Authorities::aura_authorities(),
+            }
         }
     }
 
     impl apis::GrandpaApi<Block> for Runtime {
         fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
-            Authorities::grandpa_authorities()
+            match Executive::current_committee() {
+                Some(committee) => committee
+                    .members
+                    .into_iter()
+                    .map(|member| (member.grandpa, 1))
+                    .collect(),
+                None => Authorities::grandpa_authorities(),
+            }
         }
 
         fn current_set_id() -> sp_consensus_grandpa::SetId {
-            0u64
+            Executive::current_committee().map_or(0, |committee| committee.set_id)
         }
 
         fn submit_report_equivocation_unsigned_extrinsic(
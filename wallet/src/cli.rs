@@ -5,6 +5,7 @@ extern crate alloc;
 use std::path::PathBuf;
 
 use crate::{
+    coin_selection::SelectionStrategy,
     command,
     context::{Context, DEFAULT_ENDPOINT},
     keystore,
@@ -14,13 +15,9 @@ use crate::{
 };
 use alloc::string::String;
 use clap::{ArgAction::Append, Args, Parser, Subcommand};
-use griffin_core::{
-    pallas_crypto::hash::Hasher as PallasHasher,
-    types::{Address, Coin, Input, PolicyId, Value},
-};
-use hex::FromHex;
+use griffin_core::types::{address_from_pk, Address, Coin, Input, PolicyId, Value};
 use parity_scale_codec::Encode;
-use sp_core::H256;
+use sp_core::{ed25519::Public, H256};
 
 /// The wallet's main CLI struct
 #[derive(Debug, Parser)]
@@ -39,6 +36,12 @@ pub struct Cli<T: clap::Subcommand + clap::FromArgMatches> {
     /// The wallet will use the latest data it had previously synced.
     pub no_sync: bool,
 
+    #[arg(long, conflicts_with = "purge_db")]
+    /// Do not connect to the node at all, as on an air-gapped machine. Implies --no-sync.
+    /// Only commands that work on files and the keystore, such as `witness-tx`, `co-sign`
+    /// and `inspect-tx`, are meaningful in this mode.
+    pub offline: bool,
+
     #[arg(long)]
     /// A temporary directory will be created to store the configuration and will be deleted at the end of the process.
     /// path will be ignored if this is set.
@@ -73,7 +76,8 @@ impl WalletCommand {
             keystore,
             data_path,
             keystore_path,
-            slot_config: _,
+            slot_config,
+            network_id,
         } = Context::<WalletCommand>::load_context().await.unwrap();
         // Dispatch to proper subcommand
         match cli.command {
@@ -88,7 +92,10 @@ impl WalletCommand {
                     // Print the details from the local db
                     match sync::get_unspent(&db, &input)? {
                         Some((owner, amount, _)) => {
-                            println!("Found in local db. Value: {amount:?}, owned by {owner}");
+                            println!(
+                                "Found in local db. Value: {amount:?}, owned by {}",
+                                owner.to_string_on(network_id)
+                            );
                         }
                         None => {
                             println!("Not found in local db");
@@ -107,15 +114,34 @@ impl WalletCommand {
                 }
                 Command::ShowKeys => {
                     keystore::get_keys(&keystore)?.for_each(|pubkey| {
-                        let pk_str: &str = &hex::encode(pubkey);
-                        let hash: String =
-                            PallasHasher::<224>::hash(&<[u8; 32]>::from_hex(pk_str).unwrap())
-                                .to_string();
-                        println!("key: 0x{}; addr: 0x61{}", pk_str, hash);
+                        println!(
+                            "key: 0x{}; addr: {}",
+                            hex::encode(&pubkey),
+                            address_from_pk(&Public::try_from(&pubkey[..]).unwrap())
+                                .to_string_on(network_id)
+                        );
                     });
+                    if let Some(stake_key) = keystore.stake_key() {
+                        println!(
+                            "stake key (m/1852'/1815'/0'/2/0): 0x{}",
+                            hex::encode(stake_key)
+                        );
+                    }
+                    for key in keystore.derived_keys() {
+                        println!(
+                            "key ({}): 0x{}; addr: {}",
+                            key.path(),
+                            hex::encode(key.public),
+                            key.address.to_string_on(network_id)
+                        );
+                    }
 
                     Ok(())
                 }
+                Command::InsertMnemonic { phrase } => {
+                    keystore::insert_mnemonic(&keystore_path, &phrase)
+                }
+                Command::GenerateMnemonic => keystore::generate_mnemonic(&keystore_path),
                 Command::RemoveKey { pub_key } => {
                     println!(
                             "CAUTION!!! About permanently remove {pub_key}. This action CANNOT BE REVERSED. Type \"proceed\" to confirm deletion."
@@ -137,9 +163,18 @@ impl WalletCommand {
                     println!("Balance Summary");
                     let mut total = Value::Coin(0);
                     let balances = sync::get_balances(&db)?;
+                    let tracked = sync::get_tracked(&db)?;
                     for (account, balance) in balances {
                         total += balance.clone();
-                        println!("{account}: {balance}");
+                        let watched = tracked
+                            .iter()
+                            .any(|(address, key)| *address == account && key.is_none());
+                        let account = account.to_string_on(network_id);
+                        if watched {
+                            println!("{account} (watched): {balance}");
+                        } else {
+                            println!("{account}: {balance}");
+                        }
                     }
                     println!("{:-<58}", "");
                     println!("Total:   {}", total.normalize());
@@ -148,25 +183,32 @@ impl WalletCommand {
                 }
                 Command::ShowAllOutputs => {
                     println!("###### Unspent outputs ###########");
-                    sync::show_outputs(sync::print_unspent_tree(&db)?);
+                    sync::show_outputs(sync::print_unspent_tree(&db)?, network_id);
                     println!("To see all details of a particular UTxO, invoke the `verify-utxo` command.");
                     Ok(())
                 }
                 Command::ShowOutputsAt(args) => {
                     println!(
                         "###### Unspent outputs at address {} ###########",
-                        args.address
+                        args.address.to_string_on(network_id)
                     );
-                    sync::show_outputs(sync::get_outputs_at(&db, args)?);
+                    sync::show_outputs(sync::get_outputs_at(&db, args)?, network_id);
                     println!("To see all details of a particular UTxO, invoke the `verify-utxo` command.");
                     Ok(())
                 }
                 Command::ShowOutputsWithAsset(args) => {
-                    println!(
+                    match (&args.fingerprint, &args.policy, &args.name) {
+                        (Some(fingerprint), _, _) => println!(
+                            "###### Unspent outputs containing asset with fingerprint {} ###########",
+                            fingerprint
+                        ),
+                        (None, Some(policy), Some(name)) => println!(
                             "###### Unspent outputs containing asset with name {} and policy ID {} ###########",
-                            args.name, args.policy
-                        );
-                    sync::show_outputs(sync::get_outputs_with_asset(&db, args)?);
+                            name, policy
+                        ),
+                        _ => (),
+                    }
+                    sync::show_outputs(sync::get_outputs_with_asset(&db, args)?, network_id);
                     println!("To see all details of a particular UTxO, invoke the `verify-utxo` command.");
                     Ok(())
                 }
@@ -175,7 +217,26 @@ impl WalletCommand {
                     sync::print_orders(&db)?;
                     Ok(())
                 }
+                Command::ShowHistory(args) => {
+                    println!("###### Transaction history ###########");
+                    sync::show_history(
+                        sync::get_history(&db, args.address.as_ref(), args.from, args.to)?,
+                        slot_config.slot_length,
+                        network_id,
+                    );
+                    Ok(())
+                }
+                Command::WatchAddress { address } => sync::watch_addresses(&db, &[address]).await,
+                Command::UnwatchAddress { address } => sync::unwatch_address(&db, &address).await,
                 Command::BuildTx(args) => command::build_tx(&db, &client, &keystore, args).await,
+                Command::NativeScriptAddress(args) => {
+                    command::native_script_address(args, network_id)
+                }
+                Command::CoSign(args) => command::co_sign(&client, &keystore, args).await,
+                Command::WitnessTx(args) => command::witness_tx(&keystore, args),
+                Command::AssembleTx(args) => command::assemble_tx(&client, args).await,
+                Command::InspectTx(args) => command::inspect_tx(&db, args, network_id),
+                Command::SubmitTx(args) => command::submit_tx(&client, args).await,
             },
             None => {
                 log::info!("No Wallet Command invoked. Exiting.");
@@ -225,9 +286,23 @@ pub enum Command {
         password: Option<String>,
     },
 
-    /// Show public information about all the keys in the keystore.
+    /// Show public information about all the keys in the keystore, including
+    /// those derived from the mnemonic which are in use.
+    #[command(verbatim_doc_comment)]
     ShowKeys,
 
+    /// Store a BIP39 mnemonic in the keystore. From the next run on, payment keys
+    /// are derived from it along CIP-1852 paths, owning base addresses.
+    #[command(verbatim_doc_comment)]
+    InsertMnemonic {
+        /// The mnemonic words, separated by spaces.
+        phrase: String,
+    },
+
+    /// Generate a 24-word BIP39 mnemonic and store it in the keystore, readable
+    /// only by its owner. The mnemonic is printed this once, to be written down.
+    GenerateMnemonic,
+
     /// Remove a specific key from the keystore.
     /// WARNING! This will permanently delete the private key information.
     /// Make sure your keys are backed up somewhere safe.
@@ -239,7 +314,7 @@ pub enum Command {
     },
 
     /// For each key tracked by the wallet, shows the sum of all UTXO values owned by that key.
-    /// This sum is sometimes known as the "balance".
+    /// This sum is sometimes known as the "balance". Watched addresses are shown as well.
     #[command(verbatim_doc_comment)]
     ShowBalance,
 
@@ -255,8 +330,57 @@ pub enum Command {
     /// Show the list of UTxOs sitting at the order book example script address.
     ShowAllOrders,
 
+    /// Show the transactions that sent or received value from the tracked addresses,
+    /// with the fees they paid.
+    #[command(verbatim_doc_comment)]
+    ShowHistory(ShowHistoryArgs),
+
+    /// Track the UTxOs and history of an address not belonging to the keystore,
+    /// such as a script address.
+    #[command(verbatim_doc_comment)]
+    WatchAddress {
+        /// Address to watch, in bech32 or hex.
+        #[arg(value_parser = address_from_string)]
+        address: Address,
+    },
+
+    /// Stop tracking a watched address.
+    UnwatchAddress {
+        /// Address to stop watching, in bech32 or hex.
+        #[arg(value_parser = address_from_string)]
+        address: Address,
+    },
+
     /// Build a complete Griffin transaction from a JSON file containing all the necessary information.
     BuildTx(BuildTxArgs),
+
+    /// Build a native script requiring signatures and/or a validity interval,
+    /// and show its hash and the address it locks.
+    #[command(verbatim_doc_comment)]
+    NativeScriptAddress(NativeScriptAddressArgs),
+
+    /// Add signatures from the keystore to a transaction stored in a file,
+    /// as produced by `build-tx --out`, and optionally submit it.
+    #[command(verbatim_doc_comment)]
+    CoSign(CoSignArgs),
+
+    /// Sign a transaction stored in a file with a key from the keystore, writing
+    /// the signature to a separate witness file, as `cardano-cli transaction witness`.
+    #[command(verbatim_doc_comment)]
+    WitnessTx(WitnessTxArgs),
+
+    /// Add the signatures in witness files, or in other copies of a transaction,
+    /// to the transaction stored in a file, and optionally submit it.
+    #[command(verbatim_doc_comment)]
+    AssembleTx(AssembleTxArgs),
+
+    /// Show the contents of a transaction stored in a file, along with the validity
+    /// of its signatures and the signatures still missing.
+    #[command(verbatim_doc_comment)]
+    InspectTx(InspectTxArgs),
+
+    /// Submit a transaction stored in a file.
+    SubmitTx(SubmitTxArgs),
 }
 
 /// Arguments for building a complete Griffin transaction.
@@ -270,29 +394,71 @@ pub struct BuildTxArgs {
     ///     - `tx_hash`: The hash of the transaction containing the output to be used as input.
     ///     - `index`: The index of the output in the transaction.
     ///     - `redeemer_cbor`: The cbor-encoded redeemer (optional, for script inputs).
+    ///     - `ex_units`: The execution budget of the redeemer, with fields `mem` and `steps`
+    ///       (optional, for script inputs; evaluated by the node when missing).
     /// - `outputs_info`: A list of output information objects.
     ///    Each output info contains the following fields:
-    ///     - `address`: The address of the output.
+    ///     - `address`: The address of the output, in bech32 or hex.
     ///     - `coin`: An amount of `Coin`s to be included in the output.
     ///     - `value`: A list of asset bundles to be included in the output.
     ///       Each asset bundle contains the following fields:
     ///         - `policy`: The policy ID of the asset bundle.
     ///         - `assets`: A list of tuples containing the asset name and the amount to be included.
     ///     - `datum`: The hex-encoded datum (optional, for script outputs).
+    ///     - `datum_hash`: The hex-encoded hash of a datum, to be used instead of `datum`
+    ///       (optional).
+    ///     - `script_ref`: The hex-encoded plutus script to be stored as reference script (optional).
+    ///     - `script_ref_version`: The Plutus version of `script_ref`, one of `"V1"`, `"V2"` or
+    ///       `"V3"` (optional, defaults to `"V2"`).
     /// - `scripts_info`: A list of JSON objects containing the hex of plutus scripts
     ///    and their parameters (if any) to be applied to the scripts.
     ///    Each object must contain the following fields:
     ///     - `script_hex`: The hex-encoded script.
     ///     - `script_params_cbor`: The cbor-encoded parameter list (optional).
+    ///     - `version`: The Plutus version of the script, one of `"V1"`, `"V2"` or `"V3"`
+    ///       (optional, defaults to `"V2"`).
     /// - `mintings_info`: A list of minting information objects (optional).
     ///    Each minting info contains the following fields:
     ///     - `policy`: The policy ID of the asset to be minted/burnt.
     ///     - `assets`: A list of tuples containing the asset name and the amount to be minted/burnt.
     ///     - `redeemer_cbor`: The cbor-encoded redeemer to the minting policy.
+    ///     - `ex_units`: The execution budget of the redeemer, with fields `mem` and `steps`
+    ///       (optional; evaluated by the node when missing).
     /// - `witnesses`: A list of public keys of input owners.
     /// - `required_signers`: A list of payment hashes of the senders.
     /// - `validity_interval_start`: Start of the validity interval (optional).
     /// - `ttl`: Time to live (optional).
+    /// - `fee`: An amount of `Coin`s to be paid as transaction fee (optional, defaults to 0).
+    ///    When balancing, it is raised to the minimum fee of the chain if lower.
+    /// - `reference_inputs`: A list of outputs to be read but not consumed (optional).
+    ///    Each reference input contains the fields `tx_hash` and `index`.
+    ///    Scripts stored in them need not be included in `scripts_info`.
+    /// - `collateral`: A list of outputs forfeited if scripts fail (optional).
+    ///    Each collateral input contains the fields `tx_hash` and `index`.
+    ///    When absent from a script transaction, an output owned by the keystore is
+    ///    put up, returning all of it but the share of the fee demanded by the chain.
+    /// - `collateral_return`: An output, with the same fields as those in `outputs_info`,
+    ///    returning the excess of collateral (optional).
+    /// - `total_collateral`: The amount of `Coin`s forfeited if scripts fail (optional).
+    /// - `is_valid`: Whether scripts are expected to succeed (optional, defaults to true).
+    ///    Transactions whose scripts contradict it are rejected, while those flagged
+    ///    as invalid forfeit their collateral.
+    /// - `native_scripts`: A list of native scripts ruling script inputs or minting
+    ///    policies, as printed by `native-script-address` (optional).
+    /// - `datums`: A list of hex-encoded datums whose hashes appear in the spent inputs
+    ///    or in the outputs (optional). Datums must be canonically encoded.
+    /// - `metadata`: An object mapping metadata labels to values, attached to the
+    ///    transaction as auxiliary data (optional). Integers, strings, lists and
+    ///    objects are mapped to their metadatum counterparts, except for strings
+    ///    starting with `0x`, which hold hex-encoded bytes.
+    /// - `withdrawals_info`: A list of withdrawals from reward accounts (optional).
+    ///    Each withdrawal info contains the following fields:
+    ///     - `reward_account`: The reward account, in bech32 (`stake1...`) or hex.
+    ///     - `amount`: The whole balance of the account.
+    ///     - `redeemer_cbor`: The cbor-encoded redeemer to the script ruling the
+    ///       account (optional, for Plutus script accounts).
+    ///     - `ex_units`: The execution budget of the redeemer, with fields `mem` and `steps`
+    ///       (optional; evaluated by the node when missing).
     #[arg(
         long,
         short,
@@ -301,13 +467,150 @@ pub struct BuildTxArgs {
         value_name = "TX_INFO_JSON"
     )]
     pub tx_info: String,
+
+    /// Write the transaction to this file instead of submitting it, so that other
+    /// parties can add their signatures with `co-sign` or `witness-tx`.
+    /// The file is a `cardano-cli` text envelope that also holds the outputs spent
+    /// by the transaction.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub out: Option<PathBuf>,
+
+    /// Do not sign the transaction, so that the keys need not be in the keystore.
+    /// The keys that must sign it are shown instead.
+    #[arg(long, verbatim_doc_comment, requires = "out")]
+    pub unsigned: bool,
+
+    /// Balance the transaction by selecting further inputs from the outputs owned by keys
+    /// in the keystore, and send the surplus to this address (in bech32 or hex).
+    /// Without it, transactions spending known outputs are refused unless balanced.
+    #[arg(long, verbatim_doc_comment, value_parser = address_from_string, value_name = "ADDRESS")]
+    pub change_address: Option<Address>,
+
+    /// Strategy used to select inputs when balancing.
+    #[arg(
+        long,
+        verbatim_doc_comment,
+        value_enum,
+        default_value_t,
+        value_name = "STRATEGY"
+    )]
+    pub strategy: SelectionStrategy,
+}
+
+/// Arguments for building a native script and its address.
+#[derive(Clone, Debug, Args)]
+pub struct NativeScriptAddressArgs {
+    /// 32-byte H256 public key of a signer. This argument may be specified multiple times.
+    #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, action = Append, value_name = "PUBLIC_KEY")]
+    pub signer: Vec<H256>,
+
+    /// How many of the signers must sign. Defaults to all of them.
+    #[arg(long, short, verbatim_doc_comment, value_name = "N")]
+    pub required: Option<u32>,
+
+    /// First slot at which the script can be satisfied.
+    #[arg(long, short, verbatim_doc_comment, value_name = "SLOT")]
+    pub after: Option<u64>,
+
+    /// Slot from which on the script cannot be satisfied.
+    #[arg(long, short, verbatim_doc_comment, value_name = "SLOT")]
+    pub before: Option<u64>,
+
+    /// Write the script in JSON format to this file.
+    #[arg(long, short, verbatim_doc_comment, value_name = "SCRIPT_FILE")]
+    pub out: Option<PathBuf>,
+}
+
+/// Arguments for co-signing a transaction.
+#[derive(Clone, Debug, Args)]
+pub struct CoSignArgs {
+    /// Path to the file containing the transaction.
+    /// The signatures are added to the transaction in this file.
+    #[arg(
+        long,
+        short,
+        verbatim_doc_comment,
+        required = true,
+        value_name = "TX_FILE"
+    )]
+    pub tx: PathBuf,
+
+    /// 32-byte H256 public key of a signer. This argument may be specified multiple times.
+    /// Their pk/sk pair must be registered in the wallet's keystore.
+    #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, action = Append, required = true, value_name = "PUBLIC_KEY")]
+    pub witness: Vec<H256>,
+
+    /// Submit the transaction once signed.
+    #[arg(long, short, verbatim_doc_comment)]
+    pub submit: bool,
+}
+
+/// Arguments for signing a transaction into a witness file.
+#[derive(Clone, Debug, Args)]
+pub struct WitnessTxArgs {
+    /// Path to the file containing the transaction.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub tx: PathBuf,
+
+    /// 32-byte H256 public key of the signer.
+    /// Their pk/sk pair must be registered in the wallet's keystore.
+    #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, value_name = "PUBLIC_KEY")]
+    pub witness: H256,
+
+    /// Path of the witness file to write.
+    #[arg(long, short, verbatim_doc_comment, value_name = "WITNESS_FILE")]
+    pub out: PathBuf,
+}
+
+/// Arguments for adding the signatures in witness files to a transaction.
+#[derive(Clone, Debug, Args)]
+pub struct AssembleTxArgs {
+    /// Path to the file containing the transaction.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub tx: PathBuf,
+
+    /// A witness file, or a file containing the same transaction with other signatures.
+    /// This argument may be specified multiple times.
+    #[arg(long, short, verbatim_doc_comment, action = Append, required = true, value_name = "WITNESS_FILE")]
+    pub witness_file: Vec<PathBuf>,
+
+    /// Write the signed transaction to this file instead of the original one.
+    #[arg(
+        long,
+        short,
+        verbatim_doc_comment,
+        conflicts_with = "submit",
+        value_name = "TX_FILE"
+    )]
+    pub out: Option<PathBuf>,
+
+    /// Submit the transaction once signed.
+    #[arg(long, short, verbatim_doc_comment)]
+    pub submit: bool,
+}
+
+/// Arguments for showing a transaction stored in a file.
+#[derive(Clone, Debug, Args)]
+pub struct InspectTxArgs {
+    /// Path to the file containing the transaction.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub tx: PathBuf,
+}
+
+/// Arguments for submitting a transaction stored in a file.
+#[derive(Clone, Debug, Args)]
+pub struct SubmitTxArgs {
+    /// Path to the file containing the transaction.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub tx: PathBuf,
 }
 
 /// Arguments for spending wallet inputs only.
 #[derive(Clone, Debug, Args)]
 pub struct SpendValueArgs {
     /// An input to be consumed by this transaction. This argument may be specified multiple times.
-    #[arg(long, short, verbatim_doc_comment, value_parser = input_from_string, required = true, value_name = "OUTPUT_REF")]
+    /// Further inputs are selected from the outputs owned by keys in the keystore when needed.
+    #[arg(long, short, verbatim_doc_comment, value_parser = input_from_string, value_name = "OUTPUT_REF")]
     pub input: Vec<Input>,
 
     /// 32-byte H256 public key of an input owner.
@@ -315,7 +618,7 @@ pub struct SpendValueArgs {
     #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, default_value = SHAWN_PUB_KEY, value_name = "PUBLIC_KEY")]
     pub witness: Vec<H256>,
 
-    /// 29-byte hash-address of the recipient.
+    /// Address of the recipient, in bech32 or hex.
     #[arg(long, short, verbatim_doc_comment, value_parser = address_from_string, default_value = SHAWN_ADDRESS, value_name = "ADDRESS")]
     pub recipient: Address,
 
@@ -334,11 +637,55 @@ pub struct SpendValueArgs {
     /// How many tokens of the given asset should be included.
     #[arg(long, short, verbatim_doc_comment, action = Append, value_name = "AMOUNT")]
     pub token_amount: Vec<Coin>,
+
+    /// An amount of `Coin`s to be paid as transaction fee.
+    /// It is raised to the minimum fee of the chain if lower.
+    #[arg(long, verbatim_doc_comment, default_value_t = 0, value_name = "COIN")]
+    pub fee: Coin,
+
+    /// Address receiving the surplus of the inputs, in bech32 or hex.
+    /// Defaults to the address of the first witness.
+    #[arg(long, verbatim_doc_comment, value_parser = address_from_string, value_name = "ADDRESS")]
+    pub change_address: Option<Address>,
+
+    /// Strategy used to select inputs from the outputs owned by keys in the keystore.
+    #[arg(
+        long,
+        verbatim_doc_comment,
+        value_enum,
+        default_value_t,
+        value_name = "STRATEGY"
+    )]
+    pub strategy: SelectionStrategy,
+
+    /// Write the transaction to this file instead of submitting it, as `build-tx --out`.
+    #[arg(long, short, verbatim_doc_comment, value_name = "TX_FILE")]
+    pub out: Option<PathBuf>,
+
+    /// Do not sign the transaction, so that the keys need not be in the keystore.
+    /// The keys that must sign it are shown instead.
+    #[arg(long, verbatim_doc_comment, requires = "out")]
+    pub unsigned: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ShowHistoryArgs {
+    /// Only show the history of this address, given in bech32 or hex.
+    #[arg(long, short, verbatim_doc_comment, value_parser = address_from_string, value_name = "ADDRESS")]
+    pub address: Option<Address>,
+
+    /// Only show transactions from this block height on.
+    #[arg(long, short, verbatim_doc_comment, value_name = "HEIGHT")]
+    pub from: Option<u32>,
+
+    /// Only show transactions up to this block height.
+    #[arg(long, short, verbatim_doc_comment, value_name = "HEIGHT")]
+    pub to: Option<u32>,
 }
 
 #[derive(Clone, Debug, Args)]
 pub struct ShowOutputsAtArgs {
-    /// 29-byte hash-address.
+    /// Address, in bech32 or hex.
     #[arg(long, short, verbatim_doc_comment, value_parser = address_from_string, required = true, value_name = "ADDRESS")]
     pub address: Address,
 }
@@ -346,16 +693,27 @@ pub struct ShowOutputsAtArgs {
 #[derive(Clone, Debug, Args)]
 pub struct ShowOutputsWithAssetArgs {
     /// Policy ID of the asset.
-    #[arg(long, short, verbatim_doc_comment, value_parser = h224_from_string, required = true, value_name = "POLICY_ID")]
-    pub policy: PolicyId,
+    #[arg(long, short, verbatim_doc_comment, value_parser = h224_from_string, required_unless_present = "fingerprint", requires = "name", value_name = "POLICY_ID")]
+    pub policy: Option<PolicyId>,
 
     /// Name of the asset.
     #[arg(
         long,
         short,
         verbatim_doc_comment,
-        required = true,
+        required_unless_present = "fingerprint",
+        requires = "policy",
         value_name = "ASSET_NAME"
     )]
-    pub name: String,
+    pub name: Option<String>,
+
+    /// CIP-14 fingerprint of the asset (`asset1...`), instead of its policy and name.
+    #[arg(
+        long,
+        short,
+        verbatim_doc_comment,
+        conflicts_with_all = ["policy", "name"],
+        value_name = "FINGERPRINT"
+    )]
+    pub fingerprint: Option<String>,
 }
@@ -0,0 +1,575 @@
+//! Selection of wallet outputs funding a transaction, and balancing of the
+//! transaction with a change output.
+//!
+//! Two of the strategies described in
+//! [CIP-2](https://cips.cardano.org/cip/CIP-0002) are supported. Both of them
+//! work asset by asset, covering tokens first and `Coin`s last, since outputs
+//! selected because of their tokens also contribute their `Coin`s.
+
+use crate::sync::ResolvedInputInfo;
+use anyhow::anyhow;
+use griffin_core::{
+    checks_interface::babbage_tx_to_cbor,
+    pallas_applying::utils::get_val_size_in_words,
+    pallas_primitives::babbage::{Tx as PallasTransaction, Value as PallasValue},
+    protocol_parameters::ProtocolParameters,
+    types::{
+        Address, AssetName, Coin, Input, Multiasset, Output, PolicyId, RedeemerTag, Transaction,
+        VKeyWitness, Value,
+    },
+};
+use rand::{seq::SliceRandom, Rng};
+
+/// Strategy used to pick the wallet outputs spent by a transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SelectionStrategy {
+    /// Spend the outputs holding the most of each needed asset first.
+    #[default]
+    LargestFirst,
+    /// Spend random outputs until each needed asset is covered, and then add
+    /// random outputs while they bring the selection closer to twice the
+    /// needed amount.
+    RandomImprove,
+}
+
+/// An asset held in a `Value`, where `None` stands for `Coin`s.
+type Asset = Option<(PolicyId, AssetName)>;
+
+/// Amount of `asset` in `value`.
+fn quantity(value: &Value, asset: &Asset) -> Coin {
+    match asset {
+        None => value.coin_of(),
+        Some((policy, name)) => value.quantity_of(policy, name),
+    }
+}
+
+/// The assets in `value`, tokens first and `Coin`s last.
+fn assets(value: &Value) -> Vec<Asset> {
+    let mut res: Vec<Asset> = Vec::from(&Multiasset::from(value.clone()))
+        .into_iter()
+        .map(|(policy, name, _)| Some((policy, name)))
+        .collect();
+    res.push(None);
+    res
+}
+
+/// The part of `required` not covered by `provided`.
+fn shortfall(required: &Value, provided: &Value) -> Value {
+    assets(required)
+        .into_iter()
+        .fold(Value::Coin(0), |res, asset| {
+            let missing = quantity(required, &asset).saturating_sub(quantity(provided, &asset));
+            res + match asset {
+                None => Value::Coin(missing),
+                Some((policy, name)) => Value::from((policy, name, missing)),
+            }
+        })
+}
+
+fn total_value(outputs: &[ResolvedInputInfo]) -> Value {
+    outputs
+        .iter()
+        .fold(Value::Coin(0), |res, output| res + output.value.clone())
+}
+
+/// Picks outputs among `available` that, together with those in `selected`,
+/// cover `target`.
+pub fn select_inputs<R: Rng>(
+    strategy: SelectionStrategy,
+    target: &Value,
+    mut available: Vec<ResolvedInputInfo>,
+    rng: &mut R,
+) -> anyhow::Result<Vec<ResolvedInputInfo>> {
+    let mut selected: Vec<ResolvedInputInfo> = Vec::new();
+    let mut selected_value = Value::Coin(0);
+
+    for asset in assets(target) {
+        let needed = quantity(target, &asset);
+        while quantity(&selected_value, &asset) < needed {
+            let candidates = available
+                .iter()
+                .enumerate()
+                .filter(|(_, output)| quantity(&output.value, &asset) > 0);
+            let pick = match strategy {
+                SelectionStrategy::LargestFirst => candidates
+                    .max_by_key(|(_, output)| quantity(&output.value, &asset))
+                    .map(|(i, _)| i),
+                SelectionStrategy::RandomImprove => {
+                    let indices: Vec<usize> = candidates.map(|(i, _)| i).collect();
+                    indices.choose(rng).copied()
+                }
+            };
+            let Some(i) = pick else {
+                return Err(anyhow!(
+                    "Insufficient funds: {} more {} needed.",
+                    needed - quantity(&selected_value, &asset),
+                    asset_to_string(&asset),
+                ));
+            };
+            let output = available.swap_remove(i);
+            selected_value += output.value.clone();
+            selected.push(output);
+        }
+    }
+
+    if strategy == SelectionStrategy::RandomImprove {
+        for asset in assets(target) {
+            let ideal = 2 * quantity(target, &asset);
+            let maximum = 3 * quantity(target, &asset);
+            available.shuffle(rng);
+            let mut i = 0;
+            while i < available.len() {
+                let current = quantity(&selected_value, &asset);
+                let improved = current + quantity(&available[i].value, &asset);
+                if improved > current
+                    && improved <= maximum
+                    && improved.abs_diff(ideal) < current.abs_diff(ideal)
+                {
+                    let output = available.swap_remove(i);
+                    selected_value += output.value.clone();
+                    selected.push(output);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    Ok(selected)
+}
+
+fn asset_to_string(asset: &Asset) -> String {
+    match asset {
+        None => "Coins".to_string(),
+        Some((policy, name)) => {
+            format!("of asset {} with policy ID {}", name.0, hex::encode(policy))
+        }
+    }
+}
+
+/// Smallest amount of `Coin`s an output holding `value` must carry.
+pub fn min_coin(value: &Value, pps: &ProtocolParameters) -> Coin {
+    let size: u64 = get_val_size_in_words(&PallasValue::from(value.clone()));
+    pps.min_coin_per_output
+        .max(pps.coins_per_utxo_word * (size + 160))
+}
+
+/// Minimum fee of `transaction` once signed by `signers` keys.
+pub fn min_fee(
+    transaction: &Transaction,
+    signers: usize,
+    pps: &ProtocolParameters,
+) -> anyhow::Result<Coin> {
+    let mut signed = transaction.clone();
+    let placeholders = (0..signers).map(|_| VKeyWitness::from((vec![0; 32], vec![0; 64])));
+    signed.transaction_witness_set.vkeywitness = Some(
+        signed
+            .transaction_witness_set
+            .vkeywitness
+            .unwrap_or_default()
+            .into_iter()
+            .chain(placeholders)
+            .collect(),
+    );
+    let pallas_tx =
+        PallasTransaction::try_from(signed).map_err(|e| anyhow!("Invalid transaction: {:?}", e))?;
+    let size = babbage_tx_to_cbor(&pallas_tx).len() as Coin;
+
+    Ok(pps.min_fee_a as Coin * size + pps.min_fee_b as Coin)
+}
+
+/// Value consumed by `transaction` besides its inputs, worth `input_value`:
+/// withdrawals and minted tokens.
+fn consumed(transaction: &Transaction, input_value: &Value) -> Value {
+    let body = &transaction.transaction_body;
+    let withdrawn: Coin = body.withdrawals.iter().flatten().map(|(_, c)| c).sum();
+    let minted: Value = body
+        .mint
+        .iter()
+        .flat_map(Vec::<(PolicyId, AssetName, i64)>::from)
+        .fold(
+            Value::Coin(0),
+            |res, (policy, name, amount): (PolicyId, AssetName, i64)| match Coin::try_from(amount) {
+                Ok(amount) => res + Value::from((policy, name, amount)),
+                Err(_) => res,
+            },
+        );
+
+    input_value.clone() + Value::Coin(withdrawn) + minted
+}
+
+/// Value produced by `transaction`: its outputs, its fee and burned tokens.
+fn produced(transaction: &Transaction) -> Value {
+    let body = &transaction.transaction_body;
+    let burned: Value = body
+        .mint
+        .iter()
+        .flat_map(Vec::<(PolicyId, AssetName, i64)>::from)
+        .fold(
+            Value::Coin(0),
+            |res, (policy, name, amount): (PolicyId, AssetName, i64)| {
+                if amount < 0 {
+                    res + Value::from((policy, name, amount.unsigned_abs()))
+                } else {
+                    res
+                }
+            },
+        );
+
+    body.outputs
+        .iter()
+        .fold(Value::Coin(body.fee) + burned, |res, output| {
+            res + output.value.clone()
+        })
+}
+
+/// Whether the value consumed by `transaction`, whose inputs are worth
+/// `input_value`, equals the value it produces.
+pub fn is_balanced(transaction: &Transaction, input_value: &Value) -> bool {
+    consumed(transaction, input_value).normalize() == produced(transaction).normalize()
+}
+
+/// Deducts from the collateral return of `transaction` the share of its fee
+/// demanded by the chain, which is forfeited if its scripts fail. Since the
+/// share already deducted is given back first, it can be settled again after
+/// the fee changes.
+pub fn settle_collateral(
+    transaction: &mut Transaction,
+    pps: &ProtocolParameters,
+) -> anyhow::Result<()> {
+    let body = &mut transaction.transaction_body;
+    let forfeited: Coin = (body.fee * pps.collateral_percentage as Coin).div_ceil(100);
+    if let Some(collateral_return) = &mut body.collateral_return {
+        collateral_return.value += Value::Coin(body.total_collateral.unwrap_or(0));
+        if collateral_return.value.coin_of() < forfeited + pps.min_coin_per_output {
+            Err(anyhow!(
+                "The collateral does not cover {forfeited} Coins of the fee"
+            ))?;
+        }
+        collateral_return.value -= Value::Coin(forfeited);
+        body.total_collateral = Some(forfeited);
+    }
+
+    Ok(())
+}
+
+/// Rounds of fee estimation after which balancing gives up.
+const MAX_BALANCING_ROUNDS: usize = 10;
+
+/// Balances `transaction`, whose inputs are worth `input_value`, by spending
+/// outputs from `available` and sending the surplus to `change_address`.
+///
+/// The fee is raised to the minimum one for the transaction signed by
+/// `signers` keys plus the owners of the added inputs. A change holding only
+/// `Coin`s below the minimum for an output is added to the fee instead. The
+/// collateral is settled in every round, so that the fee covers the total
+/// collateral and the collateral return as finally sized.
+/// Returns the added inputs, which are sorted among the existing ones with
+/// the indices of spending redeemers updated accordingly.
+#[allow(clippy::too_many_arguments)]
+pub fn balance_transaction<R: Rng>(
+    transaction: &mut Transaction,
+    input_value: &Value,
+    available: Vec<ResolvedInputInfo>,
+    change_address: &Address,
+    signers: usize,
+    pps: &ProtocolParameters,
+    strategy: SelectionStrategy,
+    rng: &mut R,
+) -> anyhow::Result<Vec<ResolvedInputInfo>> {
+    let available: Vec<ResolvedInputInfo> = available
+        .into_iter()
+        .filter(|output| !transaction.transaction_body.inputs.contains(&output.input))
+        .collect();
+    let mut fee: Coin = transaction.transaction_body.fee;
+    let mut change_coin: Coin = 0;
+
+    for _ in 0..MAX_BALANCING_ROUNDS {
+        let mut candidate = transaction.clone();
+        candidate.transaction_body.fee = fee;
+        let required = produced(&candidate) + Value::Coin(change_coin);
+        let provided = consumed(&candidate, input_value);
+        let selected = select_inputs(
+            strategy,
+            &shortfall(&required, &provided),
+            available.clone(),
+            rng,
+        )?;
+        let change: Value = (provided + total_value(&selected)) - produced(&candidate);
+
+        add_inputs(&mut candidate, &selected);
+        if !change.is_null() {
+            let change_output = Output::from((change_address.clone(), change.clone()));
+            candidate.transaction_body.outputs.push(change_output);
+        }
+        let mut owners: Vec<&Address> = selected.iter().map(|output| &output.address).collect();
+        owners.sort_by_key(|address| &address.0);
+        owners.dedup();
+
+        let min_change_coin = min_coin(&change, pps);
+        if !change.is_null() && change.coin_of() < min_change_coin {
+            if Multiasset::from(change.clone()).is_null() {
+                // Too little change to be worth an output.
+                candidate.transaction_body.outputs.pop();
+                candidate.transaction_body.fee += change.coin_of();
+            } else {
+                change_coin += min_change_coin - change.coin_of();
+                continue;
+            }
+        }
+
+        settle_collateral(&mut candidate, pps)?;
+        let required_fee = min_fee(&candidate, signers + owners.len(), pps)?;
+        if candidate.transaction_body.fee < required_fee {
+            fee = required_fee;
+            continue;
+        }
+
+        *transaction = candidate;
+        return Ok(selected);
+    }
+
+    Err(anyhow!(
+        "Unable to balance the transaction after {MAX_BALANCING_ROUNDS} rounds of fee estimation."
+    ))
+}
+
+/// Adds the `selected` outputs to the inputs of `transaction`, keeping them
+/// sorted and the spending redeemers pointing to the same inputs.
+fn add_inputs(transaction: &mut Transaction, selected: &[ResolvedInputInfo]) {
+    let old_inputs: Vec<Input> = transaction.transaction_body.inputs.clone();
+    let inputs = &mut transaction.transaction_body.inputs;
+    inputs.extend(selected.iter().map(|output| output.input.clone()));
+    inputs.sort();
+
+    for redeemer in transaction
+        .transaction_witness_set
+        .redeemer
+        .iter_mut()
+        .flatten()
+        .filter(|redeemer| redeemer.tag == RedeemerTag::Spend)
+    {
+        if let Some(input) = old_inputs.get(redeemer.index as usize) {
+            redeemer.index = inputs.iter().position(|i| i == input).unwrap_or_default() as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use griffin_core::types::{address_from_hex, ExUnits, PlutusData, Redeemer};
+    use rand::{rngs::StdRng, SeedableRng};
+    use sp_core::H256;
+
+    const ADDRESS: &str = "6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6544b47329c4";
+
+    fn policy() -> PolicyId {
+        PolicyId::from([7; 28])
+    }
+
+    fn utxo(index: u32, value: Value) -> ResolvedInputInfo {
+        ResolvedInputInfo::from((
+            Input {
+                tx_hash: H256::repeat_byte(1),
+                index,
+            },
+            address_from_hex(ADDRESS),
+            value,
+            None,
+        ))
+    }
+
+    fn wallet() -> Vec<ResolvedInputInfo> {
+        vec![
+            utxo(0, Value::Coin(1_000)),
+            utxo(1, Value::Coin(5_000)),
+            utxo(
+                2,
+                Value::from((2_000, policy(), AssetName::from("tok".to_string()), 10)),
+            ),
+            utxo(3, Value::Coin(3_000)),
+        ]
+    }
+
+    fn pps() -> ProtocolParameters {
+        serde_json::from_value(serde_json::json!({
+            "zero_time": 0,
+            "zero_slot": 0,
+            "slot_length": 1000,
+            "min_fee_a": 2,
+            "min_fee_b": 100,
+            "min_coin_per_output": 500,
+        }))
+        .unwrap()
+    }
+
+    fn indices(selected: &[ResolvedInputInfo]) -> Vec<u32> {
+        let mut res: Vec<u32> = selected.iter().map(|output| output.input.index).collect();
+        res.sort();
+        res
+    }
+
+    #[test]
+    fn largest_first_spends_the_largest_outputs() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = select_inputs(
+            SelectionStrategy::LargestFirst,
+            &Value::Coin(6_000),
+            wallet(),
+            &mut rng,
+        )
+        .unwrap();
+
+        assert_eq!(indices(&selected), vec![1, 3]);
+    }
+
+    #[test]
+    fn tokens_are_selected_before_coins() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let target = Value::from((1_500, policy(), AssetName::from("tok".to_string()), 4));
+        let selected =
+            select_inputs(SelectionStrategy::LargestFirst, &target, wallet(), &mut rng).unwrap();
+
+        // The token output already covers the coins.
+        assert_eq!(indices(&selected), vec![2]);
+    }
+
+    #[test]
+    fn random_improve_covers_the_target_within_three_times() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let selected = select_inputs(
+                SelectionStrategy::RandomImprove,
+                &Value::Coin(2_500),
+                wallet(),
+                &mut rng,
+            )
+            .unwrap();
+            let total = total_value(&selected).coin_of();
+
+            assert!(total >= 2_500);
+            assert!(total <= 7_500);
+        }
+    }
+
+    #[test]
+    fn insufficient_funds_are_reported() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let error = select_inputs(
+            SelectionStrategy::LargestFirst,
+            &Value::Coin(20_000),
+            wallet(),
+            &mut rng,
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().contains("Insufficient funds"));
+    }
+
+    #[test]
+    fn balanced_transaction_pays_fee_and_change() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let recipient = address_from_hex(ADDRESS);
+        let mut tx = Transaction::from((
+            Vec::new(),
+            vec![Output::from((recipient.clone(), Value::Coin(4_000)))],
+        ));
+
+        let selected = balance_transaction(
+            &mut tx,
+            &Value::Coin(0),
+            wallet(),
+            &recipient,
+            0,
+            &pps(),
+            SelectionStrategy::LargestFirst,
+            &mut rng,
+        )
+        .unwrap();
+
+        let input_value = total_value(&selected);
+        assert!(is_balanced(&tx, &input_value));
+        assert_eq!(tx.transaction_body.outputs.len(), 2);
+        assert!(tx.transaction_body.fee >= min_fee(&tx, 1, &pps()).unwrap());
+        assert!(tx.transaction_body.outputs[1].value.coin_of() >= 500);
+    }
+
+    #[test]
+    fn added_inputs_keep_spending_redeemers_in_place() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let script_input = Input {
+            tx_hash: H256::repeat_byte(2),
+            index: 0,
+        };
+        let recipient = address_from_hex(ADDRESS);
+        let mut tx = Transaction::from((
+            vec![script_input.clone()],
+            vec![Output::from((recipient.clone(), Value::Coin(4_000)))],
+        ));
+        tx.transaction_witness_set.redeemer = Some(vec![Redeemer {
+            tag: RedeemerTag::Spend,
+            index: 0,
+            data: PlutusData(vec![0x00]),
+            ex_units: ExUnits { mem: 0, steps: 0 },
+        }]);
+
+        balance_transaction(
+            &mut tx,
+            &Value::Coin(1_000),
+            wallet(),
+            &recipient,
+            0,
+            &pps(),
+            SelectionStrategy::LargestFirst,
+            &mut rng,
+        )
+        .unwrap();
+
+        let inputs = &tx.transaction_body.inputs;
+        let redeemer = &tx.transaction_witness_set.redeemer.as_ref().unwrap()[0];
+        assert!(inputs.len() > 1);
+        assert_eq!(inputs[redeemer.index as usize], script_input);
+    }
+
+    #[test]
+    fn balanced_fee_covers_the_settled_collateral() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let recipient = address_from_hex(ADDRESS);
+        let mut tx = Transaction::from((
+            Vec::new(),
+            vec![Output::from((recipient.clone(), Value::Coin(4_000)))],
+        ));
+        tx.transaction_body.collateral = Some(vec![Input {
+            tx_hash: H256::repeat_byte(3),
+            index: 0,
+        }]);
+        tx.transaction_body.collateral_return =
+            Some(Output::from((recipient.clone(), Value::Coin(10_000))));
+        let pps = ProtocolParameters {
+            collateral_percentage: 150,
+            ..pps()
+        };
+
+        balance_transaction(
+            &mut tx,
+            &Value::Coin(0),
+            wallet(),
+            &recipient,
+            0,
+            &pps,
+            SelectionStrategy::LargestFirst,
+            &mut rng,
+        )
+        .unwrap();
+
+        let body = &tx.transaction_body;
+        let forfeited = (body.fee * 150).div_ceil(100);
+        assert_eq!(body.total_collateral, Some(forfeited));
+        assert_eq!(
+            body.collateral_return.as_ref().unwrap().value.coin_of(),
+            10_000 - forfeited
+        );
+        assert!(body.fee >= min_fee(&tx, 1, &pps).unwrap());
+    }
+}
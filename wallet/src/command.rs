@@ -1,42 +1,69 @@
 use crate::{
-    cli::{BuildTxArgs, SpendValueArgs},
-    keystore::SHAWN_ADDRESS,
+    cli::{
+        AssembleTxArgs, BuildTxArgs, CoSignArgs, InspectTxArgs, NativeScriptAddressArgs,
+        SpendValueArgs, SubmitTxArgs, WitnessTxArgs,
+    },
+    coin_selection::{
+        balance_transaction, is_balanced, min_fee, settle_collateral, SelectionStrategy,
+    },
+    keystore::WalletKeystore,
+    rpc::{
+        fetch_storage, node_evaluate_transaction, node_get_protocol_parameters,
+        node_set_evaluated_ex_units,
+    },
     sync,
+    tx_file::{self, TxFile},
 };
 use anyhow::anyhow;
+use core::str::FromStr;
 use griffin_core::{
     checks_interface::{babbage_minted_tx_from_cbor, babbage_tx_to_cbor},
     genesis::config_builder::{
         transp_to_multiasset, transp_to_output, TransparentMultiasset, TransparentOutput,
     },
     h224::H224,
-    pallas_primitives::babbage::{MintedTx, Tx as PallasTransaction},
+    pallas_addresses::{Address as PallasAddress, Error as AddressError, ShelleyPaymentPart},
+    pallas_codec::{minicbor, utils::KeyValuePairs},
+    pallas_crypto::hash::Hasher as PallasHasher,
+    pallas_primitives::{
+        babbage::{AuxiliaryData as PallasAuxiliaryData, MintedTx, Tx as PallasTransaction},
+        Metadatum, MetadatumLabel,
+    },
     pallas_traverse::OriginalHash,
     types::{
-        address_from_hex, value_leq, AssetName, Input, Multiasset, Output, PlutusData,
-        PlutusScript, PolicyId, Redeemer, RedeemerTag, Transaction, VKeyWitness, Value,
+        address_from_native_script, address_from_pk, compute_auxiliary_data_hash,
+        compute_native_script_hash, Address, AssetName, AuxiliaryData, Coin, ExUnits, Input,
+        Multiasset, NativeScript, Output, PlutusData, PlutusScript, PlutusVersion, PolicyId,
+        Redeemer, RedeemerTag, Transaction, VKeyWitness, Value,
     },
     uplc::tx::apply_params_to_script,
 };
 use jsonrpsee::{core::client::ClientT, http_client::HttpClient, rpc_params};
 use parity_scale_codec::Encode;
-use sc_keystore::LocalKeystore;
 use serde::{Deserialize, Serialize};
 use sled::Db;
-use sp_core::{ed25519::Public, H256};
+use sp_core::{
+    crypto::Pair as _,
+    ed25519::{Pair, Public, Signature},
+    H256,
+};
 use sp_runtime::traits::{BlakeTwo256, Hash};
+use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct MintingInfo {
     policy: String,
     assets: Vec<(String, i64)>,
     redeemer_cbor: String,
+    ex_units: Option<ExUnits>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct ScriptInfo {
     script_hex: String,
     script_params_cbor: Option<String>,
+    #[serde(default)]
+    version: PlutusVersion,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -44,6 +71,15 @@ struct InputInfo {
     tx_hash: H256,
     index: u32,
     redeemer_cbor: Option<String>, // For script inputs
+    ex_units: Option<ExUnits>,     // For script inputs
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct WithdrawalInfo {
+    reward_account: String,
+    amount: Coin,
+    redeemer_cbor: Option<String>, // For script reward accounts
+    ex_units: Option<ExUnits>,     // For script reward accounts
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -56,13 +92,277 @@ struct TxInfo {
     required_signers: Vec<H224>,
     validity_interval_start: Option<u64>,
     ttl: Option<u64>,
+    #[serde(default)]
+    fee: Coin,
+    reference_inputs: Option<Vec<Input>>,
+    collateral: Option<Vec<Input>>,
+    collateral_return: Option<TransparentOutput>,
+    total_collateral: Option<Coin>,
+    is_valid: Option<bool>,
+    native_scripts: Option<Vec<NativeScript>>,
+    datums: Option<Vec<String>>,
+    metadata: Option<serde_json::Value>,
+    withdrawals_info: Option<Vec<WithdrawalInfo>>,
+}
+
+/// Sorting key of reward accounts, in the order followed by the indices of
+/// their redeemers: by network, script accounts first, and then by hash.
+fn reward_account_order(account: &[u8]) -> (u8, bool, &[u8]) {
+    let header = account.first().copied().unwrap_or_default();
+
+    (
+        header & 0x0f,
+        header & 0x10 == 0,
+        account.get(1..).unwrap_or_default(),
+    )
+}
+
+/// Explanation of the rejection of a transaction, obtained by evaluating it.
+async fn rejection_reason(transaction: &Transaction, client: &HttpClient) -> String {
+    match node_evaluate_transaction(transaction, client).await {
+        Ok(evaluation) => evaluation.error.map_or_else(
+            || "no validation error found at the best block".to_string(),
+            |err| format!("{} (error code {})", err.message, err.code),
+        ),
+        Err(err) => err.to_string(),
+    }
+}
+
+/// Hash of the body of `transaction`, which is signed by its witnesses.
+fn tx_body_hash(transaction: &Transaction) -> anyhow::Result<Vec<u8>> {
+    let pallas_tx = PallasTransaction::try_from(transaction.clone())
+        .map_err(|e| anyhow!("Invalid transaction: {:?}", e))?;
+    let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
+    let mtx: MintedTx = babbage_minted_tx_from_cbor(&cbor_bytes);
+
+    Ok(Vec::from(mtx.transaction_body.original_hash().as_ref()))
+}
+
+/// Signatures of the body of `transaction` by the given keys, which must be
+/// registered in the keystore.
+fn sign_transaction(
+    keystore: &WalletKeystore,
+    transaction: &Transaction,
+    keys: &[H256],
+) -> anyhow::Result<Vec<VKeyWitness>> {
+    let tx_hash: &Vec<u8> = &tx_body_hash(transaction)?;
+    log::debug!("Original tx_body hash is: {:#x?}", tx_hash);
+
+    let mut witnesses: Vec<VKeyWitness> = Vec::new();
+    for key in keys {
+        let vkey: Vec<u8> = Vec::from(key.0);
+        let public = Public::from_h256(*key);
+        let signature: Vec<u8> =
+            Vec::from(crate::keystore::sign_with(keystore, &public, tx_hash)?.0);
+        witnesses.push(VKeyWitness::from((vkey, signature)));
+    }
+
+    Ok(witnesses)
+}
+
+/// Whether `witness` holds a valid signature of `tx_hash`.
+fn is_valid_witness(witness: &VKeyWitness, tx_hash: &[u8]) -> bool {
+    match (
+        Public::try_from(witness.vkey.as_slice()),
+        Signature::try_from(witness.signature.as_slice()),
+    ) {
+        (Ok(public), Ok(signature)) => Pair::verify(&signature, tx_hash, &public),
+        _ => false,
+    }
+}
+
+/// Adds to `transaction` the witnesses of keys that have not signed it yet,
+/// returning how many were added.
+fn add_witnesses(transaction: &mut Transaction, new_witnesses: Vec<VKeyWitness>) -> usize {
+    let witnesses = transaction
+        .transaction_witness_set
+        .vkeywitness
+        .get_or_insert_with(Vec::new);
+    let mut added = 0;
+    for witness in new_witnesses {
+        if !witnesses.iter().any(|w| w.vkey == witness.vkey) {
+            witnesses.push(witness);
+            added += 1;
+        }
+    }
+
+    added
+}
+
+/// The outputs spent, referenced or put up as collateral by `transaction`,
+/// as found in chain storage. Outputs that cannot be found are left out.
+async fn resolve_inputs(transaction: &Transaction, client: &HttpClient) -> Vec<(Input, Output)> {
+    let body = &transaction.transaction_body;
+    let mut resolved: Vec<(Input, Output)> = Vec::new();
+    for input in body
+        .inputs
+        .iter()
+        .chain(body.reference_inputs.iter().flatten())
+        .chain(body.collateral.iter().flatten())
+    {
+        match fetch_storage(input, client).await {
+            Ok(output) => resolved.push((input.clone(), output)),
+            Err(err) => log::warn!("Unable to resolve input {input:?}: {err}"),
+        }
+    }
+
+    resolved
+}
+
+/// Writes the transaction to `out` together with the outputs it spends, if a
+/// path is given, and otherwise submits it.
+async fn write_or_submit(
+    transaction: Transaction,
+    client: &HttpClient,
+    out: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    match out {
+        Some(path) => {
+            let resolved_inputs = resolve_inputs(&transaction, client).await;
+            TxFile {
+                transaction,
+                resolved_inputs,
+            }
+            .write(&path)?;
+            println!("Transaction written to {}.", path.display());
+            Ok(())
+        }
+        None => submit_transaction(&transaction, client).await,
+    }
+}
+
+/// Value of the outputs spent by `transaction`, all of which must be known
+/// to the wallet.
+fn input_value(db: &Db, transaction: &Transaction) -> anyhow::Result<Value> {
+    let mut value = Value::Coin(0);
+    for input in &transaction.transaction_body.inputs {
+        let (_, amount, _) = sync::get_unspent(db, input)?
+            .ok_or(anyhow!("Input {input:?} not found in wallet database"))?;
+        value += amount;
+    }
+
+    Ok(value)
+}
+
+/// Balances `transaction` by spending outputs owned by keys in the keystore
+/// and sending the surplus to `change_address`. Returns the keys that must
+/// sign it, namely the given `witnesses` followed by the owners of the
+/// added inputs.
+async fn balance_with_keystore(
+    db: &Db,
+    client: &HttpClient,
+    keystore: &WalletKeystore,
+    transaction: &mut Transaction,
+    witnesses: &[H256],
+    change_address: &Address,
+    strategy: SelectionStrategy,
+) -> anyhow::Result<Vec<H256>> {
+    let input_value = input_value(db, transaction)?;
+    let keys = keystore.addresses()?;
+    let addresses: Vec<Address> = keys.iter().map(|(_, address)| address.clone()).collect();
+    let available = sync::get_outputs_at_any(db, &addresses)?;
+    let pps = node_get_protocol_parameters(client).await?;
+
+    let selected = balance_transaction(
+        transaction,
+        &input_value,
+        available,
+        change_address,
+        witnesses.len(),
+        &pps,
+        strategy,
+        &mut rand::rng(),
+    )?;
+
+    let mut signers: Vec<H256> = witnesses.to_vec();
+    for (key, address) in keys {
+        if !signers.contains(&key) && selected.iter().any(|output| output.address == address) {
+            signers.push(key);
+        }
+    }
+
+    Ok(signers)
+}
+
+/// Puts up as collateral of `transaction` the output with the most `Coin`s
+/// among those owned by keys in the keystore and not spent by it, returning
+/// its whole value for now (see `settle_collateral`). Returns the key owning
+/// the collateral, which must sign the transaction.
+fn put_up_collateral(
+    db: &Db,
+    keystore: &WalletKeystore,
+    transaction: &mut Transaction,
+) -> anyhow::Result<H256> {
+    let keys = keystore.addresses()?;
+    let addresses: Vec<Address> = keys.iter().map(|(_, address)| address.clone()).collect();
+    let collateral = sync::get_outputs_at_any(db, &addresses)?
+        .into_iter()
+        .filter(|output| !transaction.transaction_body.inputs.contains(&output.input))
+        .max_by_key(|output| output.value.coin_of())
+        .ok_or(anyhow!(
+            "No output owned by the keystore can be put up as collateral"
+        ))?;
+    let (key, _) = keys
+        .into_iter()
+        .find(|(_, address)| *address == collateral.address)
+        .ok_or(anyhow!("Collateral owner not found in keystore"))?;
+
+    transaction.transaction_body.collateral = Some(vec![collateral.input]);
+    transaction.transaction_body.collateral_return =
+        Some(Output::from((collateral.address, collateral.value)));
+
+    Ok(key)
+}
+
+/// Submits a transaction to the node and prints the references to the outputs
+/// it creates.
+async fn submit_transaction(transaction: &Transaction, client: &HttpClient) -> anyhow::Result<()> {
+    log::debug!("Griffin transaction is: {:#x?}", transaction);
+    log::debug!(
+        "Babbage transaction is: {:#x?}",
+        PallasTransaction::try_from(transaction.clone())
+    );
+
+    // Send the transaction
+    let genesis_spend_hex = hex::encode(Encode::encode(transaction));
+    let params = rpc_params![genesis_spend_hex];
+    let genesis_spend_response: Result<String, _> =
+        client.request("author_submitExtrinsic", params).await;
+    log::info!(
+        "Node's response to spend transaction: {:?}",
+        genesis_spend_response
+    );
+    if let Err(_) = genesis_spend_response {
+        Err(anyhow!(
+            "Node did not accept the transaction: {}",
+            rejection_reason(transaction, client).await
+        ))?;
+    } else {
+        println!("Transaction queued. When accepted, the following UTxOs will become available:");
+        // Print new output refs for user to check later
+        let tx_hash = <BlakeTwo256 as Hash>::hash_of(&Encode::encode(transaction));
+        for (i, output) in transaction.transaction_body.outputs.iter().enumerate() {
+            let new_value_ref = Input {
+                tx_hash,
+                index: i as u32,
+            };
+            let amount = &output.value;
+
+            println!(
+                "{:?} worth {amount:?}.",
+                hex::encode(Encode::encode(&new_value_ref))
+            );
+        }
+    }
+
+    Ok(())
 }
 
 /// Complete transaction builder.
 pub async fn build_tx(
     db: &Db,
     client: &HttpClient,
-    keystore: &LocalKeystore,
+    keystore: &WalletKeystore,
     args: BuildTxArgs,
 ) -> anyhow::Result<()> {
     log::debug!("The args are:: {:?}", args);
@@ -104,13 +404,34 @@ pub async fn build_tx(
     }
 
     let outputs_info: Vec<TransparentOutput> = tx_info.outputs_info;
-    let outputs: Vec<Output> = outputs_info.into_iter().map(transp_to_output).collect();
+    let outputs: Vec<Output> = outputs_info
+        .into_iter()
+        .map(transp_to_output)
+        .collect::<Result<_, _>>()
+        .map_err(|e| anyhow!(e))?;
 
     // Construct a template Transaction from the inputs and outputs
     let mut transaction = Transaction::from((inputs, outputs));
     transaction.transaction_body.required_signers = Some(tx_info.required_signers);
     transaction.transaction_body.validity_interval_start = tx_info.validity_interval_start;
     transaction.transaction_body.ttl = tx_info.ttl;
+    transaction.transaction_body.fee = tx_info.fee;
+    transaction.transaction_body.reference_inputs = tx_info.reference_inputs.map(|mut ris| {
+        // Lexicographically order reference inputs by tx_hash and index
+        ris.sort();
+        ris
+    });
+    transaction.transaction_body.collateral = tx_info.collateral.map(|mut cs| {
+        cs.sort();
+        cs
+    });
+    transaction.transaction_body.collateral_return = tx_info
+        .collateral_return
+        .map(transp_to_output)
+        .transpose()
+        .map_err(|e| anyhow!(e))?;
+    transaction.transaction_body.total_collateral = tx_info.total_collateral;
+    transaction.is_valid = tx_info.is_valid.unwrap_or(true);
 
     let mut mintings_info: Vec<MintingInfo> = Vec::new();
     if tx_info.mintings_info.is_some() {
@@ -123,24 +444,53 @@ pub async fn build_tx(
                 assets: mi.assets,
             })
             .collect::<Vec<TransparentMultiasset<i64>>>();
-        transaction.transaction_body.mint = Some(Multiasset::from(transp_to_multiasset(tmas)));
+        transaction.transaction_body.mint = Some(Multiasset::from(
+            transp_to_multiasset(tmas).map_err(|e| anyhow!(e))?,
+        ));
     }
 
-    let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
-    let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
-    let mtx: MintedTx = babbage_minted_tx_from_cbor(&cbor_bytes);
-    let tx_hash: &Vec<u8> = &Vec::from(mtx.transaction_body.original_hash().as_ref());
-    log::debug!("Original tx_body hash is: {:#x?}", tx_hash);
+    let ordered_withdrawals_info: Vec<(Address, WithdrawalInfo)> = {
+        let mut withdrawals_info = tx_info
+            .withdrawals_info
+            .unwrap_or_default()
+            .into_iter()
+            .map(|wi| Ok((Address::from_str(&wi.reward_account)?, wi)))
+            .collect::<Result<Vec<_>, AddressError>>()
+            .map_err(|e| anyhow!("Failed to decode reward account: {}", e))?;
+        withdrawals_info
+            .sort_by(|(a, _), (b, _)| reward_account_order(&a.0).cmp(&reward_account_order(&b.0)));
+        withdrawals_info
+    };
+    if !ordered_withdrawals_info.is_empty() {
+        transaction.transaction_body.withdrawals = Some(
+            ordered_withdrawals_info
+                .iter()
+                .map(|(account, wi)| (account.clone(), wi.amount))
+                .collect(),
+        );
+    }
 
-    let mut witnesses: Vec<VKeyWitness> = Vec::new();
-    for witness in &tx_info.witnesses {
-        let vkey: Vec<u8> = Vec::from(witness.0);
-        let public = Public::from_h256(*witness);
-        let signature: Vec<u8> =
-            Vec::from(crate::keystore::sign_with(keystore, &public, tx_hash)?.0);
-        witnesses.push(VKeyWitness::from((vkey, signature)));
+    if let Some(metadata) = &tx_info.metadata {
+        let aux_data = auxiliary_data_from_json(metadata)?;
+        transaction.transaction_body.auxiliary_data_hash =
+            Some(compute_auxiliary_data_hash(&aux_data));
+        transaction.auxiliary_data = Some(aux_data);
     }
-    transaction.transaction_witness_set = <_>::from(witnesses);
+
+    transaction.transaction_witness_set.native_script = tx_info.native_scripts;
+    transaction.transaction_witness_set.plutus_data = tx_info
+        .datums
+        .map(|datums| {
+            datums
+                .into_iter()
+                .map(|datum| match hex::decode(&datum).map(PlutusData) {
+                    Ok(data) if data.is_canonical() => Ok(data),
+                    Ok(_) => Err(anyhow!("Datum {} is not canonically encoded", datum)),
+                    Err(e) => Err(anyhow!("Failed to decode datum: {}", e)),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .transpose()?;
 
     if tx_info.scripts_info.is_some() {
         // Lexicographically order scripts by script_hex
@@ -151,7 +501,8 @@ pub async fn build_tx(
             .iter()
             .map(|si| {
                 if si.script_params_cbor.is_some() {
-                    PlutusScript(
+                    PlutusScript::new(
+                        si.version,
                         apply_params_to_script(
                             &hex::decode(si.script_params_cbor.clone().unwrap()).unwrap(),
                             &hex::decode(&si.script_hex).unwrap(),
@@ -159,13 +510,18 @@ pub async fn build_tx(
                         .unwrap(),
                     )
                 } else {
-                    PlutusScript(hex::decode(&si.script_hex).unwrap())
+                    PlutusScript::new(si.version, hex::decode(&si.script_hex).unwrap())
                 }
             })
             .collect();
         transaction.transaction_witness_set.plutus_script = Some(plutus_scripts);
     }
 
+    // Redeemers without execution units are given the ones spent by their
+    // scripts once the transaction is balanced. Until then they declare the
+    // per-transaction limit, which is never cheaper to encode.
+    let pps = node_get_protocol_parameters(client).await?;
+    let placeholder = pps.max_tx_ex_units.clone();
     let redeemers: Vec<Redeemer> = {
         // Lexicographically order mint info
         let ordered_mintings_info = {
@@ -181,6 +537,7 @@ pub async fn build_tx(
                 tag: RedeemerTag::Mint,
                 index: i as u32,
                 data: PlutusData(hex::decode(mi.redeemer_cbor.clone()).unwrap()),
+                ex_units: mi.ex_units.clone().unwrap_or(placeholder.clone()),
             })
             .collect();
 
@@ -193,6 +550,7 @@ pub async fn build_tx(
                         tag: RedeemerTag::Spend,
                         index: i as u32,
                         data: PlutusData(hex::decode(redeemer_cbor).unwrap()),
+                        ex_units: info.ex_units.clone().unwrap_or(placeholder.clone()),
                     })
                 } else {
                     None
@@ -200,62 +558,101 @@ pub async fn build_tx(
             })
             .collect();
 
+        let withdrawal_redeemers: Vec<Redeemer> = ordered_withdrawals_info
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, info))| {
+                info.redeemer_cbor.as_ref().map(|redeemer_cbor| Redeemer {
+                    tag: RedeemerTag::Reward,
+                    index: i as u32,
+                    data: PlutusData(hex::decode(redeemer_cbor).unwrap()),
+                    ex_units: info.ex_units.clone().unwrap_or(placeholder.clone()),
+                })
+            })
+            .collect();
+
         script_inputs_redeemers
             .into_iter()
             .chain(minting_redeemers.into_iter())
+            .chain(withdrawal_redeemers.into_iter())
             .collect()
     };
+    // Scripts are run only if collateral is put up, which is taken from the
+    // keystore when not given.
+    let mut witnesses: Vec<H256> = tx_info.witnesses;
+    let collateral_needed =
+        !redeemers.is_empty() && transaction.transaction_body.collateral.is_none();
+    let evaluation_needed = redeemers.iter().any(|r| r.ex_units == placeholder);
     if !redeemers.is_empty() {
         transaction.transaction_witness_set.redeemer = Some(redeemers);
     }
+    if collateral_needed {
+        let key = put_up_collateral(db, keystore, &mut transaction)?;
+        if !witnesses.contains(&key) {
+            witnesses.push(key);
+        }
+    }
 
-    log::debug!("Griffin transaction is: {:#x?}", transaction);
-    let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
-    log::debug!("Babbage transaction is: {:#x?}", pallas_tx);
-
-    // Send the transaction
-    let genesis_spend_hex = hex::encode(Encode::encode(&transaction));
-    let params = rpc_params![genesis_spend_hex];
-    let genesis_spend_response: Result<String, _> =
-        client.request("author_submitExtrinsic", params).await;
-    log::info!(
-        "Node's response to spend transaction: {:?}",
-        genesis_spend_response
-    );
-    if let Err(_) = genesis_spend_response {
-        Err(anyhow!("Node did not accept the transaction"))?;
-    } else {
-        println!("Transaction queued. When accepted, the following UTxOs will become available:");
-        // Print new output refs for user to check later
-        let tx_hash = <BlakeTwo256 as Hash>::hash_of(&Encode::encode(&transaction));
-        for (i, output) in transaction.transaction_body.outputs.iter().enumerate() {
-            let new_value_ref = Input {
-                tx_hash,
-                index: i as u32,
-            };
-            let amount = &output.value;
-
-            println!(
-                "{:?} worth {amount:?}.",
-                hex::encode(Encode::encode(&new_value_ref))
-            );
+    let witnesses: Vec<H256> = match &args.change_address {
+        Some(change_address) => {
+            balance_with_keystore(
+                db,
+                client,
+                keystore,
+                &mut transaction,
+                &witnesses,
+                change_address,
+                args.strategy,
+            )
+            .await?
         }
+        None => {
+            // Balance can only be checked when all inputs are known
+            if let Ok(input_value) = input_value(db, &transaction) {
+                if !is_balanced(&transaction, &input_value) {
+                    Err(anyhow!(
+                        "The transaction is not balanced. Use `--change-address` to balance it."
+                    ))?;
+                }
+            }
+            if collateral_needed {
+                settle_collateral(&mut transaction, &pps)?;
+            }
+            let required_fee = min_fee(&transaction, witnesses.len(), &pps)?;
+            if transaction.transaction_body.fee < required_fee {
+                Err(anyhow!(
+                    "The fee is below the minimum of {required_fee} Coins. Use `--change-address` to balance the transaction."
+                ))?;
+            }
+            witnesses
+        }
+    };
+    // The fee was computed with placeholder execution units, namely the limit
+    // of the transaction, which are never encoded in fewer bytes than the
+    // evaluated ones, so it still covers the transaction afterwards.
+    if evaluation_needed {
+        node_set_evaluated_ex_units(&mut transaction, &placeholder, client).await?;
+    }
+    if args.unsigned {
+        println!("Keys that must sign: {}", display_keys(&witnesses));
+    } else {
+        transaction.transaction_witness_set.vkeywitness =
+            Some(sign_transaction(keystore, &transaction, &witnesses)?);
     }
 
-    Ok(())
+    write_or_submit(transaction, client, args.out).await
 }
 
-/// Create and submit a transaction that spends `Value`. Any surplus from inputs
-/// is sent to Shawn's address.
-///
-/// Local checks of balance are omitted.
+/// Create and submit a transaction that spends `Value`. Inputs are selected
+/// from the outputs owned by keys in the keystore, on top of those given, and
+/// any surplus is sent back to the change address.
 ///
 /// This command can be replaced with `build_tx` command, but we leave it here
 /// since it is simpler for the case of spending wallet inputs.
 pub async fn spend_value(
     db: &Db,
     client: &HttpClient,
-    keystore: &LocalKeystore,
+    keystore: &WalletKeystore,
     args: SpendValueArgs,
 ) -> anyhow::Result<()> {
     log::debug!("The args are:: {:?}", args);
@@ -282,19 +679,6 @@ pub async fn spend_value(
     // Construct a template Transaction to push coins into later
     let mut transaction = Transaction::from((Vec::new(), Vec::new()));
 
-    // Total amount in inputs
-    let mut input_value: Value = Value::Coin(0);
-    for input in &args.input {
-        if let Some((_owner_pubkey, amount, _)) = sync::get_unspent(db, input)? {
-            input_value += amount;
-        } else {
-            log::info!(
-                "Warning: User-specified utxo {:x?} not found in wallet database",
-                input
-            );
-        }
-    }
-
     let coin_amount = args.amount.unwrap_or(0);
     // Total amount in outputs
     let mut output_value: Value = Value::Coin(coin_amount);
@@ -327,73 +711,415 @@ pub async fn spend_value(
     let output = Output::from((args.recipient.clone(), output_value.clone()));
     transaction.transaction_body.outputs.push(output);
 
-    // If the supplied inputs surpass output amount, we redirect the rest to Shawn
-    if value_leq(&output_value, &input_value) {
-        let remainder: Value = input_value - output_value;
-        if !remainder.is_null() {
-            println!("Note: Excess input amount goes to Shawn.");
-            let output = Output::from((address_from_hex(SHAWN_ADDRESS), remainder));
-            transaction.transaction_body.outputs.push(output);
+    // The given fee is raised to the minimum one while balancing
+    transaction.transaction_body.fee = args.fee;
+    transaction.transaction_body.inputs = args.input.clone();
+
+    let change_address: Address = match args.change_address {
+        Some(address) => address,
+        None => {
+            let witness = args.witness.first().ok_or(anyhow!(
+                "Missing witness to derive the change address from."
+            ))?;
+            keystore
+                .addresses()?
+                .into_iter()
+                .find(|(key, _)| key == witness)
+                .map_or_else(
+                    || address_from_pk(&Public::from_h256(*witness)),
+                    |(_, address)| address,
+                )
         }
+    };
+    let witnesses = balance_with_keystore(
+        db,
+        client,
+        keystore,
+        &mut transaction,
+        &args.witness,
+        &change_address,
+        args.strategy,
+    )
+    .await?;
+    if args.unsigned {
+        println!("Keys that must sign: {}", display_keys(&witnesses));
+    } else {
+        transaction.transaction_witness_set =
+            <_>::from(sign_transaction(keystore, &transaction, &witnesses)?);
     }
 
-    // Push each input to the transaction.
-    for input in &args.input {
-        transaction.transaction_body.inputs.push(input.clone());
+    write_or_submit(transaction, client, args.out).await
+}
+
+/// Comma-separated hex-encoded keys.
+fn display_keys(keys: &[H256]) -> String {
+    keys.iter()
+        .map(|key| format!("0x{}", hex::encode(key)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Auxiliary data holding the metadata given in JSON, as an object mapping
+/// labels to metadatum values. Values are mapped as follows: integers to
+/// integers, strings starting with `0x` to bytes, other strings to text,
+/// arrays to lists and objects to maps with text keys.
+fn auxiliary_data_from_json(json: &serde_json::Value) -> anyhow::Result<AuxiliaryData> {
+    let labels = json.as_object().ok_or(anyhow!(
+        "Metadata must be an object mapping labels to values."
+    ))?;
+    let mut metadata: Vec<(MetadatumLabel, Metadatum)> = Vec::new();
+    for (label, value) in labels {
+        let label: MetadatumLabel = label
+            .parse()
+            .map_err(|_| anyhow!("Invalid metadata label {label}."))?;
+        metadata.push((label, metadatum_from_json(value)?));
     }
 
-    let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
-    let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
-    let mtx: MintedTx = babbage_minted_tx_from_cbor(&cbor_bytes);
-    let tx_hash: &Vec<u8> = &Vec::from(mtx.transaction_body.original_hash().as_ref());
-    log::debug!("Original tx_body hash is: {:#x?}", tx_hash);
+    let mut bytes: Vec<u8> = Vec::new();
+    minicbor::encode(
+        PallasAuxiliaryData::Shelley(KeyValuePairs::from(metadata)),
+        &mut bytes,
+    )
+    .map_err(|e| anyhow!("Failed to encode metadata: {}", e))?;
 
-    let mut witnesses: Vec<VKeyWitness> = Vec::new();
-    for witness in &args.witness {
-        let vkey: Vec<u8> = Vec::from(witness.0);
-        let public = Public::from_h256(*witness);
-        let signature: Vec<u8> =
-            Vec::from(crate::keystore::sign_with(keystore, &public, tx_hash)?.0);
-        witnesses.push(VKeyWitness::from((vkey, signature)));
+    Ok(AuxiliaryData(bytes))
+}
+
+/// Longest text or byte string allowed in metadata.
+const MAX_METADATUM_STRING_LEN: usize = 64;
+
+fn metadatum_from_json(json: &serde_json::Value) -> anyhow::Result<Metadatum> {
+    use serde_json::Value as Json;
+
+    let metadatum = match json {
+        Json::Number(n) => Metadatum::Int(
+            n.as_i64()
+                .ok_or(anyhow!("Metadata number {n} is not a 64-bit integer."))?
+                .into(),
+        ),
+        Json::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => Metadatum::Bytes(
+                hex::decode(hex)
+                    .map_err(|e| anyhow!("Invalid metadata bytes {s}: {}", e))?
+                    .into(),
+            ),
+            None => Metadatum::Text(s.clone()),
+        },
+        Json::Array(values) => Metadatum::Array(
+            values
+                .iter()
+                .map(metadatum_from_json)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Json::Object(entries) => Metadatum::Map(KeyValuePairs::from(
+            entries
+                .iter()
+                .map(|(k, v)| Ok((Metadatum::Text(k.clone()), metadatum_from_json(v)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        )),
+        Json::Bool(_) | Json::Null => Err(anyhow!("Metadata cannot hold {json}."))?,
+    };
+    match &metadatum {
+        Metadatum::Text(s) if s.len() > MAX_METADATUM_STRING_LEN => Err(anyhow!(
+            "Metadata text {s} is longer than {MAX_METADATUM_STRING_LEN} bytes."
+        )),
+        Metadatum::Bytes(b) if b.len() > MAX_METADATUM_STRING_LEN => Err(anyhow!(
+            "Metadata bytes are longer than {MAX_METADATUM_STRING_LEN} bytes."
+        )),
+        _ => Ok(metadatum),
     }
-    transaction.transaction_witness_set = <_>::from(witnesses);
+}
 
-    log::debug!("Griffin transaction is: {:#x?}", transaction);
-    let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
-    log::debug!("Babbage transaction is: {:#x?}", pallas_tx);
+/// The native script requiring the signatures and the validity interval given
+/// in `args`.
+fn native_script_from_args(args: &NativeScriptAddressArgs) -> anyhow::Result<NativeScript> {
+    let signers: Vec<NativeScript> = args
+        .signer
+        .iter()
+        .map(|pk| NativeScript::ScriptPubkey(H224::from(PallasHasher::<224>::hash(&pk.0))))
+        .collect();
+    let required = args.required.unwrap_or(signers.len() as u32);
+    if required as usize > signers.len() {
+        Err(anyhow!(
+            "Cannot require {required} signatures from {} signers.",
+            signers.len()
+        ))?;
+    }
 
-    // Send the transaction
-    let genesis_spend_hex = hex::encode(Encode::encode(&transaction));
-    let params = rpc_params![genesis_spend_hex];
-    let genesis_spend_response: Result<String, _> =
-        client.request("author_submitExtrinsic", params).await;
-    log::info!(
-        "Node's response to spend transaction: {:?}",
-        genesis_spend_response
+    let mut scripts: Vec<NativeScript> = Vec::new();
+    if required > 0 {
+        scripts.push(if required as usize == signers.len() {
+            NativeScript::ScriptAll(signers)
+        } else if required == 1 {
+            NativeScript::ScriptAny(signers)
+        } else {
+            NativeScript::ScriptNOfK(required, signers)
+        });
+    }
+    if let Some(slot) = args.after {
+        scripts.push(NativeScript::InvalidBefore(slot));
+    }
+    if let Some(slot) = args.before {
+        scripts.push(NativeScript::InvalidHereafter(slot));
+    }
+
+    match scripts.len() {
+        0 => Err(anyhow!("The script needs some signer or slot bound.")),
+        1 => Ok(scripts.remove(0)),
+        _ => Ok(NativeScript::ScriptAll(scripts)),
+    }
+}
+
+/// Shows a native script together with its hash and the address it locks, as
+/// on a chain with network ID `network_id`.
+pub fn native_script_address(args: NativeScriptAddressArgs, network_id: u8) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let script = native_script_from_args(&args)?;
+    let script_json = serde_json::to_string(&script)?;
+    if let Some(path) = &args.out {
+        std::fs::write(path, &script_json)?;
+    }
+
+    println!("Script: {script_json}");
+    println!(
+        "Script hash: 0x{}",
+        hex::encode(compute_native_script_hash(script.clone()))
     );
-    if let Err(_) = genesis_spend_response {
-        Err(anyhow!("Node did not accept the transaction"))?;
+    println!(
+        "Address: {}",
+        address_from_native_script(script).to_string_on(network_id)
+    );
+
+    Ok(())
+}
+
+/// Adds signatures from the keystore to the transaction stored in a file, and
+/// either submits the transaction or writes it back for other parties to sign.
+pub async fn co_sign(
+    client: &HttpClient,
+    keystore: &WalletKeystore,
+    args: CoSignArgs,
+) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let mut tx_file = TxFile::read(&args.tx)?;
+    let transaction = &mut tx_file.transaction;
+    let new_keys: Vec<H256> = args
+        .witness
+        .into_iter()
+        .filter(|key| {
+            !transaction
+                .transaction_witness_set
+                .vkeywitness
+                .iter()
+                .flatten()
+                .any(|w| w.vkey == key.0)
+        })
+        .collect();
+    let new_witnesses = sign_transaction(keystore, transaction, &new_keys)?;
+    add_witnesses(transaction, new_witnesses);
+
+    if args.submit {
+        submit_transaction(transaction, client).await
     } else {
-        println!("Transaction queued. When accepted, the following UTxOs will become available:");
-        // Print new output refs for user to check later
-        let tx_hash = <BlakeTwo256 as Hash>::hash_of(&Encode::encode(&transaction));
-        for (i, output) in transaction.transaction_body.outputs.iter().enumerate() {
-            let new_value_ref = Input {
-                tx_hash,
-                index: i as u32,
-            };
-            let amount = &output.value;
+        tx_file.write(&args.tx)?;
+        println!(
+            "Added {} signatures to {}.",
+            new_keys.len(),
+            args.tx.display()
+        );
+        Ok(())
+    }
+}
 
-            println!(
-                "{:?} worth {amount:?}.",
-                hex::encode(Encode::encode(&new_value_ref))
-            );
+/// Signs the transaction stored in a file with a key from the keystore, and
+/// writes the signature to a witness file, leaving the transaction untouched.
+pub fn witness_tx(keystore: &WalletKeystore, args: WitnessTxArgs) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let tx_file = TxFile::read(&args.tx)?;
+    let witness = sign_transaction(keystore, &tx_file.transaction, &[args.witness])?.remove(0);
+    tx_file::write_witness(&args.out, &witness)?;
+    println!("Witness written to {}.", args.out.display());
+
+    Ok(())
+}
+
+/// Adds the signatures in witness files, or in other copies of the
+/// transaction, to the transaction stored in a file. Either submits the
+/// result or writes it to a file.
+pub async fn assemble_tx(client: &HttpClient, args: AssembleTxArgs) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let mut tx_file = TxFile::read(&args.tx)?;
+    let tx_hash = tx_body_hash(&tx_file.transaction)?;
+    let mut added = 0;
+    for path in &args.witness_file {
+        let witnesses = tx_file::read_witnesses(path)?;
+        if let Some(witness) = witnesses.iter().find(|w| !is_valid_witness(w, &tx_hash)) {
+            Err(anyhow!(
+                "Witness of key 0x{} in {} does not sign this transaction.",
+                hex::encode(&witness.vkey),
+                path.display()
+            ))?;
         }
+        added += add_witnesses(&mut tx_file.transaction, witnesses);
+    }
+    println!("Added {added} signatures.");
+
+    if args.submit {
+        submit_transaction(&tx_file.transaction, client).await
+    } else {
+        let path = args.out.unwrap_or(args.tx);
+        tx_file.write(&path)?;
+        println!("Transaction written to {}.", path.display());
+        Ok(())
+    }
+}
+
+/// Shows the contents of the transaction stored in a file, checking its
+/// signatures and listing those missing. Addresses are shown as on a chain
+/// with network ID `network_id`.
+pub fn inspect_tx(db: &Db, args: InspectTxArgs, network_id: u8) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let tx_file = TxFile::read(&args.tx)?;
+    let transaction = &tx_file.transaction;
+    let body = &transaction.transaction_body;
+    let tx_hash = tx_body_hash(transaction)?;
+    // Spent outputs are looked up in the wallet database when not in the file.
+    let resolve = |input: &Input| -> Option<(Address, Value)> {
+        match tx_file.resolve(input) {
+            Some(output) => Some((output.address.clone(), output.value.clone())),
+            None => sync::get_unspent(db, input)
+                .ok()
+                .flatten()
+                .map(|(address, value, _)| (address, value)),
+        }
+    };
+    let show_inputs = |title: &str, inputs: &[Input]| {
+        if inputs.is_empty() {
+            return;
+        }
+        println!("{title}:");
+        for input in inputs {
+            match resolve(input) {
+                Some((address, value)) => println!(
+                    "  {}: owner address {}, amount: {}",
+                    hex::encode(input.encode()),
+                    address.to_string_on(network_id),
+                    value.normalize()
+                ),
+                None => println!("  {}: unknown output", hex::encode(input.encode())),
+            }
+        }
+    };
+
+    println!("Transaction body hash: 0x{}", hex::encode(&tx_hash));
+    show_inputs("Inputs", &body.inputs);
+    show_inputs(
+        "Reference inputs",
+        body.reference_inputs.as_deref().unwrap_or_default(),
+    );
+    show_inputs("Collateral", body.collateral.as_deref().unwrap_or_default());
+    println!("Outputs:");
+    for output in body.outputs.iter().chain(&body.collateral_return) {
+        println!(
+            "  owner address {}, datum {:?}, amount: {}",
+            output.address.to_string_on(network_id),
+            output.datum_option,
+            output.value.normalize()
+        );
+    }
+    println!("Fee: {} Coins", body.fee);
+    if let Some(mint) = &body.mint {
+        println!("Mint:\n{mint}");
+    }
+    for (account, amount) in body.withdrawals.iter().flatten() {
+        println!(
+            "Withdrawal: {amount} Coins from {}",
+            account.to_string_on(network_id)
+        );
+    }
+    if body.validity_interval_start.is_some() || body.ttl.is_some() {
+        println!(
+            "Validity interval: from slot {:?} to slot {:?}",
+            body.validity_interval_start, body.ttl
+        );
+    }
+
+    let witnesses = transaction
+        .transaction_witness_set
+        .vkeywitness
+        .clone()
+        .unwrap_or_default();
+    println!("Signatures:");
+    for witness in &witnesses {
+        println!(
+            "  0x{}: {}",
+            hex::encode(&witness.vkey),
+            if is_valid_witness(witness, &tx_hash) {
+                "valid"
+            } else {
+                "INVALID"
+            }
+        );
+    }
+    let signed: Vec<H224> = witnesses
+        .iter()
+        .map(|w| H224::from(PallasHasher::<224>::hash(&w.vkey)))
+        .collect();
+    let mut missing: Vec<H224> = Vec::new();
+    for key_hash in required_key_hashes(transaction, |input| resolve(input).map(|(a, _)| a)) {
+        if !signed.contains(&key_hash) && !missing.contains(&key_hash) {
+            missing.push(key_hash);
+        }
+    }
+    for key_hash in missing {
+        println!("Missing signature of key hash 0x{}", hex::encode(key_hash));
     }
 
     Ok(())
 }
 
+/// Hashes of the keys that must sign `transaction`: required signers, owners
+/// of the spent outputs and the collateral locked by keys, and key reward
+/// accounts being withdrawn from. Owners of outputs that cannot be
+/// `resolve`d are left out.
+fn required_key_hashes(
+    transaction: &Transaction,
+    resolve: impl Fn(&Input) -> Option<Address>,
+) -> Vec<H224> {
+    let body = &transaction.transaction_body;
+    let mut key_hashes: Vec<H224> = body.required_signers.clone().unwrap_or_default();
+    for input in body.inputs.iter().chain(body.collateral.iter().flatten()) {
+        if let Some(PallasAddress::Shelley(address)) =
+            resolve(input).and_then(|address| PallasAddress::from_bytes(&address.0).ok())
+        {
+            if let ShelleyPaymentPart::Key(hash) = address.payment() {
+                key_hashes.push(H224::from(*hash));
+            }
+        }
+    }
+    for (account, _) in body.withdrawals.iter().flatten() {
+        if let (_, true, hash) = reward_account_order(&account.0) {
+            if hash.len() == 28 {
+                key_hashes.push(H224::from_slice(hash));
+            }
+        }
+    }
+
+    key_hashes
+}
+
+/// Submits the transaction stored in a file.
+pub async fn submit_tx(client: &HttpClient, args: SubmitTxArgs) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    submit_transaction(&TxFile::read(&args.tx)?.transaction, client).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -411,7 +1137,8 @@ mod tests {
         TransactionInput, TransactionOutput,
     };
     use griffin_core::types::{
-        Address, Datum, Input, Output, PlutusData, PlutusScript, Redeemer, RedeemerTag, Value,
+        Address, Datum, DatumOption, ExUnits, Input, Output, PlutusData, PlutusScript,
+        PlutusVersion, Redeemer, RedeemerTag, Value,
     };
     use griffin_core::uplc::tx::{eval_phase_two, ResolvedInput, SlotConfig};
     use sp_core::H256;
@@ -433,8 +1160,8 @@ mod tests {
         };
         use griffin_core::pallas_primitives::Fragment;
         use griffin_core::types::{
-            compute_plutus_v2_script_hash, Address, AssetName, Input, Multiasset, Output,
-            PlutusData, PlutusScript, Redeemer, RedeemerTag, Value,
+            compute_plutus_script_hash, Address, AssetName, ExUnits, Input, Multiasset, Output,
+            PlutusData, PlutusScript, PlutusVersion, Redeemer, RedeemerTag, Value,
         };
         use griffin_core::uplc::tx::{
             apply_params_to_script, eval_phase_two, ResolvedInput, SlotConfig,
@@ -476,14 +1203,15 @@ mod tests {
             .to_vec(),
         ));
 
-        let script = PlutusScript(
+        let script = PlutusScript::new(
+            PlutusVersion::V2,
             apply_params_to_script(
                 utxo_ref_data.encode_fragment().unwrap().as_slice(),
                 hex::decode(parameterized_script_hex).unwrap().as_slice(),
             )
             .unwrap(),
         );
-        let policy = compute_plutus_v2_script_hash(script.clone());
+        let policy = compute_plutus_script_hash(script.clone());
 
         let sender_payment_hash = H224::from(
             Hash::from_str("5b6de1be218ebb35fc08b2983e3a1d72aec969c8d2a6301212e2ea9a").unwrap(),
@@ -496,6 +1224,7 @@ mod tests {
             ),
             value: Value::Coin(10),
             datum_option: None,
+            script_ref: None,
         }];
 
         let pallas_inputs = inputs
@@ -504,7 +1233,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -520,6 +1249,7 @@ mod tests {
                 any_constructor: None,
                 fields: Def([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
         let mint = Some(Multiasset::from((
             policy,
@@ -531,7 +1261,7 @@ mod tests {
         transaction.transaction_witness_set.redeemer = Some(vec![mint_redeemer]);
         transaction.transaction_witness_set.plutus_script = Some(vec![script]);
 
-        let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
+        let pallas_tx = PallasTransaction::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -569,14 +1299,14 @@ mod tests {
             TransactionInput, TransactionOutput,
         };
         use griffin_core::types::{
-            compute_plutus_v2_script_hash, Address, Datum, Input, Output, PlutusData, PlutusScript,
-            Redeemer, RedeemerTag, VKeyWitness, Value,
+            compute_plutus_script_hash, Address, Datum, ExUnits, Input, Output, PlutusData,
+            PlutusScript, PlutusVersion, Redeemer, RedeemerTag, VKeyWitness, Value,
         };
         use griffin_core::uplc::tx::{eval_phase_two, ResolvedInput, SlotConfig};
         use sp_core::H256;
 
-        let script = PlutusScript(hex::decode("58f2010000323232323232323222232325333008323232533300b002100114a06644646600200200644a66602200229404c8c94ccc040cdc78010028a511330040040013014002375c60240026eb0c038c03cc03cc03cc03cc03cc03cc03cc03cc020c008c020014dd71801180400399b8f375c6002600e00a91010d48656c6c6f2c20576f726c6421002300d00114984d958c94ccc020cdc3a400000226464a66601a601e0042930b1bae300d00130060041630060033253330073370e900000089919299980618070010a4c2c6eb8c030004c01401058c01400c8c014dd5000918019baa0015734aae7555cf2ab9f5742ae881").unwrap());
-        let script_hash = compute_plutus_v2_script_hash(script.clone());
+        let script = PlutusScript::new(PlutusVersion::V2, hex::decode("58f2010000323232323232323222232325333008323232533300b002100114a06644646600200200644a66602200229404c8c94ccc040cdc78010028a511330040040013014002375c60240026eb0c038c03cc03cc03cc03cc03cc03cc03cc03cc020c008c020014dd71801180400399b8f375c6002600e00a91010d48656c6c6f2c20576f726c6421002300d00114984d958c94ccc020cdc3a400000226464a66601a601e0042930b1bae300d00130060041630060033253330073370e900000089919299980618070010a4c2c6eb8c030004c01401058c01400c8c014dd5000918019baa0015734aae7555cf2ab9f5742ae881").unwrap());
+        let script_hash = compute_plutus_script_hash(script.clone());
 
         let owner = H224::from(
             Hash::from_str("5b6de1be218ebb35fc08b2983e3a1d72aec969c8d2a6301212e2ea9a").unwrap(),
@@ -604,7 +1334,10 @@ mod tests {
         let resolved_inputs = vec![Output {
             address: Address(hex::decode("70".to_owned() + &hex::encode(script_hash)).unwrap()),
             value: Value::Coin(10),
-            datum_option: Some(Datum(PlutusData::from(datum.clone()).0)),
+            datum_option: Some(DatumOption::Inline(Datum(
+                PlutusData::from(datum.clone()).0,
+            ))),
+            script_ref: None,
         }];
 
         let pallas_inputs = inputs
@@ -613,7 +1346,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|ri| TransactionOutput::from(ri.clone()))
+            .map(|ri| TransactionOutput::try_from(ri.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let redeemer = Redeemer {
@@ -629,6 +1362,7 @@ mod tests {
                     .to_vec(),
                 ),
             })),
+            ex_units: ExUnits::default(),
         };
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -645,7 +1379,7 @@ mod tests {
         transaction.transaction_witness_set.redeemer = Some(vec![redeemer]);
         transaction.transaction_witness_set.plutus_script = Some(vec![script]);
 
-        let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
+        let pallas_tx = PallasTransaction::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -740,7 +1474,8 @@ mod tests {
                     hex::decode("7089A87CE851285C4FEBAD4AAC83F1B1D9A25E72B3342F6C5FE0B27A8F").unwrap(),
                 ),
                 value: Value::Coin(314),
-                datum_option: Some(Datum(PlutusData::from(datum.clone()).0)),
+                datum_option: Some(DatumOption::Inline(Datum(PlutusData::from(datum.clone()).0))),
+                script_ref: None,
             },
             Output {
                 address: Address(
@@ -748,6 +1483,7 @@ mod tests {
                 ),
                 value: Value::Coin(3),
                 datum_option: None,
+                script_ref: None,
             },
         ];
 
@@ -757,7 +1493,7 @@ mod tests {
             .collect::<Vec<_>>();
         let pallas_resolved_inputs = resolved_inputs
             .iter()
-            .map(|o| TransactionOutput::from(o.clone()))
+            .map(|o| TransactionOutput::try_from(o.clone()).unwrap())
             .collect::<Vec<_>>();
 
         let script = hex::decode("590b2d0100003232323322323233223232323232323233223233223232323232323232333222323232322323222323253353232323253355335323235002222222222222533533355301a12001321233001225335002210031001002502c25335333573466e3c0380040ec0e84d40b8004540b4010840ec40e4d401488009400440b04cd5ce2491f62656e65666963696172792773207369676e6174757265206d697373696e670002b15335323232350022235002223500522350022253335333501900b00600215335001153350051333501800b00300710361333501800b00300710361333501800b00300735500322222222222200533501433501635029350052200102d335015502802d123333333300122333573466e1c0080040bc0b8894cd4ccd5cd19b8700200102f02e101515335333573466e240080040bc0b8404c405088ccd5cd19b8800200102f02e22333573466e240080040bc0b888ccd5cd19b8900200102e02f22333573466e200080040b80bc894cd4ccd5cd19b8900200102f02e10011002225335333573466e240080040bc0b84008400440b04cd5ce248114646561646c696e65206e6f7420726561636865640002b102b135001220023333573466e1cd55cea80224000466442466002006004646464646464646464646464646666ae68cdc39aab9d500c480008cccccccccccc88888888888848cccccccccccc00403403002c02802402001c01801401000c008cd408c090d5d0a80619a8118121aba1500b33502302535742a014666aa04eeb94098d5d0a804999aa813bae502635742a01066a0460606ae85401cccd5409c0c5d69aba150063232323333573466e1cd55cea80124000466a0486464646666ae68cdc39aab9d5002480008cd40a8cd40edd69aba15002303e357426ae8940088c98c8100cd5ce02182101f09aab9e5001137540026ae854008c8c8c8cccd5cd19b8735573aa0049000119a81499a81dbad35742a004607c6ae84d5d1280111931902019ab9c04304203e135573ca00226ea8004d5d09aba2500223263203c33573807e07c07426aae7940044dd50009aba1500533502375c6ae854010ccd5409c0b48004d5d0a801999aa813bae200135742a004605e6ae84d5d1280111931901c19ab9c03b03a036135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d5d1280089aba25001135744a00226ae8940044d55cf280089baa00135742a008603e6ae84d5d1280211931901519ab9c02d02c0283333573466e1cd55ce9baa0054800080ac8c98c80a4cd5ce0160158139999ab9a3370e6aae7540192000233221233001003002375c6ae854018dd69aba135744a00c464c6405066ae700ac0a809840a44c98c809ccd5ce2490350543500029135573ca00226ea80044d55cf280089baa00132001355023221122253350011350032200122133350052200230040023335530071200100500400112223500222350032253335333500800700400215335003100110261025102612223232323253335006215333500621533350082130044984c00d261533350072130044984c00d26100d100b1533350072130044984c00d261533350062130044984c00d26100c1533350052100a100b100915333500521533350072130054984c011261533350062130054984c01126100c100a1533350062130054984c011261533350052130054984c01126100b2533350052153335007215333500721333500b00a002001161616100b153335006215333500621333500a009002001161616100a10092533350042153335006215333500621333500a009002001161616100a1533350052153335005213335009008002001161616100910082533350032153335005215333500521333500900800200116161610091533350042153335004213335008007002001161616100810072533350022153335004215333500421333500800700200116161610081533350032153335003213335007006002001161616100710061235001222222220071222003122200212220011221233001003002122123300100300212212330010030021232230023758002640026aa034446666aae7c004940288cd4024c010d5d080118019aba200201a232323333573466e1cd55cea80124000466442466002006004601c6ae854008c014d5d09aba2500223263201833573803603402c26aae7940044dd50009191919191999ab9a3370e6aae75401120002333322221233330010050040030023232323333573466e1cd55cea80124000466442466002006004602e6ae854008cd403c058d5d09aba2500223263201d33573804003e03626aae7940044dd50009aba150043335500875ca00e6ae85400cc8c8c8cccd5cd19b875001480108c84888c008010d5d09aab9e500323333573466e1d4009200223212223001004375c6ae84d55cf280211999ab9a3370ea00690001091100191931900f99ab9c02202101d01c01b135573aa00226ea8004d5d0a80119a805bae357426ae8940088c98c8064cd5ce00e00d80b89aba25001135744a00226aae7940044dd5000899aa800bae75a224464460046eac004c8004d5405c88c8cccd55cf80112804119a8039991091980080180118031aab9d5002300535573ca00460086ae8800c0604d5d080088910010910911980080200189119191999ab9a3370ea002900011a80398029aba135573ca00646666ae68cdc3a801240044a00e464c6402866ae7005c0580480444d55cea80089baa0011212230020031122001232323333573466e1d400520062321222230040053007357426aae79400c8cccd5cd19b875002480108c848888c008014c024d5d09aab9e500423333573466e1d400d20022321222230010053007357426aae7940148cccd5cd19b875004480008c848888c00c014dd71aba135573ca00c464c6402466ae7005405004003c0380344d55cea80089baa001232323333573466e1cd55cea80124000466442466002006004600a6ae854008dd69aba135744a004464c6401c66ae700440400304d55cf280089baa0012323333573466e1cd55cea800a400046eb8d5d09aab9e500223263200c33573801e01c01426ea80048c8c8c8c8c8cccd5cd19b8750014803084888888800c8cccd5cd19b875002480288488888880108cccd5cd19b875003480208cc8848888888cc004024020dd71aba15005375a6ae84d5d1280291999ab9a3370ea00890031199109111111198010048041bae35742a00e6eb8d5d09aba2500723333573466e1d40152004233221222222233006009008300c35742a0126eb8d5d09aba2500923333573466e1d40192002232122222223007008300d357426aae79402c8cccd5cd19b875007480008c848888888c014020c038d5d09aab9e500c23263201533573803002e02602402202001e01c01a26aae7540104d55cf280189aab9e5002135573ca00226ea80048c8c8c8c8cccd5cd19b875001480088ccc888488ccc00401401000cdd69aba15004375a6ae85400cdd69aba135744a00646666ae68cdc3a80124000464244600400660106ae84d55cf280311931900719ab9c01101000c00b135573aa00626ae8940044d55cf280089baa001232323333573466e1d400520022321223001003375c6ae84d55cf280191999ab9a3370ea004900011909118010019bae357426aae7940108c98c802ccd5ce00700680480409aab9d50011375400224464646666ae68cdc3a800a40084a00c46666ae68cdc3a8012400446a010600c6ae84d55cf280211999ab9a3370ea00690001091100111931900619ab9c00f00e00a009008135573aa00226ea8004484888c00c010448880048c8cccd5cd19b8750014800880188cccd5cd19b8750024800080188c98c8018cd5ce00480400200189aab9d37540029309100109100089000a490350543100112323001001223300330020020011").unwrap();
@@ -769,6 +1505,7 @@ mod tests {
                 any_constructor: None,
                 fields: Def([].to_vec()),
             })),
+            ex_units: ExUnits::default(),
         };
 
         let mut transaction = Transaction::from((Vec::new(), Vec::new()));
@@ -782,8 +1519,9 @@ mod tests {
         transaction.transaction_body.required_signers = Some(vec![sign]);
         transaction.transaction_body.validity_interval_start = Some(82651727);
         transaction.transaction_witness_set.redeemer = Some(vec![redeemer]);
-        transaction.transaction_witness_set.plutus_script = Some(vec![PlutusScript(script)]);
-        let pallas_tx: PallasTransaction = <_>::from(transaction.clone());
+        transaction.transaction_witness_set.plutus_script =
+            Some(vec![PlutusScript::new(PlutusVersion::V2, script)]);
+        let pallas_tx = PallasTransaction::try_from(transaction.clone()).unwrap();
         let cbor_bytes: Vec<u8> = babbage_tx_to_cbor(&pallas_tx);
         let mtx: ConwayMintedTx = conway_minted_tx_from_cbor(&cbor_bytes);
 
@@ -806,4 +1544,94 @@ mod tests {
             eval_phase_two(&mtx, &input_utxos, None, None, &slot_config, false, |_| ()).unwrap();
         assert_eq!(redeemers.len(), 1);
     }
+
+    #[test]
+    fn test_native_script_from_args() {
+        use crate::{keystore::SHAWN_PUB_KEY, utils::h256_from_string};
+
+        let shawn = h256_from_string(SHAWN_PUB_KEY).unwrap();
+        let shawn_hash = H224::from(PallasHasher::<224>::hash(&shawn.0));
+        let args = NativeScriptAddressArgs {
+            signer: vec![shawn, H256::repeat_byte(1)],
+            required: Some(1),
+            after: Some(100),
+            before: None,
+            out: None,
+        };
+
+        let script = native_script_from_args(&args).unwrap();
+        match &script {
+            NativeScript::ScriptAll(scripts) => {
+                assert_eq!(scripts.len(), 2);
+                assert!(matches!(
+                    &scripts[0],
+                    NativeScript::ScriptAny(signers)
+                        if signers[0] == NativeScript::ScriptPubkey(shawn_hash)
+                ));
+                assert_eq!(scripts[1], NativeScript::InvalidBefore(100));
+            }
+            other => panic!("unexpected script: {other:?}"),
+        }
+        assert_eq!(address_from_native_script(script).0[0], 0x71);
+
+        let args = NativeScriptAddressArgs {
+            required: Some(3),
+            ..args
+        };
+        assert!(native_script_from_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_auxiliary_data_from_json() {
+        let metadata = serde_json::json!({
+            "1": -3,
+            "674": { "msg": ["Ship minted", "0x00ff"] },
+        });
+
+        let aux_data = auxiliary_data_from_json(&metadata).unwrap();
+        let decoded: PallasAuxiliaryData = minicbor::decode(&aux_data.0).unwrap();
+        let expected = PallasAuxiliaryData::Shelley(KeyValuePairs::from(vec![
+            (1, Metadatum::Int((-3).into())),
+            (
+                674,
+                Metadatum::Map(KeyValuePairs::from(vec![(
+                    Metadatum::Text("msg".to_string()),
+                    Metadatum::Array(vec![
+                        Metadatum::Text("Ship minted".to_string()),
+                        Metadatum::Bytes(vec![0x00, 0xff].into()),
+                    ]),
+                )])),
+            ),
+        ]));
+        assert_eq!(decoded, expected);
+
+        assert!(auxiliary_data_from_json(&serde_json::json!({ "x": 1 })).is_err());
+        assert!(auxiliary_data_from_json(&serde_json::json!({ "1": true })).is_err());
+        assert!(auxiliary_data_from_json(&serde_json::json!({ "1": "a".repeat(65) })).is_err());
+    }
+
+    #[test]
+    fn test_reward_account_order() {
+        let key_account = [vec![0xe0], vec![0x00; 28]].concat();
+        let script_account = [vec![0xf0], vec![0xff; 28]].concat();
+        let other_script_account = [vec![0xf0], vec![0x01; 28]].concat();
+        let mainnet_script_account = [vec![0xf1], vec![0x00; 28]].concat();
+
+        let mut accounts = vec![
+            mainnet_script_account.clone(),
+            key_account.clone(),
+            script_account.clone(),
+            other_script_account.clone(),
+        ];
+        accounts.sort_by(|a, b| reward_account_order(a).cmp(&reward_account_order(b)));
+        assert_eq!(
+            accounts,
+            vec![
+                other_script_account,
+                script_account,
+                key_account,
+                mainnet_script_account
+            ]
+        );
+    }
 }
@@ -1,6 +1,6 @@
-use crate::{cli::Cli, rpc, sync};
+use crate::{cli::Cli, keystore::WalletKeystore, rpc, sync};
 use clap::Parser;
-use griffin_core::uplc::tx::SlotConfig;
+use griffin_core::{protocol_parameters::MAINNET_NETWORK_ID, uplc::tx::SlotConfig};
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
 use std::path::PathBuf;
 
@@ -11,10 +11,13 @@ pub struct Context<T: clap::Subcommand + clap::FromArgMatches> {
     pub cli: Cli<T>,
     pub client: HttpClient,
     pub db: sled::Db,
-    pub keystore: sc_keystore::LocalKeystore,
+    pub keystore: WalletKeystore,
     pub data_path: PathBuf,
     pub keystore_path: PathBuf,
     pub slot_config: SlotConfig,
+    /// The network ID of the chain, giving the human-readable part of the
+    /// addresses shown. Mainnet is assumed when working offline.
+    pub network_id: u8,
 }
 
 impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
@@ -36,7 +39,7 @@ impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
         let db_path = data_path.join("wallet_database");
 
         // Setup the keystore
-        let keystore = sc_keystore::LocalKeystore::open(keystore_path.clone(), None)?;
+        let mut keystore = WalletKeystore::open(keystore_path.clone())?;
 
         if cli.dev {
             // Insert the example Shawn key so example transactions can be signed.
@@ -47,6 +50,26 @@ impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
         // https://github.com/paritytech/jsonrpsee/blob/master/examples/examples/http.rs
         let client = HttpClientBuilder::default().build(&cli.endpoint)?;
 
+        if cli.offline {
+            log::warn!("Working offline. Using previously synced information.");
+
+            // Without the node, neither the genesis block nor the protocol
+            // parameters can be checked, so the database is opened as is.
+            let db = sled::open(db_path)?;
+            sync::discover_keys(&db, &mut keystore).await?;
+
+            return Ok(Context {
+                cli,
+                client,
+                db,
+                keystore,
+                data_path,
+                keystore_path,
+                slot_config: SlotConfig::default(),
+                network_id: MAINNET_NETWORK_ID,
+            });
+        }
+
         // Read node's genesis block.
         let node_genesis_hash = rpc::node_get_block_hash(0, &client)
             .await?
@@ -56,19 +79,11 @@ impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
             .expect("node should be able to return some genesis block");
         log::debug!("Node's Genesis block::{:?}", node_genesis_hash);
 
-        let zero_time = rpc::node_get_zero_time(&client)
-            .await?
-            .expect("node should be able to return zero time");
-        let zero_slot = rpc::node_get_zero_slot(&client)
-            .await?
-            .expect("node should be able to return zero slot");
-        let slot_length = rpc::node_get_slot_length(&client)
-            .await?
-            .expect("node should be able to return slot length");
+        let protocol_parameters = rpc::node_get_protocol_parameters(&client).await?;
         let slot_config = SlotConfig {
-            zero_time,
-            zero_slot,
-            slot_length,
+            zero_time: protocol_parameters.zero_time,
+            zero_slot: protocol_parameters.zero_slot,
+            slot_length: protocol_parameters.slot_length,
         };
 
         if cli.purge_db {
@@ -92,11 +107,16 @@ impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
             sync::apply_block(&db, node_genesis_block, node_genesis_hash).await?;
         }
 
+        // Track the outputs of the keys in the keystore
+        sync::discover_keys(&db, &mut keystore).await?;
+
         // Synchronize the wallet with attached node unless instructed otherwise.
         if cli.no_sync {
             log::warn!("Skipping sync with node. Using previously synced information.")
         } else {
             sync::synchronize(&db, &client).await?;
+            // New blocks may use further derived addresses.
+            sync::discover_keys(&db, &mut keystore).await?;
 
             log::info!(
                 "Wallet database synchronized with node to height {:?}",
@@ -112,6 +132,7 @@ impl<T: clap::Subcommand + clap::FromArgMatches> Context<T> {
             data_path,
             keystore_path,
             slot_config,
+            network_id: protocol_parameters.network_id,
         })
     }
 }
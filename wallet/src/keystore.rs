@@ -1,7 +1,17 @@
 //! Wallet's local keystore.
+//!
+//! Besides the flat keys kept in a Substrate [`LocalKeystore`], the wallet may
+//! hold a BIP39 mnemonic from which payment and stake keys are derived along
+//! the CIP-1852 paths `m/1852'/1815'/0'/role/index`. Derived payment keys own
+//! base addresses whose stake part is the key at `m/1852'/1815'/0'/2/0`.
 
 use anyhow::anyhow;
-use griffin_core::types::address_from_pk;
+use bip39::Mnemonic;
+use griffin_core::{
+    pallas_crypto::key::bip32::{XPrv, HARDENED},
+    types::{address_from_pk, base_address_from_pks, Address},
+};
+use rand::RngCore;
 use sc_keystore::LocalKeystore;
 use sp_core::{
     // The `Pair` trait is used to have `Pair::{generate_with,from}_phrase`
@@ -10,7 +20,11 @@ use sp_core::{
     H256,
 };
 use sp_keystore::Keystore;
-use std::path::Path;
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 /// A default seed phrase for signing inputs when none is provided
 /// Corresponds to the default pubkey.
@@ -26,33 +40,206 @@ pub const SHAWN_ADDRESS: &str = "6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6
 /// A KeyTypeId to use in the keystore for Griffin transactions.
 const KEY_TYPE: KeyTypeId = KeyTypeId(*b"_gri");
 
+/// Name of the file in the keystore directory holding the mnemonic of the
+/// hierarchical deterministic keys.
+const MNEMONIC_FILE: &str = "hd_mnemonic";
+
+/// Purpose and coin type indices of CIP-1852 paths, followed by the account.
+const ACCOUNT_PATH: [u32; 3] = [HARDENED + 1852, HARDENED + 1815, HARDENED];
+
+/// Roles of CIP-1852 keys that own addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Keys for receiving payments.
+    External = 0,
+    /// Keys for change outputs.
+    Internal = 1,
+}
+
+/// Role of the staking key.
+const STAKE_ROLE: u32 = 2;
+
+/// Payment key derived from the mnemonic, along with the base address it owns.
+#[derive(Clone, Debug)]
+pub struct DerivedKey {
+    pub role: Role,
+    pub index: u32,
+    pub public: Public,
+    pub address: Address,
+}
+
+impl DerivedKey {
+    /// The CIP-1852 derivation path of this key.
+    pub fn path(&self) -> String {
+        format!("m/1852'/1815'/0'/{}/{}", self.role as u32, self.index)
+    }
+}
+
+/// Keys derived from a mnemonic. Only those up to the number set for each
+/// role are used by the wallet.
+struct HdKeys {
+    account: XPrv,
+    stake: Public,
+    derived: Vec<DerivedKey>,
+}
+
+impl HdKeys {
+    fn derive(&self, role: Role, index: u32) -> DerivedKey {
+        let public = public_of(&self.account.derive_path(&[role as u32, index]));
+        DerivedKey {
+            role,
+            index,
+            public,
+            address: base_address_from_pks(&public, &self.stake),
+        }
+    }
+}
+
+/// The wallet keystore: flat keys in a [`LocalKeystore`], plus the keys
+/// derived from a mnemonic, if one has been inserted.
+pub struct WalletKeystore {
+    local: LocalKeystore,
+    hd: Option<HdKeys>,
+}
+
+impl WalletKeystore {
+    /// Opens the keystore at `path`, reading the mnemonic stored there, if any.
+    pub fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let mnemonic_path = path.join(MNEMONIC_FILE);
+        let hd = match mnemonic_path.exists() {
+            true => {
+                let account = account_key(&std::fs::read_to_string(mnemonic_path)?)?;
+                let stake = public_of(&account.derive_path(&[STAKE_ROLE, 0]));
+                Some(HdKeys {
+                    account,
+                    stake,
+                    derived: Vec::new(),
+                })
+            }
+            false => None,
+        };
+
+        Ok(Self {
+            local: LocalKeystore::open(path, None)?,
+            hd,
+        })
+    }
+
+    /// Payment keys derived from the mnemonic which are in use.
+    pub fn derived_keys(&self) -> &[DerivedKey] {
+        self.hd.as_ref().map_or(&[], |hd| &hd.derived)
+    }
+
+    /// The staking key derived from the mnemonic, if there is one.
+    pub fn stake_key(&self) -> Option<Public> {
+        self.hd.as_ref().map(|hd| hd.stake)
+    }
+
+    /// The payment key at `index` with the given `role`, whether in use or not.
+    /// `None` if there is no mnemonic.
+    pub fn derived_key(&self, role: Role, index: u32) -> Option<DerivedKey> {
+        self.hd.as_ref().map(|hd| hd.derive(role, index))
+    }
+
+    /// Use the first `count` payment keys with the given `role`, if fewer were
+    /// in use. Returns whether new keys were added.
+    pub fn extend(&mut self, role: Role, count: u32) -> bool {
+        let Some(hd) = self.hd.as_mut() else {
+            return false;
+        };
+        let in_use = hd.derived.iter().filter(|key| key.role == role).count() as u32;
+        for index in in_use..count {
+            let key = hd.derive(role, index);
+            hd.derived.push(key);
+        }
+
+        count > in_use
+    }
+
+    /// Public keys in use, along with the addresses they own: enterprise
+    /// addresses for flat keys and base addresses for derived ones.
+    pub fn addresses(&self) -> anyhow::Result<Vec<(H256, Address)>> {
+        let mut addresses: Vec<(H256, Address)> = get_keys(self)?
+            .map(|pubkey| {
+                let key = H256::from_slice(&pubkey);
+                (key, address_from_pk(&Public::from_h256(key)))
+            })
+            .collect();
+        addresses.extend(
+            self.derived_keys()
+                .iter()
+                .map(|key| (H256::from(key.public.0), key.address.clone())),
+        );
+
+        Ok(addresses)
+    }
+}
+
+/// The CIP-1852 account key derived from `phrase`, with an empty passphrase.
+fn account_key(phrase: &str) -> anyhow::Result<XPrv> {
+    let mnemonic = Mnemonic::parse(phrase.trim())?;
+
+    Ok(XPrv::from_entropy(&mnemonic.to_entropy(), b"").derive_path(&ACCOUNT_PATH))
+}
+
+fn public_of(xprv: &XPrv) -> Public {
+    Public::from_raw(xprv.public_key().into())
+}
+
 /// Insert the example "Shawn" key into the keystore for the current session only.
-pub fn insert_development_key_for_this_session(keystore: &LocalKeystore) -> anyhow::Result<()> {
-    keystore.ed25519_generate_new(KEY_TYPE, Some(SHAWN_PHRASE))?;
+pub fn insert_development_key_for_this_session(keystore: &WalletKeystore) -> anyhow::Result<()> {
+    keystore
+        .local
+        .ed25519_generate_new(KEY_TYPE, Some(SHAWN_PHRASE))?;
 
     Ok(())
 }
 
 /// Sign a given message with the private key that corresponds to the given public key.
 ///
+/// Flat keys are looked up first, and then the keys derived from the mnemonic
+/// which are in use.
+///
 /// Returns an error if the keystore itself errors, or does not contain the requested key.
 pub fn sign_with(
-    keystore: &LocalKeystore,
+    keystore: &WalletKeystore,
     public: &Public,
     message: &[u8],
 ) -> anyhow::Result<Signature> {
-    keystore
-        .ed25519_sign(KEY_TYPE, public, message)?
-        .ok_or(anyhow!("Key doesn't exist in keystore"))
+    if let Some(signature) = keystore.local.ed25519_sign(KEY_TYPE, public, message)? {
+        return Ok(signature);
+    }
+    let hd = keystore
+        .hd
+        .as_ref()
+        .ok_or(anyhow!("Key doesn't exist in keystore"))?;
+    let key = hd
+        .derived
+        .iter()
+        .find(|key| key.public == *public)
+        .ok_or(anyhow!("Key doesn't exist in keystore"))?;
+    let signature = hd
+        .account
+        .derive_path(&[key.role as u32, key.index])
+        .secret_key()
+        .sign(message);
+
+    Ok(Signature::from_raw(
+        signature
+            .as_ref()
+            .try_into()
+            .expect("Ed25519 signatures have 64 bytes"),
+    ))
 }
 
 /// Insert the private key associated with the given seed into the keystore for later use.
-pub fn insert_key(keystore: &LocalKeystore, seed: &str) -> anyhow::Result<()> {
+pub fn insert_key(keystore: &WalletKeystore, seed: &str) -> anyhow::Result<()> {
     // We need to provide a public key to the keystore manually, so let's calculate it.
     let public_key = Pair::from_phrase(seed, None)?.0.public();
     println!("The generated public key is {:?}", public_key);
-    println!("Associated address is 0x{}", address_from_pk(&public_key));
+    println!("Associated address is {}", address_from_pk(&public_key));
     keystore
+        .local
         .insert(KEY_TYPE, seed, public_key.as_ref())
         .map_err(|()| anyhow!("Error inserting key"))?;
 
@@ -61,23 +248,22 @@ pub fn insert_key(keystore: &LocalKeystore, seed: &str) -> anyhow::Result<()> {
 
 /// Generate a new key from system entropy and insert it into the keystore, optionally
 /// protected by a password.
-pub fn generate_key(keystore: &LocalKeystore, password: Option<String>) -> anyhow::Result<()> {
+pub fn generate_key(keystore: &WalletKeystore, password: Option<String>) -> anyhow::Result<()> {
     let (pair, phrase, _) = Pair::generate_with_phrase(password.as_deref());
     println!("Generated public key is {:?}", pair.public());
     println!("Generated Phrase is {:?}", phrase);
-    println!(
-        "Associated address is 0x{}",
-        address_from_pk(&pair.public())
-    );
+    println!("Associated address is {}", address_from_pk(&pair.public()));
     keystore
+        .local
         .insert(KEY_TYPE, phrase.as_ref(), pair.public().as_ref())
         .map_err(|()| anyhow!("Error inserting key"))?;
 
     Ok(())
 }
 
-pub fn get_keys(keystore: &LocalKeystore) -> anyhow::Result<impl Iterator<Item = Vec<u8>>> {
-    Ok(keystore.keys(KEY_TYPE)?.into_iter())
+/// Flat keys in the keystore. Keys derived from the mnemonic are not included.
+pub fn get_keys(keystore: &WalletKeystore) -> anyhow::Result<impl Iterator<Item = Vec<u8>>> {
+    Ok(keystore.local.keys(KEY_TYPE)?.into_iter())
 }
 
 /// Removes key from keystore. Call with care.
@@ -95,3 +281,101 @@ pub fn remove_key(keystore_path: &Path, pub_key: &H256) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Store the given mnemonic in the keystore, to derive keys from it in later runs.
+/// Only one mnemonic can be stored.
+pub fn insert_mnemonic(keystore_path: &Path, phrase: &str) -> anyhow::Result<()> {
+    let mnemonic_path = keystore_path.join(MNEMONIC_FILE);
+    if mnemonic_path.exists() {
+        return Err(anyhow!(
+            "There is already a mnemonic in the keystore, at {}",
+            mnemonic_path.to_string_lossy()
+        ));
+    }
+    let account = account_key(phrase)?;
+    let payment = public_of(&account.derive_path(&[Role::External as u32, 0]));
+    let stake = public_of(&account.derive_path(&[STAKE_ROLE, 0]));
+    std::fs::create_dir_all(keystore_path)?;
+    // Only readable by its owner, like the keys of the `LocalKeystore`.
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(mnemonic_path)?
+        .write_all(phrase.trim().as_bytes())?;
+    println!(
+        "First address is {}",
+        base_address_from_pks(&payment, &stake)
+    );
+
+    Ok(())
+}
+
+/// Generate a 24-word mnemonic from system entropy and store it in the keystore.
+pub fn generate_mnemonic(keystore_path: &Path) -> anyhow::Result<()> {
+    let mut entropy = [0u8; 32];
+    rand::rng().fill_bytes(&mut entropy);
+    let phrase = Mnemonic::from_entropy(&entropy)?.to_string();
+    insert_mnemonic(keystore_path, &phrase)?;
+    println!("Generated mnemonic, shown only this once. Write it down and keep it secret:");
+    println!("{}", phrase);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_address_of_first_derived_key() {
+        let path = std::env::temp_dir().join(format!(
+            "gpc-wallet-keystore-test-{}",
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos(),
+        ));
+        insert_mnemonic(
+            &path,
+            "test walk nut penalty hip pave soap entry language right filter choice",
+        )
+        .unwrap();
+        let mut keystore = WalletKeystore::open(path.clone()).unwrap();
+
+        // Same payment and stake parts as the testnet address
+        // addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3jcu5d8ps7zex2k2xt3uqxgjqnnj83ws8lhrn648jjxtwq2ytjqp
+        let key = keystore.derived_key(Role::External, 0).unwrap();
+        assert_eq!(
+            key.address.to_string(),
+            "addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3jcu5d8ps7\
+             zex2k2xt3uqxgjqnnj83ws8lhrn648jjxtwqfjkjv7"
+        );
+
+        // Only keys in use sign
+        let message = b"griffin";
+        assert!(sign_with(&keystore, &key.public, message).is_err());
+        assert!(keystore.extend(Role::External, 1));
+        assert!(!keystore.extend(Role::External, 1));
+        let signature = sign_with(&keystore, &key.public, message).unwrap();
+        assert!(Pair::verify(&signature, message, &key.public));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn mnemonic_is_only_readable_by_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "gpc-wallet-mnemonic-test-{}",
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos(),
+        ));
+        generate_mnemonic(&path).unwrap();
+
+        let metadata = std::fs::metadata(path.join(MNEMONIC_FILE)).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert!(generate_mnemonic(&path).is_err());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
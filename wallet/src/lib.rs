@@ -1,8 +1,10 @@
 pub mod cli;
+pub mod coin_selection;
 pub mod command;
 pub mod context;
 pub mod keystore;
 pub mod order_book;
 pub mod rpc;
 pub mod sync;
+pub mod tx_file;
 pub mod utils;
@@ -21,12 +21,14 @@
 //! ```
 
 mod cli;
+mod coin_selection;
 mod command;
 mod context;
 mod keystore;
 mod order_book;
 mod rpc;
 mod sync;
+mod tx_file;
 mod utils;
 
 #[tokio::main]
@@ -1,7 +1,9 @@
 //! Helper functions for communicating with the Node's RPC endpoint.
 
-use griffin_core::types::{Input, OpaqueBlock, Output};
-use griffin_core::{SLOT_LENGTH, ZERO_SLOT, ZERO_TIME};
+use anyhow::anyhow;
+use griffin_core::evaluation::TxEvaluation;
+use griffin_core::protocol_parameters::ProtocolParameters;
+use griffin_core::types::{ExUnits, Input, OpaqueBlock, Output, Transaction};
 use jsonrpsee::{core::client::ClientT, http_client::HttpClient, rpc_params};
 use parity_scale_codec::Encode;
 use sp_core::H256;
@@ -41,38 +43,60 @@ pub async fn fetch_storage(input: &Input, client: &HttpClient) -> anyhow::Result
     Ok(utxo)
 }
 
-/// Get the Node's initial POSIX time
-pub async fn node_get_zero_time(client: &HttpClient) -> anyhow::Result<Option<u64>> {
-    let params = rpc_params![hex::encode(str::from_utf8(ZERO_TIME).unwrap())];
-    let rpc_response: Option<String> = client.request("state_getStorage", params).await?;
-    let time_bytes: [u8; 8] = hex::decode(rpc_response.unwrap().strip_prefix("0x").unwrap())
-        .unwrap()
-        .try_into()
-        .unwrap();
-    let time = u64::from_le_bytes(time_bytes);
-    Ok(Some(time))
+/// Get the Node's protocol parameters at the best block
+pub async fn node_get_protocol_parameters(
+    client: &HttpClient,
+) -> anyhow::Result<ProtocolParameters> {
+    let params = rpc_params![];
+    let pps: ProtocolParameters = client
+        .request("griffin_get_protocol_parameters", params)
+        .await?;
+    Ok(pps)
 }
 
-/// Get the Node's zero slot
-pub async fn node_get_zero_slot(client: &HttpClient) -> anyhow::Result<Option<u64>> {
-    let params = rpc_params![hex::encode(str::from_utf8(ZERO_SLOT).unwrap())];
-    let rpc_response: Option<String> = client.request("state_getStorage", params).await?;
-    let slot_bytes: [u8; 8] = hex::decode(rpc_response.unwrap().strip_prefix("0x").unwrap())
-        .unwrap()
-        .try_into()
-        .unwrap();
-    let slot = u64::from_le_bytes(slot_bytes);
-    Ok(Some(slot))
+/// Evaluate a transaction against the Node's best block without submitting it
+pub async fn node_evaluate_transaction(
+    transaction: &Transaction,
+    client: &HttpClient,
+) -> anyhow::Result<TxEvaluation> {
+    let tx_hex = hex::encode(transaction.encode());
+    let params = rpc_params![tx_hex];
+    let evaluation: TxEvaluation = client.request("griffin_evaluate_tx", params).await?;
+    Ok(evaluation)
 }
 
-/// Get the Node's slot length
-pub async fn node_get_slot_length(client: &HttpClient) -> anyhow::Result<Option<u32>> {
-    let params = rpc_params![hex::encode(str::from_utf8(SLOT_LENGTH).unwrap())];
-    let rpc_response: Option<String> = client.request("state_getStorage", params).await?;
-    let slot_length_bytes: [u8; 4] = hex::decode(rpc_response.unwrap().strip_prefix("0x").unwrap())
-        .unwrap()
-        .try_into()
-        .unwrap();
-    let slot_length = u32::from_le_bytes(slot_length_bytes);
-    Ok(Some(slot_length))
+/// Replace the execution units of the redeemers of a transaction that equal
+/// `placeholder` with those spent by their scripts, as evaluated by the Node
+pub async fn node_set_evaluated_ex_units(
+    transaction: &mut Transaction,
+    placeholder: &ExUnits,
+    client: &HttpClient,
+) -> anyhow::Result<()> {
+    let evaluation = node_evaluate_transaction(transaction, client).await?;
+    for redeemer in transaction
+        .transaction_witness_set
+        .redeemer
+        .iter_mut()
+        .flatten()
+        .filter(|redeemer| redeemer.ex_units == *placeholder)
+    {
+        let evaluated = evaluation
+            .redeemers
+            .iter()
+            .find(|evaluated| evaluated.evaluates(redeemer))
+            .ok_or_else(|| match &evaluation.error {
+                Some(err) => anyhow!(
+                    "The scripts could not be evaluated: {} (error code {})",
+                    err.message,
+                    err.code
+                ),
+                None => anyhow!(
+                    "The node did not evaluate the {:?} redeemer at index {}",
+                    redeemer.tag,
+                    redeemer.index
+                ),
+            })?;
+        redeemer.ex_units = evaluated.ex_units.clone();
+    }
+    Ok(())
 }
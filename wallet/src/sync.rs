@@ -5,22 +5,36 @@
 //!
 //! ## Scheme
 //!
-//! There are 4 tables in the database:
+//! There are 7 tables in the database:
 //!
 //! - BlockHashes     `block_number:u32` => `block_hash:H256`
 //! - Blocks          `block_hash:H256` => `block:Block`
 //! - UnspentOutputs  `input` => `(owner_pubkey, amount, datum_option)`
 //! - SpentOutputs    `input` => `(owner_pubkey, amount, datum_option)`
+//! - Tracked         `address` => `owner_key:Option<H256>`
+//! - Watched         `address` => `()`
+//! - History         `(block_number, tx_hash, address)` => `entry:HistoryEntry`
+//!
+//! The stored `datum_option` is the datum itself. Outputs carrying only a datum
+//! hash store the datum when the creating transaction published it in its
+//! witness set, and `None` otherwise.
+//!
+//! Only outputs at tracked addresses are stored. These are the addresses of the
+//! keys in the keystore, which own them, and the addresses explicitly watched.
+//! Keys derived from the keystore mnemonic are tracked up to a gap of unused
+//! addresses (see [`discover_keys`]). Whenever the tracked addresses change, the
+//! outputs and the history are rebuilt from the stored blocks.
 
 use std::path::PathBuf;
 
-use crate::order_book::{OrderDatum, ORDER_SCRIPT_HEX};
+use crate::keystore::{Role, WalletKeystore};
+use crate::order_book::{order_address, OrderDatum};
 use crate::rpc;
 use anyhow::anyhow;
 use colored::Colorize;
 use griffin_core::types::{
-    compute_plutus_v2_script_hash, Address, AssetName, Datum, Input, OpaqueBlock, PlutusScript,
-    Transaction, Value,
+    asset_fingerprint, compute_datum_hash, Address, AssetName, Coin, Datum, DatumOption, Header,
+    Input, OpaqueBlock, Output, Transaction, Value,
 };
 use jsonrpsee::http_client::HttpClient;
 use parity_scale_codec::{Decode, Encode};
@@ -28,8 +42,9 @@ use sled::Db;
 use sp_core::H256;
 use sp_runtime::{
     traits::{BlakeTwo256, Hash},
-    OpaqueExtrinsic,
+    DigestItem, OpaqueExtrinsic,
 };
+use std::collections::{HashMap, HashSet};
 
 /// The identifier for the blocks tree in the db.
 const BLOCKS: &str = "blocks";
@@ -43,6 +58,38 @@ pub const UNSPENT: &str = "unspent";
 /// The identifier for the spent tree in the db.
 const SPENT: &str = "spent";
 
+/// The identifier for the tracked addresses tree in the db.
+const TRACKED: &str = "tracked";
+
+/// The identifier for the watched addresses tree in the db.
+const WATCHED: &str = "watched";
+
+/// The identifier for the history tree in the db.
+const HISTORY: &str = "history";
+
+/// Number of consecutive unused addresses of each role derived past the last
+/// used one.
+const ADDRESS_GAP: u32 = 20;
+
+/// Effect of a transaction on a tracked address.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub tx_hash: H256,
+    pub address: Address,
+    /// Height of the block including the transaction.
+    pub height: u32,
+    /// Aura slot of the block, absent for the genesis block.
+    pub slot: Option<u64>,
+    /// Value of the outputs of the transaction at the address.
+    pub received: Value,
+    /// Value of the outputs at the address spent by the transaction.
+    pub sent: Value,
+    /// Fee of the transaction if the address funded it, and zero otherwise.
+    /// For a transaction whose scripts failed, this is the forfeited
+    /// collateral, and the values are those of its collateral and return.
+    pub fee: Coin,
+}
+
 /// Open a database at the given location intended for the given genesis block.
 ///
 /// If the database is already populated, make sure it is based on the expected genesis
@@ -206,17 +253,45 @@ pub(crate) async fn apply_block(db: &Db, b: OpaqueBlock, block_hash: H256) -> an
     let wallet_blocks_tree = db.open_tree(BLOCKS)?;
     wallet_blocks_tree.insert(block_hash.encode(), b.encode())?;
 
+    apply_block_transactions(db, b).await
+}
+
+/// Apply the transactions of a block to the outputs and history of the tracked
+/// addresses.
+async fn apply_block_transactions(db: &Db, b: OpaqueBlock) -> anyhow::Result<()> {
+    let tracked: Vec<Address> = get_tracked(db)?
+        .into_iter()
+        .map(|(address, _)| address)
+        .collect();
+    let slot = aura_slot(&b.header);
+
     // Iterate through each transaction
     for tx in b.extrinsics {
-        apply_transaction(db, tx).await?;
+        apply_transaction(db, tx, b.header.number, slot, &tracked).await?;
     }
 
     Ok(())
 }
 
+/// The Aura slot of the block with the given header, if it has one.
+fn aura_slot(header: &Header) -> Option<u64> {
+    header.digest.logs.iter().find_map(|item| match item {
+        DigestItem::PreRuntime(engine_id, data) if engine_id == b"aura" => {
+            u64::decode(&mut &data[..]).ok()
+        }
+        _ => None,
+    })
+}
+
 /// Apply a single transaction to the local database
 /// The owner-specific tables are mappings from inputs to coin amounts
-async fn apply_transaction(db: &Db, opaque_tx: OpaqueExtrinsic) -> anyhow::Result<()> {
+async fn apply_transaction(
+    db: &Db,
+    opaque_tx: OpaqueExtrinsic,
+    height: u32,
+    slot: Option<u64>,
+    tracked: &[Address],
+) -> anyhow::Result<()> {
     let encoded_extrinsic = opaque_tx.encode();
     let tx_hash = BlakeTwo256::hash_of(&encoded_extrinsic);
     log::debug!("syncing transaction {tx_hash:?}");
@@ -224,31 +299,120 @@ async fn apply_transaction(db: &Db, opaque_tx: OpaqueExtrinsic) -> anyhow::Resul
     // Now get a structured transaction
     let tx = <Transaction>::decode(&mut &encoded_extrinsic[..])?;
 
-    // Insert all new outputs
-    for (index, output) in tx.transaction_body.outputs.iter().enumerate() {
-        let input = Input {
-            tx_hash,
-            index: index as u32,
-        };
+    // Values received and sent by each tracked address
+    let mut flows = HashMap::<Address, (Value, Value)>::new();
+    let (consumed, produced) = transaction_effects(&tx);
+
+    // Insert the new outputs at tracked addresses
+    for (index, output) in produced {
+        if !tracked.contains(&output.address) {
+            continue;
+        }
+        let input = Input { tx_hash, index };
 
         crate::sync::add_unspent_output(
             db,
             &input,
             &output.address,
             &output.value,
-            &output.datum_option,
+            &resolve_datum(&tx, &output.datum_option),
         )?;
+        flows
+            .entry(output.address.clone())
+            .or_insert((Value::Coin(0), Value::Coin(0)))
+            .0 += output.value.clone();
     }
 
     log::debug!("about to spend all inputs");
-    // Spend all the inputs
-    for input in tx.transaction_body.inputs {
-        spend_output(db, &input)?;
+    // Spend all the inputs, which are only stored if they are at tracked addresses
+    let mut spent_coin: Coin = 0;
+    for input in consumed {
+        if let Some((owner, amount, _)) = get_unspent(db, input)? {
+            spend_output(db, input)?;
+            spent_coin += amount.coin_of();
+            flows
+                .entry(owner)
+                .or_insert((Value::Coin(0), Value::Coin(0)))
+                .1 += amount;
+        }
+    }
+
+    // A transaction whose scripts fail forfeits its collateral, less the
+    // collateral return, instead of paying its fee.
+    let body = &tx.transaction_body;
+    let tx_fee = if tx.is_valid {
+        body.fee
+    } else {
+        body.total_collateral.unwrap_or_else(|| {
+            let returned = body.collateral_return.as_ref().map(|o| o.value.coin_of());
+            spent_coin.saturating_sub(returned.unwrap_or(0))
+        })
+    };
+
+    let history_tree = db.open_tree(HISTORY)?;
+    for (address, (received, sent)) in flows {
+        let fee = if sent.is_null() { 0 } else { tx_fee };
+        let entry = HistoryEntry {
+            tx_hash,
+            address,
+            height,
+            slot,
+            received,
+            sent,
+            fee,
+        };
+        history_tree.insert(
+            history_key(height, &tx_hash, &entry.address),
+            entry.encode(),
+        )?;
     }
 
     Ok(())
 }
 
+/// The inputs consumed by a transaction and the outputs it produces, along with
+/// their indices. A transaction whose scripts fail consumes its collateral
+/// instead of its inputs, and only produces its collateral return, which is
+/// placed after the regular outputs.
+fn transaction_effects(tx: &Transaction) -> (&[Input], Vec<(u32, &Output)>) {
+    let body = &tx.transaction_body;
+    if tx.is_valid {
+        let outputs = body.outputs.iter().enumerate();
+        (
+            &body.inputs,
+            outputs.map(|(index, o)| (index as u32, o)).collect(),
+        )
+    } else {
+        let index = body.outputs.len() as u32;
+        (
+            body.collateral.as_deref().unwrap_or_default(),
+            body.collateral_return.iter().map(|o| (index, o)).collect(),
+        )
+    }
+}
+
+/// Key of a history entry. The height goes first, in big-endian, so that
+/// entries are sorted by height and those of a block share a prefix.
+fn history_key(height: u32, tx_hash: &H256, address: &Address) -> Vec<u8> {
+    [&height.to_be_bytes()[..], tx_hash.as_bytes(), &address.0].concat()
+}
+
+/// The datum of an output: the inline one, or the witness-set datum matching
+/// its datum hash.
+fn resolve_datum(tx: &Transaction, datum_option: &Option<DatumOption>) -> Option<Datum> {
+    match datum_option {
+        Some(DatumOption::Inline(datum)) => Some(datum.clone()),
+        Some(DatumOption::Hash(hash)) => tx
+            .transaction_witness_set
+            .plutus_data
+            .iter()
+            .flatten()
+            .map(|data| Datum(data.0.clone()))
+            .find(|datum| compute_datum_hash(datum) == *hash),
+        None => None,
+    }
+}
+
 /// Add a new output to the database updating all tables.
 pub(crate) fn add_unspent_output(
     db: &Db,
@@ -304,26 +468,23 @@ fn unspend_output(db: &Db, input: &Input) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Run a transaction backwards against a database. Mark all of the Inputs
-/// as unspent, and drop all of the outputs.
+/// Run a transaction backwards against a database. Mark all of the consumed
+/// inputs as unspent, and drop all of the produced outputs.
 fn unapply_transaction(db: &Db, tx: &OpaqueExtrinsic) -> anyhow::Result<()> {
     // We need to decode the opaque extrinsics. So we do a scale round-trip.
     let tx = <Transaction>::decode(&mut &tx.encode()[..])?;
+    let (consumed, produced) = transaction_effects(&tx);
 
     // Loop through the inputs moving each from spent to unspent
-    for input in &tx.transaction_body.inputs {
+    for input in consumed {
         unspend_output(db, input)?;
     }
 
     // Loop through the outputs pruning them from unspent and dropping all record
     let tx_hash = BlakeTwo256::hash_of(&tx.encode());
 
-    for i in 0..tx.transaction_body.outputs.len() {
-        let input = Input {
-            tx_hash,
-            index: i as u32,
-        };
-        remove_unspent_output(db, &input)?;
+    for (index, _) in produced {
+        remove_unspent_output(db, &Input { tx_hash, index })?;
     }
 
     Ok(())
@@ -359,9 +520,227 @@ pub(crate) async fn unapply_highest_block(db: &Db) -> anyhow::Result<()> {
         unapply_transaction(db, tx)?;
     }
 
+    // Drop the history of the block
+    let history_tree = db.open_tree(HISTORY)?;
+    for key in history_tree.scan_prefix(height.to_be_bytes()).keys() {
+        history_tree.remove(key?)?;
+    }
+
     Ok(())
 }
 
+/// The tracked addresses, along with the keys owning them. Watched addresses
+/// have no owner.
+pub fn get_tracked(db: &Db) -> anyhow::Result<Vec<(Address, Option<H256>)>> {
+    let tracked_tree = db.open_tree(TRACKED)?;
+    let mut tracked = Vec::new();
+    for x in tracked_tree.iter() {
+        let (address_ivec, key_ivec) = x?;
+        tracked.push((
+            Address::decode(&mut &address_ivec[..])?,
+            <Option<H256>>::decode(&mut &key_ivec[..])?,
+        ));
+    }
+
+    Ok(tracked)
+}
+
+/// The explicitly watched addresses.
+fn get_watched(db: &Db) -> anyhow::Result<Vec<Address>> {
+    let watched_tree = db.open_tree(WATCHED)?;
+    let mut watched = Vec::new();
+    for x in watched_tree.iter() {
+        let (address_ivec, _) = x?;
+        watched.push(Address::decode(&mut &address_ivec[..])?);
+    }
+
+    Ok(watched)
+}
+
+/// Track the given addresses of keys and the watched ones, along with the order
+/// book script address. If they differ from those currently tracked, rebuild the
+/// outputs and history from the stored blocks.
+pub(crate) async fn track_keys(db: &Db, keys: &[(H256, Address)]) -> anyhow::Result<()> {
+    let mut tracked: Vec<(Address, Option<H256>)> = keys
+        .iter()
+        .map(|(key, address)| (address.clone(), Some(*key)))
+        .collect();
+    for address in get_watched(db)?
+        .into_iter()
+        .chain(std::iter::once(order_address()))
+    {
+        if !tracked.iter().any(|(tracked, _)| *tracked == address) {
+            tracked.push((address, None));
+        }
+    }
+
+    set_tracked(db, tracked).await
+}
+
+/// Track the addresses of the keys in `keystore`, deriving new keys from its
+/// mnemonic until the last [`ADDRESS_GAP`] addresses of each role have no
+/// history.
+pub(crate) async fn discover_keys(db: &Db, keystore: &mut WalletKeystore) -> anyhow::Result<()> {
+    // Resume from the derived keys tracked in previous runs.
+    let tracked: HashSet<Address> = get_tracked(db)?
+        .into_iter()
+        .map(|(address, _)| address)
+        .collect();
+    for role in [Role::External, Role::Internal] {
+        let mut count = 0;
+        while keystore
+            .derived_key(role, count)
+            .is_some_and(|key| tracked.contains(&key.address))
+        {
+            count += 1;
+        }
+        keystore.extend(role, count.max(ADDRESS_GAP));
+    }
+
+    loop {
+        track_keys(db, &keystore.addresses()?).await?;
+
+        let used: HashSet<Address> = get_history(db, None, None, None)?
+            .into_iter()
+            .map(|entry| entry.address)
+            .collect();
+        let mut extended = false;
+        for role in [Role::External, Role::Internal] {
+            let used_count = keystore
+                .derived_keys()
+                .iter()
+                .filter(|key| key.role == role && used.contains(&key.address))
+                .map(|key| key.index + 1)
+                .max()
+                .unwrap_or(0);
+            extended |= keystore.extend(role, used_count + ADDRESS_GAP);
+        }
+        if !extended {
+            return Ok(());
+        }
+    }
+}
+
+/// Watch the given addresses, rebuilding the outputs and history from the
+/// stored blocks if any of them was not tracked yet.
+pub async fn watch_addresses(db: &Db, addresses: &[Address]) -> anyhow::Result<()> {
+    let watched_tree = db.open_tree(WATCHED)?;
+    let mut tracked = get_tracked(db)?;
+    for address in addresses {
+        watched_tree.insert(address.encode(), ().encode())?;
+        if !tracked.iter().any(|(tracked, _)| tracked == address) {
+            tracked.push((address.clone(), None));
+        }
+    }
+
+    set_tracked(db, tracked).await
+}
+
+/// Stop watching an address. Its outputs and history are dropped unless it
+/// belongs to a key in the keystore.
+pub(crate) async fn unwatch_address(db: &Db, address: &Address) -> anyhow::Result<()> {
+    let watched_tree = db.open_tree(WATCHED)?;
+    if watched_tree.remove(address.encode())?.is_none() {
+        return Err(anyhow!("Address {address} is not watched."));
+    }
+    let tracked = get_tracked(db)?
+        .into_iter()
+        .filter(|(tracked, key)| tracked != address || key.is_some())
+        .collect();
+
+    set_tracked(db, tracked).await
+}
+
+/// Replace the tracked addresses, rescanning the stored blocks if they change.
+async fn set_tracked(db: &Db, tracked: Vec<(Address, Option<H256>)>) -> anyhow::Result<()> {
+    let current: HashMap<Address, Option<H256>> = get_tracked(db)?.into_iter().collect();
+    let new: HashMap<Address, Option<H256>> = tracked.into_iter().collect();
+    if current == new {
+        return Ok(());
+    }
+
+    let tracked_tree = db.open_tree(TRACKED)?;
+    tracked_tree.clear()?;
+    for (address, key) in new {
+        tracked_tree.insert(address.encode(), key.encode())?;
+    }
+
+    rescan(db).await
+}
+
+/// Rebuild the outputs and history of the tracked addresses from the stored
+/// blocks.
+async fn rescan(db: &Db) -> anyhow::Result<()> {
+    log::info!("Tracked addresses changed. Rescanning the blocks in the db.");
+    for tree in [UNSPENT, SPENT, HISTORY] {
+        db.open_tree(tree)?.clear()?;
+    }
+
+    let wallet_blocks_tree = db.open_tree(BLOCKS)?;
+    let Some(best) = height(db)? else {
+        return Ok(());
+    };
+    for height in 0..=best {
+        let hash = get_block_hash(db, height)?.ok_or(anyhow!(
+            "No block hash found at height {height}. DB is inconsistent."
+        ))?;
+        let Some(ivec) = wallet_blocks_tree.get(hash.encode())? else {
+            return Err(anyhow!(
+                "Block was not present in db but block hash was. DB is corrupted."
+            ));
+        };
+        apply_block_transactions(db, OpaqueBlock::decode(&mut &ivec[..])?).await?;
+    }
+
+    Ok(())
+}
+
+/// The history entries of `address`, or of every tracked address if none is
+/// given, in blocks from height `from` to `to`, both included.
+pub(crate) fn get_history(
+    db: &Db,
+    address: Option<&Address>,
+    from: Option<u32>,
+    to: Option<u32>,
+) -> anyhow::Result<Vec<HistoryEntry>> {
+    let history_tree = db.open_tree(HISTORY)?;
+    let start = from.unwrap_or(0).to_be_bytes();
+    let mut history = Vec::new();
+    for x in history_tree.range(start..) {
+        let (_, entry_ivec) = x?;
+        let entry = HistoryEntry::decode(&mut &entry_ivec[..])?;
+        if to.is_some_and(|to| entry.height > to) {
+            break;
+        }
+        if address.is_none_or(|address| entry.address == *address) {
+            history.push(entry);
+        }
+    }
+
+    Ok(history)
+}
+
+/// Print history entries. Their times are derived from the Aura slots of their
+/// blocks, which last `slot_length` milliseconds, and their addresses are shown
+/// as on a chain with network ID `network_id`.
+pub(crate) fn show_history(history: Vec<HistoryEntry>, slot_length: u32, network_id: u8) {
+    for entry in history {
+        let time = entry.slot.map_or("-".to_string(), |slot| {
+            (slot * slot_length as u64).to_string()
+        });
+        println!(
+            "{} at height {} (POSIX time {}):\n address: {},\n received: {},\n sent: {},\n fee: {}",
+            hex::encode(entry.tx_hash).bold(),
+            entry.height,
+            time,
+            color_address(&entry.address, network_id),
+            entry.received.normalize(),
+            entry.sent.normalize(),
+            entry.fee,
+        );
+    }
+}
+
 /// Get the block height that the wallet is currently synced to
 ///
 /// None means the db is not yet initialized with a genesis block
@@ -389,15 +768,37 @@ pub fn get_outputs_at(
     get_outputs_by(|owner, _| *owner == args.address, db)
 }
 
+/// The unspent outputs at any of the given addresses.
+pub(crate) fn get_outputs_at_any(
+    db: &Db,
+    addresses: &[Address],
+) -> anyhow::Result<Vec<ResolvedInputInfo>> {
+    get_outputs_by(|owner, _| addresses.contains(owner), db)
+}
+
 /// Print the unspent outputs with a specific asset.
 pub fn get_outputs_with_asset(
     db: &Db,
     args: crate::cli::ShowOutputsWithAssetArgs,
 ) -> anyhow::Result<Vec<ResolvedInputInfo>> {
-    get_outputs_by(
-        |_, amount| amount.contains_asset(&AssetName::from(args.name.clone()), &args.policy),
-        db,
-    )
+    match (args.policy, args.name, args.fingerprint) {
+        (_, _, Some(fingerprint)) => get_outputs_by(
+            |_, amount| match amount {
+                Value::Multiasset(_, ma) => Vec::from(ma)
+                    .iter()
+                    .any(|(policy, name, _)| asset_fingerprint(policy, name) == fingerprint),
+                Value::Coin(_) => false,
+            },
+            db,
+        ),
+        (Some(policy), Some(name), None) => get_outputs_by(
+            |_, amount| amount.contains_asset(&AssetName::from(name.clone()), &policy),
+            db,
+        ),
+        _ => Err(anyhow!(
+            "Either a policy and a name or a fingerprint is required"
+        )),
+    }
 }
 
 /// Print the available orders.
@@ -409,12 +810,7 @@ pub(crate) fn print_orders(db: &Db) -> anyhow::Result<()> {
         let (owner_pubkey, value, datum_option) =
             <(Address, Value, Option<Datum>)>::decode(&mut &owner_amount_datum_ivec[..])?;
 
-        let script = PlutusScript(hex::decode(ORDER_SCRIPT_HEX).unwrap());
-        let script_hash = compute_plutus_v2_script_hash(script.clone());
-        let order_address =
-            Address(hex::decode("70".to_owned() + &hex::encode(script_hash)).unwrap());
-
-        if owner_pubkey == order_address {
+        if owner_pubkey == order_address() {
             let order_datum = datum_option.map(|d| OrderDatum::from(d));
             match order_datum {
                 None | Some(OrderDatum::MalformedOrderDatum) => {
@@ -470,8 +866,9 @@ pub(crate) fn get_balances(db: &Db) -> anyhow::Result<impl Iterator<Item = (Addr
     Ok(balances.into_iter())
 }
 
-/// Print the unspent outputs info.
-pub fn show_outputs(outputs_info: Vec<ResolvedInputInfo>) {
+/// Print the unspent outputs info, with addresses shown as on a chain with
+/// network ID `network_id`.
+pub fn show_outputs(outputs_info: Vec<ResolvedInputInfo>, network_id: u8) {
     for x in outputs_info.iter() {
         let input_string = hex::encode(x.input.encode());
         let datum_option_hex = x.datum_option.clone().map(|datum| hex::encode(datum.0));
@@ -479,7 +876,7 @@ pub fn show_outputs(outputs_info: Vec<ResolvedInputInfo>) {
         println!(
             "{}:\n address: {},\n datum: {:?},\n amount: {}",
             input_string.bold(),
-            color_address(&x.address),
+            color_address(&x.address, network_id),
             datum_option_hex,
             x.value.normalize(),
         );
@@ -512,11 +909,11 @@ where
 }
 
 /// Color the address red if it's a script address (starts with "70"), green otherwise
-fn color_address(address: &Address) -> colored::ColoredString {
-    if address.to_string().starts_with("70") {
-        address.to_string().red()
+fn color_address(address: &Address, network_id: u8) -> colored::ColoredString {
+    if address.0.first() == Some(&0x70) {
+        address.to_string_on(network_id).red()
     } else {
-        address.to_string().green()
+        address.to_string_on(network_id).green()
     }
 }
 
@@ -537,3 +934,181 @@ impl From<(Input, Address, Value, Option<Datum>)> for ResolvedInputInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn temporary_db() -> Db {
+        sled::Config::new().temporary(true).open().unwrap()
+    }
+
+    fn mine() -> Address {
+        Address(vec![0x61; 29])
+    }
+
+    fn other() -> Address {
+        Address(vec![0x62; 29])
+    }
+
+    fn block(number: u32, txs: &[Transaction]) -> OpaqueBlock {
+        OpaqueBlock {
+            header: Header::new(
+                number,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ),
+            extrinsics: txs
+                .iter()
+                .map(|tx| OpaqueExtrinsic::decode(&mut &tx.encode()[..]).unwrap())
+                .collect(),
+        }
+    }
+
+    fn input_of(tx: &Transaction, index: u32) -> Input {
+        Input {
+            tx_hash: BlakeTwo256::hash_of(&tx.encode()),
+            index,
+        }
+    }
+
+    /// Genesis transaction paying 100 coins to `mine` and 100 to `other`.
+    fn genesis_tx() -> Transaction {
+        Transaction::from((
+            vec![],
+            vec![
+                Output::from((mine(), Value::Coin(100))),
+                Output::from((other(), Value::Coin(100))),
+            ],
+        ))
+    }
+
+    /// Transaction spending the output of `mine` in `genesis_tx` to `other`,
+    /// paying a fee of 5 coins.
+    fn payment() -> Transaction {
+        let mut tx = Transaction::from((
+            vec![input_of(&genesis_tx(), 0)],
+            vec![Output::from((other(), Value::Coin(95)))],
+        ));
+        tx.transaction_body.fee = 5;
+        tx
+    }
+
+    /// A database tracking `mine`, holding the genesis block.
+    fn db_with_genesis() -> Db {
+        let db = temporary_db();
+        block_on(watch_addresses(&db, &[mine()])).unwrap();
+        block_on(apply_block(
+            &db,
+            block(0, &[genesis_tx()]),
+            H256::repeat_byte(0),
+        ))
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn only_outputs_at_tracked_addresses_are_stored() {
+        let db = db_with_genesis();
+
+        assert_eq!(
+            get_unspent(&db, &input_of(&genesis_tx(), 0)).unwrap(),
+            Some((mine(), Value::Coin(100), None))
+        );
+        assert_eq!(get_unspent(&db, &input_of(&genesis_tx(), 1)).unwrap(), None);
+
+        let history = get_history(&db, None, None, None).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].address, mine());
+        assert_eq!(history[0].received, Value::Coin(100));
+        assert_eq!(history[0].fee, 0);
+    }
+
+    #[test]
+    fn watching_an_address_rescans_the_stored_blocks() {
+        let db = db_with_genesis();
+        block_on(watch_addresses(&db, &[other()])).unwrap();
+
+        assert_eq!(
+            get_unspent(&db, &input_of(&genesis_tx(), 1)).unwrap(),
+            Some((other(), Value::Coin(100), None))
+        );
+        assert_eq!(
+            get_history(&db, Some(&other()), None, None).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn unapplied_block_restores_outputs_and_drops_its_history() {
+        let db = db_with_genesis();
+        block_on(apply_block(
+            &db,
+            block(1, &[payment()]),
+            H256::repeat_byte(1),
+        ))
+        .unwrap();
+
+        assert_eq!(get_unspent(&db, &input_of(&genesis_tx(), 0)).unwrap(), None);
+        let sent = get_history(&db, None, Some(1), None).unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            (&sent[0].sent, sent[0].fee, sent[0].height),
+            (&Value::Coin(100), 5, 1)
+        );
+
+        block_on(unapply_highest_block(&db)).unwrap();
+        assert_eq!(height(&db).unwrap(), Some(0));
+        assert_eq!(
+            get_unspent(&db, &input_of(&genesis_tx(), 0)).unwrap(),
+            Some((mine(), Value::Coin(100), None))
+        );
+        assert_eq!(get_history(&db, None, Some(1), None).unwrap(), vec![]);
+        assert_eq!(get_history(&db, None, None, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn failed_transaction_forfeits_its_collateral() {
+        let db = db_with_genesis();
+        // Spends the output of `other` in `genesis_tx`, putting up the one of
+        // `mine` as collateral, 30 coins of which are forfeited.
+        let mut failed = Transaction::from((
+            vec![input_of(&genesis_tx(), 1)],
+            vec![Output::from((other(), Value::Coin(95)))],
+        ));
+        failed.transaction_body.fee = 5;
+        failed.transaction_body.collateral = Some(vec![input_of(&genesis_tx(), 0)]);
+        failed.transaction_body.collateral_return = Some(Output::from((mine(), Value::Coin(70))));
+        failed.is_valid = false;
+        block_on(apply_block(
+            &db,
+            block(1, &[failed.clone()]),
+            H256::repeat_byte(1),
+        ))
+        .unwrap();
+
+        assert_eq!(get_unspent(&db, &input_of(&genesis_tx(), 0)).unwrap(), None);
+        assert_eq!(get_unspent(&db, &input_of(&failed, 0)).unwrap(), None);
+        assert_eq!(
+            get_unspent(&db, &input_of(&failed, 1)).unwrap(),
+            Some((mine(), Value::Coin(70), None))
+        );
+        let history = get_history(&db, None, Some(1), None).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            (&history[0].received, &history[0].sent, history[0].fee),
+            (&Value::Coin(70), &Value::Coin(100), 30)
+        );
+
+        block_on(unapply_highest_block(&db)).unwrap();
+        assert_eq!(
+            get_unspent(&db, &input_of(&genesis_tx(), 0)).unwrap(),
+            Some((mine(), Value::Coin(100), None))
+        );
+        assert_eq!(get_unspent(&db, &input_of(&failed, 1)).unwrap(), None);
+        assert_eq!(get_history(&db, None, Some(1), None).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,303 @@
+//! Files for building, signing and submitting transactions on different
+//! machines, in the text envelope format of `cardano-cli`.
+//!
+//! Transaction files hold the CBOR of the transaction in `cborHex`, so that
+//! Cardano tools can display it and witness its body. Two further fields,
+//! ignored by those tools, are used by the wallet: `scaleHex`, the
+//! SCALE-encoded Griffin transaction, from which the transaction is read back,
+//! and `resolvedInputs`, the outputs spent, referenced or put up as collateral
+//! by the transaction, so that it can be inspected offline. Files holding just
+//! the hex-encoded SCALE transaction, as formerly written by `build-tx --out`,
+//! are read as well.
+//!
+//! Witness files hold a single key witness, as those written by
+//! `cardano-cli transaction witness`.
+
+use anyhow::anyhow;
+use griffin_core::{
+    checks_interface::babbage_tx_to_cbor,
+    pallas_codec::minicbor,
+    pallas_primitives::babbage::{Tx as PallasTransaction, VKeyWitness as PallasVKeyWitness},
+    types::{Input, Output, Transaction, VKeyWitness},
+};
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Envelope type of transactions carrying some key witness.
+pub const TX_TYPE: &str = "Tx BabbageEra";
+
+/// Envelope type of transactions without key witnesses.
+pub const UNWITNESSED_TX_TYPE: &str = "Unwitnessed Tx BabbageEra";
+
+/// Envelope type of key witnesses.
+pub const WITNESS_TYPE: &str = "TxWitness BabbageEra";
+
+const TX_DESCRIPTION: &str = "Griffin transaction";
+
+const WITNESS_DESCRIPTION: &str = "Key Witness ShelleyEra";
+
+/// Tag of key witnesses (as opposed to bootstrap ones) in witness files.
+const KEY_WITNESS_TAG: u8 = 0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TextEnvelope {
+    #[serde(rename = "type")]
+    type_: String,
+    description: String,
+    cbor_hex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale_hex: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resolved_inputs: Vec<(Input, Output)>,
+}
+
+impl TextEnvelope {
+    fn read(contents: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(contents).map_err(|e| anyhow!("Invalid text envelope: {}", e))
+    }
+
+    fn is_transaction(&self) -> bool {
+        [TX_TYPE, UNWITNESSED_TX_TYPE, "Witnessed Tx BabbageEra"].contains(&self.type_.as_str())
+    }
+
+    fn is_witness(&self) -> bool {
+        self.type_.starts_with("TxWitness ")
+    }
+}
+
+/// A transaction together with the outputs it spends or references, as far
+/// as they are known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxFile {
+    pub transaction: Transaction,
+    pub resolved_inputs: Vec<(Input, Output)>,
+}
+
+impl TxFile {
+    /// Reads a transaction file, either a text envelope or a hex-encoded
+    /// SCALE transaction.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        if !contents.trim_start().starts_with('{') {
+            return Ok(Self {
+                transaction: decode_transaction(contents.trim())?,
+                resolved_inputs: Vec::new(),
+            });
+        }
+
+        Self::from_envelope(TextEnvelope::read(&contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_envelope()?)?;
+        std::fs::write(path, json + "\n")?;
+
+        Ok(())
+    }
+
+    /// The output spent or referenced at `input`, if known.
+    pub fn resolve(&self, input: &Input) -> Option<&Output> {
+        self.resolved_inputs
+            .iter()
+            .find_map(|(i, output)| (i == input).then_some(output))
+    }
+
+    fn to_envelope(&self) -> anyhow::Result<TextEnvelope> {
+        let witnessed = self
+            .transaction
+            .transaction_witness_set
+            .vkeywitness
+            .as_ref()
+            .is_some_and(|witnesses| !witnesses.is_empty());
+
+        Ok(TextEnvelope {
+            type_: String::from(if witnessed {
+                TX_TYPE
+            } else {
+                UNWITNESSED_TX_TYPE
+            }),
+            description: String::from(TX_DESCRIPTION),
+            cbor_hex: hex::encode(transaction_cbor(&self.transaction)?),
+            scale_hex: Some(hex::encode(self.transaction.encode())),
+            resolved_inputs: self.resolved_inputs.clone(),
+        })
+    }
+
+    fn from_envelope(envelope: TextEnvelope) -> anyhow::Result<Self> {
+        if !envelope.is_transaction() {
+            Err(anyhow!(
+                "Expected a transaction, found a text envelope of type {}.",
+                envelope.type_
+            ))?;
+        }
+        let scale_hex = envelope.scale_hex.ok_or(anyhow!(
+            "The transaction was not written by the Griffin wallet. \
+             Add signatures made by other tools as witness files instead."
+        ))?;
+        let transaction = decode_transaction(&scale_hex)?;
+        if hex::encode(transaction_cbor(&transaction)?) != envelope.cbor_hex.to_lowercase() {
+            Err(anyhow!(
+                "The CBOR of the transaction does not match its SCALE encoding."
+            ))?;
+        }
+
+        Ok(Self {
+            transaction,
+            resolved_inputs: envelope.resolved_inputs,
+        })
+    }
+}
+
+/// Reads the key witnesses in a file, which is either a witness file or a
+/// transaction file.
+pub fn read_witnesses(path: &Path) -> anyhow::Result<Vec<VKeyWitness>> {
+    let contents = std::fs::read_to_string(path)?;
+    if contents.trim_start().starts_with('{') {
+        let envelope = TextEnvelope::read(&contents)?;
+        if envelope.is_witness() {
+            return Ok(vec![witness_from_cbor(&hex::decode(
+                envelope.cbor_hex.trim(),
+            )?)?]);
+        }
+    }
+
+    Ok(TxFile::read(path)?
+        .transaction
+        .transaction_witness_set
+        .vkeywitness
+        .unwrap_or_default())
+}
+
+/// Writes a witness file holding `witness`.
+pub fn write_witness(path: &Path, witness: &VKeyWitness) -> anyhow::Result<()> {
+    let envelope = TextEnvelope {
+        type_: String::from(WITNESS_TYPE),
+        description: String::from(WITNESS_DESCRIPTION),
+        cbor_hex: hex::encode(witness_to_cbor(witness)?),
+        scale_hex: None,
+        resolved_inputs: Vec::new(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&envelope)? + "\n")?;
+
+    Ok(())
+}
+
+fn decode_transaction(tx_hex: &str) -> anyhow::Result<Transaction> {
+    Transaction::decode(&mut &hex::decode(tx_hex)?[..])
+        .map_err(|e| anyhow!("Invalid transaction: {}", e))
+}
+
+fn transaction_cbor(transaction: &Transaction) -> anyhow::Result<Vec<u8>> {
+    let pallas_tx = PallasTransaction::try_from(transaction.clone())
+        .map_err(|e| anyhow!("Invalid transaction: {:?}", e))?;
+
+    Ok(babbage_tx_to_cbor(&pallas_tx))
+}
+
+/// CBOR of a key witness, `[0, [vkey, signature]]`.
+fn witness_to_cbor(witness: &VKeyWitness) -> anyhow::Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    minicbor::encode(
+        (KEY_WITNESS_TAG, PallasVKeyWitness::from(witness.clone())),
+        &mut bytes,
+    )
+    .map_err(|e| anyhow!("Failed to encode witness: {}", e))?;
+
+    Ok(bytes)
+}
+
+fn witness_from_cbor(bytes: &[u8]) -> anyhow::Result<VKeyWitness> {
+    let (tag, witness): (u8, PallasVKeyWitness) =
+        minicbor::decode(bytes).map_err(|e| anyhow!("Invalid witness: {}", e))?;
+    if tag != KEY_WITNESS_TAG {
+        Err(anyhow!("Only key witnesses are supported."))?;
+    }
+
+    Ok(VKeyWitness::from(witness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use griffin_core::types::{address_from_hex, Value};
+    use sp_core::H256;
+
+    fn tx_file() -> TxFile {
+        let input = Input {
+            tx_hash: H256::repeat_byte(0x8a),
+            index: 0,
+        };
+        let output = Output::from((
+            address_from_hex("6101e6301758a6badfab05035cffc8e3438b3aff2a4edc6544b47329c4"),
+            Value::Coin(314),
+        ));
+        let mut transaction = Transaction::from((vec![input.clone()], vec![output.clone()]));
+        transaction.transaction_body.fee = 200_000;
+
+        TxFile {
+            transaction,
+            resolved_inputs: vec![(input, output)],
+        }
+    }
+
+    fn witness() -> VKeyWitness {
+        VKeyWitness::from((vec![0x7b; 32], vec![0xa4; 64]))
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "gpc-wallet-{name}-{}",
+            std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos(),
+        ))
+    }
+
+    #[test]
+    fn transaction_files() {
+        let path = temp_path("tx");
+        let mut file = tx_file();
+        file.write(&path).unwrap();
+        let envelope = TextEnvelope::read(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(envelope.type_, UNWITNESSED_TX_TYPE);
+        assert_eq!(TxFile::read(&path).unwrap(), file);
+
+        file.transaction.transaction_witness_set.vkeywitness = Some(vec![witness()]);
+        file.write(&path).unwrap();
+        let envelope = TextEnvelope::read(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(envelope.type_, TX_TYPE);
+        assert_eq!(read_witnesses(&path).unwrap(), vec![witness()]);
+
+        // Legacy files hold the hex-encoded transaction
+        std::fs::write(&path, hex::encode(file.transaction.encode())).unwrap();
+        assert_eq!(TxFile::read(&path).unwrap().transaction, file.transaction);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn tampered_transaction_files() {
+        let mut envelope = tx_file().to_envelope().unwrap();
+        envelope.cbor_hex.replace_range(..2, "00");
+        assert!(TxFile::from_envelope(envelope).is_err());
+
+        let mut envelope = tx_file().to_envelope().unwrap();
+        envelope.scale_hex = None;
+        assert!(TxFile::from_envelope(envelope).is_err());
+    }
+
+    #[test]
+    fn witness_files() {
+        // As written by `cardano-cli transaction witness`
+        assert_eq!(
+            hex::encode(witness_to_cbor(&witness()).unwrap()),
+            format!("82008258207b{}5840a4{}", "7b".repeat(31), "a4".repeat(63))
+        );
+
+        let path = temp_path("witness");
+        write_witness(&path, &witness()).unwrap();
+        assert_eq!(read_witnesses(&path).unwrap(), vec![witness()]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
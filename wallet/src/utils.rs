@@ -1,3 +1,5 @@
+use anyhow::anyhow;
+use core::str::FromStr;
 use griffin_core::{
     h224::H224,
     types::{Address, Input, Value},
@@ -27,14 +29,9 @@ pub(crate) fn h224_from_string(s: &str) -> anyhow::Result<H224> {
     Ok(H224::from(bytes))
 }
 
-/// Parse a string into an Address that represents a public key
+/// Parse an address given in bech32, base58 or hex.
 pub(crate) fn address_from_string(s: &str) -> anyhow::Result<Address> {
-    let s = strip_0x_prefix(s);
-
-    let mut bytes: [u8; 29] = [0; 29];
-    hex::decode_to_slice(s, &mut bytes as &mut [u8])
-        .map_err(|_| clap::Error::new(clap::error::ErrorKind::ValueValidation))?;
-    Ok(Address(Vec::from(bytes)))
+    Address::from_str(s).map_err(|e| anyhow!("Invalid address {s}: {e}"))
 }
 
 /// Parse an output ref from a string